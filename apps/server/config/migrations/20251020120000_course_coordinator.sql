@@ -0,0 +1,4 @@
+ALTER TABLE courses
+    ADD COLUMN IF NOT EXISTS coordinator_id UUID NULL REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS courses_coordinator_id_idx ON courses(coordinator_id) WHERE coordinator_id IS NOT NULL;
@@ -0,0 +1,19 @@
+DROP TYPE IF EXISTS supervisor_action CASCADE;
+CREATE TYPE supervisor_action AS ENUM (
+    'approve',
+    'decline',
+    'authorize',
+    'evaluate'
+);
+
+CREATE TABLE IF NOT EXISTS supervisor_tokens (
+    id UUID PRIMARY KEY,
+    practice_id UUID NOT NULL REFERENCES practices(id) ON DELETE CASCADE,
+    action supervisor_action NOT NULL,
+    token_hash TEXT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS supervisor_tokens_practice_id_idx ON supervisor_tokens(practice_id);
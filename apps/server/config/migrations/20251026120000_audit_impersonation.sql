@@ -0,0 +1,6 @@
+-- Acciones realizadas suplantando a otro usuario: `actor_id` conserva
+-- al administrador real y esta columna al usuario suplantado
+
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS impersonated_id UUID NULL;
+
+CREATE INDEX IF NOT EXISTS audit_log_impersonated_id_idx ON audit_log(impersonated_id);
@@ -0,0 +1,10 @@
+-- Los usuarios eliminados liberan su email y RUT para nuevas cuentas
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_active_idx ON users(email) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS users_rut_active_idx ON users(rut) WHERE deleted_at IS NULL;
+
+-- Cuentas cuyos datos personales fueron anonimizados de forma irreversible
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS purged_at TIMESTAMP WITH TIME ZONE NULL;
@@ -0,0 +1,57 @@
+-- Los RUT se almacenan en forma canónica: sin puntos, espacios ni ceros a la
+-- izquierda, con guion y el dígito verificador en mayúscula (12345678-K).
+-- Las cuentas activas que solo difieren en el formato del RUT no se normalizan
+-- (el índice único lo impediría): se informan y conservan su valor hasta que se
+-- fusionan, momento en que la cuenta que se conserva queda con el RUT canónico.
+
+DROP INDEX IF EXISTS users_rut_active_idx;
+
+CREATE TEMPORARY TABLE canonical_ruts AS
+SELECT
+    id,
+    deleted_at,
+    CASE
+        WHEN stripped NOT LIKE '%-%' AND LENGTH(stripped) > 1
+            THEN LEFT(stripped, -1) || '-' || RIGHT(stripped, 1)
+        ELSE stripped
+    END AS rut
+FROM (
+    SELECT
+        id,
+        deleted_at,
+        UPPER(REGEXP_REPLACE(REGEXP_REPLACE(rut, '[.[:space:]]', '', 'g'), '^0+', '')) AS stripped
+    FROM users
+    WHERE purged_at IS NULL
+) AS stripped_ruts;
+
+CREATE TEMPORARY TABLE rut_conflicts AS
+SELECT c.id
+FROM canonical_ruts c
+WHERE c.deleted_at IS NULL
+  AND EXISTS (
+      SELECT 1 FROM canonical_ruts o
+      WHERE o.deleted_at IS NULL AND o.rut = c.rut AND o.id <> c.id
+  );
+
+UPDATE users u
+SET rut = c.rut
+FROM canonical_ruts c
+WHERE u.id = c.id
+  AND u.rut <> c.rut
+  AND c.id NOT IN (SELECT id FROM rut_conflicts);
+
+DO $$
+DECLARE
+    conflicts INTEGER;
+BEGIN
+    SELECT COUNT(*) INTO conflicts FROM rut_conflicts;
+
+    IF conflicts > 0 THEN
+        RAISE WARNING '% cuenta(s) activas comparten RUT con otro formato y no se normalizaron, deben fusionarse', conflicts;
+    END IF;
+END $$;
+
+DROP TABLE canonical_ruts;
+DROP TABLE rut_conflicts;
+
+CREATE UNIQUE INDEX users_rut_active_idx ON users(rut) WHERE deleted_at IS NULL;
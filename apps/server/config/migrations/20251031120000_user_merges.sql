@@ -0,0 +1,12 @@
+-- Fusiones de cuentas duplicadas. El registro de auditoría es inmutable, por lo
+-- que sus referencias a la cuenta absorbida se resuelven a través de esta tabla.
+
+CREATE TABLE IF NOT EXISTS user_merges (
+    id UUID PRIMARY KEY,
+    source_id UUID NOT NULL UNIQUE REFERENCES users(id),
+    target_id UUID NOT NULL REFERENCES users(id),
+    report JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS user_merges_target_id_idx ON user_merges(target_id);
@@ -0,0 +1,3 @@
+-- Nota final ponderada de cada inscripción, calculada al cerrar el curso
+
+ALTER TABLE enrollments ADD COLUMN IF NOT EXISTS final_grade DOUBLE PRECISION NULL;
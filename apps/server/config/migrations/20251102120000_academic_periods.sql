@@ -0,0 +1,18 @@
+-- Periodos académicos (semestres) con sus plazos de inscripción y de notas
+
+CREATE TABLE IF NOT EXISTS academic_periods (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    year INTEGER NOT NULL,
+    term INTEGER NOT NULL CHECK (term IN (1, 2)),
+    start_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    enrollment_deadline TIMESTAMP WITH TIME ZONE NOT NULL,
+    grades_deadline TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    UNIQUE(year, term)
+);
+
+ALTER TABLE courses
+    ADD COLUMN IF NOT EXISTS period_id UUID NULL REFERENCES academic_periods(id) ON DELETE RESTRICT;
+
+CREATE INDEX IF NOT EXISTS courses_period_id_idx ON courses(period_id) WHERE period_id IS NOT NULL;
@@ -0,0 +1,28 @@
+-- Equipo docente de cada curso. El profesor a cargo y el coordinador se
+-- mantienen también en `courses` y se sincronizan al guardar el curso.
+
+DROP TYPE IF EXISTS course_staff_role CASCADE;
+CREATE TYPE course_staff_role AS ENUM (
+    'lead',
+    'co_teacher',
+    'assistant',
+    'coordinator'
+);
+
+CREATE TABLE IF NOT EXISTS course_staff (
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    role course_staff_role NOT NULL,
+
+    PRIMARY KEY(course_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS course_staff_user_id_idx ON course_staff(user_id);
+
+INSERT INTO course_staff (course_id, user_id, role)
+SELECT id, teacher_id, 'lead' FROM courses
+ON CONFLICT DO NOTHING;
+
+INSERT INTO course_staff (course_id, user_id, role)
+SELECT id, coordinator_id, 'coordinator' FROM courses WHERE coordinator_id IS NOT NULL
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,19 @@
+-- Al purgar un usuario se eliminan sus datos personales de los registros
+-- anteriores. Es la única modificación que admite el registro: debe activarse
+-- explícitamente en la transacción (`audit_log.scrub`) y solo puede quitar
+-- campos de los snapshots, sin alterar el resto de la fila.
+
+CREATE OR REPLACE FUNCTION audit_log_immutable() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND current_setting('audit_log.scrub', true) = 'on'
+        AND (to_jsonb(NEW) - 'before' - 'after') = (to_jsonb(OLD) - 'before' - 'after')
+        AND (NEW.before IS NOT DISTINCT FROM OLD.before OR OLD.before @> NEW.before)
+        AND (NEW.after IS NOT DISTINCT FROM OLD.after OR OLD.after @> NEW.after)
+    THEN
+        RETURN NEW;
+    END IF;
+
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,38 @@
+use serde_json::json;
+use sword::prelude::*;
+
+use crate::{
+    audit::{AuditEntryResponse, AuditService, GetAuditLogQueryDto},
+    auth::RoleMiddleware,
+    container::AppModule,
+    users::Role,
+};
+
+#[controller("/audit")]
+pub struct AuditController;
+
+#[routes]
+impl AuditController {
+    /// Consultar el registro de auditoría, del más reciente al más antiguo.
+    /// Filtrable por autor, acción, tipo e id de entidad y rango de fechas.
+
+    #[get("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn find_all(ctx: Context) -> HttpResult<HttpResponse> {
+        let query = ctx.validated_query::<GetAuditLogQueryDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuditService>()?;
+
+        let data = service.get_all(query.into()).await?;
+        let entries = data.items.into_iter().map(AuditEntryResponse::from).collect::<Vec<_>>();
+
+        let json = json!({
+            "entries": entries,
+            "currentPage": data.current_page,
+            "totalPages": data.total_pages,
+            "hasNext": data.has_next,
+            "hasPrevious": data.has_previous,
+        });
+
+        Ok(HttpResponse::Ok().data(json))
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::audit::{AuditEntry, AuditFilter};
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> GET AUDIT LOG QUERY <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_audit_log_range"))]
+pub struct GetAuditLogQueryDto {
+    pub actor_id: Option<Uuid>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "La acción debe tener entre 1 y 100 caracteres."
+    ))]
+    pub action: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "El tipo de entidad debe tener entre 1 y 100 caracteres."
+    ))]
+    pub entity_type: Option<String>,
+
+    pub entity_id: Option<Uuid>,
+
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, message = "La página debe ser mayor o igual a 1."))]
+    pub page: Option<usize>,
+}
+
+fn validate_audit_log_range(dto: &GetAuditLogQueryDto) -> Result<(), ValidationError> {
+    if let (Some(from), Some(to)) = (dto.from, dto.to) {
+        if from > to {
+            return Err(ValidationError::new(
+                "La fecha de inicio debe ser anterior a la de término",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+impl From<GetAuditLogQueryDto> for AuditFilter {
+    fn from(dto: GetAuditLogQueryDto) -> Self {
+        AuditFilter {
+            page: dto.page.unwrap_or(1) as u64,
+            actor_id: dto.actor_id,
+            action: dto.action,
+            entity_type: dto.entity_type,
+            entity_id: dto.entity_id,
+            from: dto.from,
+            to: dto.to,
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> AUDIT ENTRY RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntryResponse {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub impersonated_id: Option<Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+    fn from(entry: AuditEntry) -> Self {
+        AuditEntryResponse {
+            id: entry.id,
+            actor_id: entry.actor_id,
+            impersonated_id: entry.impersonated_id,
+            action: entry.action,
+            entity_type: entry.entity_type,
+            entity_id: entry.entity_id,
+            before: entry.before.map(|before| before.0),
+            after: entry.after.map(|after| after.0),
+            ip: entry.ip,
+            created_at: entry.created_at,
+        }
+    }
+}
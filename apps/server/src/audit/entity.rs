@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use sea_query::Iden;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{types::Json, FromRow};
+use uuid::Uuid;
+
+use crate::{auth::ClientInfo, users::User};
+
+/// Campos que nunca se registran en los snapshots de auditoría
+const REDACTED_FIELDS: [&str; 3] = ["password", "tokenHash", "token_hash"];
+
+/// Datos personales que se eliminan de los registros de un usuario al purgarlo
+pub const PERSONAL_FIELDS: [&str; 3] = ["rut", "name", "email"];
+
+/// Registro inmutable de una operación que modificó datos del sistema.
+/// `before` y `after` contienen sólo los campos que cambiaron.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub impersonated_id: Option<Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub before: Option<Json<Value>>,
+    pub after: Option<Json<Value>>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        ctx: &AuditContext,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<Uuid>,
+    ) -> Self {
+        AuditEntry {
+            id: Uuid::new_v4(),
+            actor_id: ctx.actor_id,
+            impersonated_id: ctx.impersonated_id,
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            before: None,
+            after: None,
+            ip: ctx.ip.clone(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Registra el estado previo y posterior de la entidad, conservando
+    /// únicamente los campos modificados.
+    pub fn diff<T: Serialize>(mut self, before: Option<&T>, after: Option<&T>) -> Self {
+        let before = before.map(snapshot);
+        let after = after.map(snapshot);
+
+        let (before, after) = match (before, after) {
+            (Some(Value::Object(before)), Some(Value::Object(after))) => {
+                let keys = before
+                    .keys()
+                    .chain(after.keys())
+                    .filter(|key| before.get(*key) != after.get(*key))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let pick = |values: &Map<String, Value>| {
+                    keys.iter()
+                        .map(|key| (key.clone(), values.get(key).cloned().unwrap_or(Value::Null)))
+                        .collect::<Map<_, _>>()
+                };
+
+                (Some(Value::Object(pick(&before))), Some(Value::Object(pick(&after))))
+            }
+            (before, after) => (before, after),
+        };
+
+        self.before = before.map(Json);
+        self.after = after.map(Json);
+        self
+    }
+
+    pub fn before<T: Serialize>(self, before: &T) -> Self {
+        self.diff(Some(before), None)
+    }
+
+    pub fn after<T: Serialize>(self, after: &T) -> Self {
+        self.diff(None, Some(after))
+    }
+}
+
+fn snapshot<T: Serialize>(value: &T) -> Value {
+    let mut value = serde_json::to_value(value).unwrap_or(Value::Null);
+
+    if let Value::Object(fields) = &mut value {
+        fields.retain(|key, _| !REDACTED_FIELDS.contains(&key.as_str()));
+    }
+
+    value
+}
+
+/// Autor de una operación: el usuario autenticado, si lo hay, y la IP
+/// desde donde se realizó. Los supervisores externos actúan sin usuario.
+/// Durante una suplantación el autor es el administrador real y
+/// `impersonated_id` el usuario suplantado.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<Uuid>,
+    pub impersonated_id: Option<Uuid>,
+    pub ip: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(actor: Option<&User>, client: &ClientInfo) -> Self {
+        AuditContext {
+            actor_id: actor.map(|user| user.id),
+            impersonated_id: None,
+            ip: client.ip.clone(),
+        }
+    }
+
+    pub fn impersonated(impersonator_id: Uuid, user: &User, client: &ClientInfo) -> Self {
+        AuditContext {
+            actor_id: Some(impersonator_id),
+            impersonated_id: Some(user.id),
+            ip: client.ip.clone(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub enum AuditLog {
+    Table,
+    Id,
+    ActorId,
+    ImpersonatedId,
+    Action,
+    EntityType,
+    EntityId,
+    Before,
+    After,
+    Ip,
+    CreatedAt,
+}
+
+impl Iden for AuditLog {
+    fn unquoted(&self) -> &str {
+        match self {
+            AuditLog::Table => "audit_log",
+            AuditLog::Id => "id",
+            AuditLog::ActorId => "actor_id",
+            AuditLog::ImpersonatedId => "impersonated_id",
+            AuditLog::Action => "action",
+            AuditLog::EntityType => "entity_type",
+            AuditLog::EntityId => "entity_id",
+            AuditLog::Before => "before",
+            AuditLog::After => "after",
+            AuditLog::Ip => "ip",
+            AuditLog::CreatedAt => "created_at",
+        }
+    }
+}
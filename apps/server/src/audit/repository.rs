@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_query::{Expr, ExprTrait, Order, PostgresQueryBuilder, Query, SelectStatement};
+use sea_query_sqlx::SqlxBinder;
+use shaku::{Component, Interface};
+use sqlx::{query_as_with as sqlx_query, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    audit::entity::{AuditEntry, AuditLog},
+    shared::{database::DatabaseConnection, entities::DEFAULT_PAGE_SIZE, errors::AppError},
+};
+
+#[derive(Component)]
+#[shaku(interface = AuditRepository)]
+pub struct PostgresAuditRepository {
+    #[shaku(inject)]
+    database_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub page: u64,
+    pub actor_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Los registros no se reescriben al fusionar cuentas: las búsquedas por una
+/// cuenta incluyen también las cuentas fusionadas en ella.
+const MERGED_ACTOR_CONDITION: &str =
+    "actor_id IN (SELECT source_id FROM user_merges WHERE target_id = ?)";
+const MERGED_ENTITY_CONDITION: &str =
+    "entity_id IN (SELECT source_id FROM user_merges WHERE target_id = ?)";
+
+impl AuditFilter {
+    fn apply(&self, query: &mut SelectStatement) {
+        query
+            .apply_if(self.actor_id, |q, value| {
+                q.and_where(
+                    Expr::col(AuditLog::ActorId)
+                        .eq(value)
+                        .or(Expr::cust_with_values(MERGED_ACTOR_CONDITION, [value])),
+                );
+            })
+            .apply_if(self.action.clone(), |q, value| {
+                q.and_where(Expr::col(AuditLog::Action).eq(value));
+            })
+            .apply_if(self.entity_type.clone(), |q, value| {
+                q.and_where(Expr::col(AuditLog::EntityType).eq(value));
+            })
+            .apply_if(self.entity_id, |q, value| {
+                q.and_where(
+                    Expr::col(AuditLog::EntityId)
+                        .eq(value)
+                        .or(Expr::cust_with_values(MERGED_ENTITY_CONDITION, [value])),
+                );
+            })
+            .apply_if(self.from, |q, value| {
+                q.and_where(Expr::col(AuditLog::CreatedAt).gte(value));
+            })
+            .apply_if(self.to, |q, value| {
+                q.and_where(Expr::col(AuditLog::CreatedAt).lte(value));
+            });
+    }
+}
+
+/// El registro sólo admite inserciones. La única excepción es `scrub`, que
+/// quita datos personales de los snapshots de un usuario purgado.
+#[async_trait]
+pub trait AuditRepository: Interface {
+    async fn find_many(&self, filter: AuditFilter) -> Result<Vec<AuditEntry>, AppError>;
+    async fn count(&self, filter: AuditFilter) -> Result<i64, AppError>;
+
+    async fn insert(&self, entry: AuditEntry) -> Result<AuditEntry, AppError>;
+
+    /// Elimina los campos indicados de los registros del usuario y de las
+    /// cuentas fusionadas en él.
+    async fn scrub(&self, user_id: &Uuid, fields: &[&str]) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl AuditRepository for PostgresAuditRepository {
+    async fn find_many(&self, filter: AuditFilter) -> Result<Vec<AuditEntry>, AppError> {
+        let mut query = Query::select().expr(Expr::cust("*")).from(AuditLog::Table).to_owned();
+
+        filter.apply(&mut query);
+
+        query.order_by(AuditLog::CreatedAt, Order::Desc);
+        query.limit(DEFAULT_PAGE_SIZE);
+        query.offset(filter.page.saturating_sub(1) * DEFAULT_PAGE_SIZE);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        let entries = sqlx_query::<Postgres, AuditEntry, _>(&sql, values)
+            .fetch_all(self.database_connection.get_pool())
+            .await?;
+
+        Ok(entries)
+    }
+
+    async fn count(&self, filter: AuditFilter) -> Result<i64, AppError> {
+        let mut query = Query::select()
+            .expr(Expr::count(Expr::col(AuditLog::Id)))
+            .from(AuditLog::Table)
+            .to_owned();
+
+        filter.apply(&mut query);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        let count: (i64,) = sqlx::query_as_with(&sql, values)
+            .fetch_one(self.database_connection.get_pool())
+            .await?;
+
+        Ok(count.0)
+    }
+
+    async fn insert(&self, entry: AuditEntry) -> Result<AuditEntry, AppError> {
+        let insert_query = r#"
+            INSERT INTO audit_log (id, actor_id, impersonated_id, action, entity_type, entity_id, before, after, ip, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+        "#;
+
+        let entry = sqlx::query_as::<_, AuditEntry>(insert_query)
+            .bind(entry.id)
+            .bind(entry.actor_id)
+            .bind(entry.impersonated_id)
+            .bind(entry.action)
+            .bind(entry.entity_type)
+            .bind(entry.entity_id)
+            .bind(entry.before)
+            .bind(entry.after)
+            .bind(entry.ip)
+            .bind(entry.created_at)
+            .fetch_one(self.database_connection.get_pool())
+            .await?;
+
+        Ok(entry)
+    }
+
+    async fn scrub(&self, user_id: &Uuid, fields: &[&str]) -> Result<(), AppError> {
+        let mut tx = self.database_connection.get_pool().begin().await?;
+
+        sqlx::query("SET LOCAL audit_log.scrub = 'on'").execute(&mut *tx).await?;
+
+        let query = r#"
+            UPDATE audit_log
+            SET before = before - $2::text[], after = after - $2::text[]
+            WHERE entity_type = 'user'
+              AND (entity_id = $1 OR entity_id IN (SELECT source_id FROM user_merges WHERE target_id = $1))
+              AND (before ?| $2::text[] OR after ?| $2::text[])
+        "#;
+
+        sqlx::query(query).bind(user_id).bind(fields).execute(&mut *tx).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use shaku::{Component, Interface};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    audit::{AuditEntry, AuditFilter, AuditRepository, PERSONAL_FIELDS},
+    shared::{
+        entities::{Pagination, DEFAULT_PAGE_SIZE},
+        errors::AppError,
+    },
+};
+
+#[derive(Component)]
+#[shaku(interface = AuditService)]
+pub struct AuditServiceImpl {
+    #[shaku(inject)]
+    entries: Arc<dyn AuditRepository>,
+}
+
+#[async_trait]
+pub trait AuditService: Interface {
+    async fn get_all(&self, filter: AuditFilter) -> Result<Pagination<AuditEntry>, AppError>;
+
+    async fn record(&self, entry: AuditEntry) -> Result<(), AppError>;
+
+    /// Quita los datos personales de los registros anteriores de un usuario purgado.
+    async fn scrub_user(&self, user_id: &Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl AuditService for AuditServiceImpl {
+    async fn get_all(&self, filter: AuditFilter) -> Result<Pagination<AuditEntry>, AppError> {
+        let (items, total) = tokio::try_join!(
+            self.entries.find_many(filter.clone()),
+            self.entries.count(filter.clone())
+        )?;
+
+        Ok(Pagination::new(items, filter.page, DEFAULT_PAGE_SIZE, total))
+    }
+
+    async fn record(&self, entry: AuditEntry) -> Result<(), AppError> {
+        self.entries.insert(entry).await?;
+
+        Ok(())
+    }
+
+    async fn scrub_user(&self, user_id: &Uuid) -> Result<(), AppError> {
+        self.entries.scrub(user_id, &PERSONAL_FIELDS).await
+    }
+}
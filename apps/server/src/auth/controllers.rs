@@ -0,0 +1,269 @@
+use serde_json::json;
+use sword::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        ensure_not_impersonating, AuthResponse, AuthService, ChangePasswordDto, ForgotPasswordDto,
+        LoginDto, LoginResponse, PasswordChangeMiddleware, RefreshSessionDto, ResetPasswordDto,
+        RoleMiddleware, SessionContext, SessionMiddleware, SessionResponse,
+        TwoFactorActivationResponse, TwoFactorCodeDto, TwoFactorLoginDto, TwoFactorSetupMiddleware,
+        TwoFactorSetupResponse,
+    },
+    container::AppModule,
+    users::{Role, UserResponse},
+};
+
+#[controller("/auth")]
+pub struct AuthController;
+
+#[routes]
+impl AuthController {
+    /// Iniciar sesión con email y contraseña, retorna el par de tokens
+    /// de sesión (corta duración) y de refresco (rotativo).
+    /// Los intentos fallidos por email e IP aplican espera exponencial
+    /// y bloquean la cuenta al superar el umbral configurado.
+    /// Si la cuenta tiene activada la verificación en dos pasos, retorna en
+    /// cambio un token de desafío para `/auth/login/two-factor`.
+
+    #[post("/login")]
+    async fn login(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<LoginDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let outcome = service.login(input, ctx.client_info()).await?;
+
+        Ok(HttpResponse::Ok().data(LoginResponse::from(outcome)))
+    }
+
+    /// Completar el login con el código TOTP o un código de recuperación.
+    /// Los códigos inválidos cuentan como intentos fallidos de login.
+
+    #[post("/login/two-factor")]
+    async fn login_two_factor(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<TwoFactorLoginDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let session = service.login_two_factor(input, ctx.client_info()).await?;
+
+        Ok(HttpResponse::Ok().data(AuthResponse::from(session)))
+    }
+
+    /// Rotar el refresh token, invalidando el anterior y emitiendo
+    /// un nuevo par de tokens para la misma sesión.
+
+    #[post("/refresh")]
+    async fn refresh(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<RefreshSessionDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let session = service.refresh(&input.refresh_token).await?;
+
+        Ok(HttpResponse::Ok().data(AuthResponse::from(session)))
+    }
+
+    /// Cerrar sesión revocando todos los refresh tokens de la sesión.
+
+    #[post("/logout")]
+    async fn logout(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<RefreshSessionDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.logout(&input.refresh_token).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Solicitar la recuperación de contraseña. Si el email pertenece a una
+    /// cuenta activa se envía un enlace de un solo uso con expiración.
+
+    #[post("/password/forgot")]
+    async fn forgot_password(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<ForgotPasswordDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.forgot_password(input).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Restablecer la contraseña usando el token recibido por correo.
+    /// Cierra todas las sesiones abiertas del usuario.
+
+    #[post("/password/reset")]
+    async fn reset_password(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<ResetPasswordDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.reset_password(input).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Cambiar la contraseña del usuario autenticado. Es la única ruta disponible
+    /// para cuentas con contraseña generada por el sistema; al completarse se
+    /// cierran las demás sesiones y se emite un nuevo par de tokens.
+    /// No disponible durante una suplantación.
+
+    #[post("/password/change")]
+    #[middleware(PasswordChangeMiddleware)]
+    async fn change_password(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let user = ctx.session_user()?;
+        let input = ctx.validated_body::<ChangePasswordDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let session = service.change_password(user, input, ctx.client_info()).await?;
+
+        Ok(HttpResponse::Ok().data(AuthResponse::from(session)))
+    }
+
+    /// Obtener el usuario asociado a la sesión actual.
+
+    #[get("/session")]
+    #[middleware(SessionMiddleware)]
+    async fn session(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+
+        Ok(HttpResponse::Ok().data(UserResponse::from(user)))
+    }
+
+    /// Listar las sesiones activas del usuario, indicando cuál es la actual.
+
+    #[get("/sessions")]
+    #[middleware(SessionMiddleware)]
+    async fn sessions(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let current = ctx.current_session()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let sessions = service
+            .list_sessions(&user.id)
+            .await?
+            .into_iter()
+            .map(|session| SessionResponse::new(session, &current.id))
+            .collect::<Vec<_>>();
+
+        Ok(HttpResponse::Ok().data(sessions))
+    }
+
+    /// Revocar una de las sesiones del usuario, cerrándola en ese dispositivo.
+    /// No disponible durante una suplantación.
+
+    #[delete("/sessions/{id}")]
+    #[middleware(SessionMiddleware)]
+    async fn revoke_session(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let id = ctx.param::<Uuid>("id")?;
+        let user = ctx.session_user()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.revoke_session(&user.id, &id).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Abrir una sesión que actúa como otro usuario (no administrador) para
+    /// ver la plataforma tal como la ve. La sesión no posee refresh token,
+    /// sus respuestas incluyen el header `X-Impersonated-By` y queda
+    /// registrada en la auditoría junto al administrador real.
+
+    #[post("/impersonate/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn impersonate(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let admin = ctx.session_user()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let session = service
+            .impersonate(&admin, &id, ctx.client_info(), &ctx.audit_context())
+            .await?;
+
+        Ok(HttpResponse::Ok().data(AuthResponse::from(session)))
+    }
+
+    /// Terminar la suplantación en curso, invalidando su sesión.
+
+    #[delete("/impersonate")]
+    #[middleware(SessionMiddleware)]
+    async fn stop_impersonation(ctx: Context) -> HttpResult<HttpResponse> {
+        let session = ctx.current_session()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.stop_impersonation(&session, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Iniciar la configuración de la verificación en dos pasos, retorna el
+    /// secreto y la URI `otpauth://` a mostrar como código QR.
+
+    #[post("/two-factor/setup")]
+    #[middleware(TwoFactorSetupMiddleware)]
+    async fn setup_two_factor(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let user = ctx.session_user()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let setup = service.setup_two_factor(&user).await?;
+
+        Ok(HttpResponse::Ok().data(TwoFactorSetupResponse::from(setup)))
+    }
+
+    /// Confirmar la configuración con un código de la aplicación autenticadora.
+    /// Cierra las demás sesiones y retorna una nueva junto a los códigos de
+    /// recuperación, que no vuelven a mostrarse.
+
+    #[post("/two-factor/confirm")]
+    #[middleware(TwoFactorSetupMiddleware)]
+    async fn confirm_two_factor(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let user = ctx.session_user()?;
+        let input = ctx.validated_body::<TwoFactorCodeDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let activation = service
+            .confirm_two_factor(user, &input.code, ctx.client_info(), &ctx.audit_context())
+            .await?;
+
+        Ok(HttpResponse::Ok().data(TwoFactorActivationResponse::from(activation)))
+    }
+
+    /// Desactivar la verificación en dos pasos, salvo que sea obligatoria para el rol.
+
+    #[post("/two-factor/disable")]
+    #[middleware(SessionMiddleware)]
+    async fn disable_two_factor(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let user = ctx.session_user()?;
+        let input = ctx.validated_body::<TwoFactorCodeDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        service.disable_two_factor(&user, &input.code, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    /// Reemplazar los códigos de recuperación por otros nuevos.
+
+    #[post("/two-factor/recovery-codes")]
+    #[middleware(SessionMiddleware)]
+    async fn regenerate_recovery_codes(ctx: Context) -> HttpResult<HttpResponse> {
+        ensure_not_impersonating(&ctx.current_session()?)?;
+
+        let user = ctx.session_user()?;
+        let input = ctx.validated_body::<TwoFactorCodeDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+        let recovery_codes = service
+            .regenerate_recovery_codes(&user, &input.code, &ctx.audit_context())
+            .await?;
+
+        Ok(HttpResponse::Ok().data(json!({ "recoveryCodes": recovery_codes })))
+    }
+}
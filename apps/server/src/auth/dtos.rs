@@ -0,0 +1,271 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::{AuthSession, LoginOutcome, Session, TwoFactorActivation, TwoFactorSetup},
+    shared::services::jwt::TokenScope,
+    users::{password_schema, UserResponse},
+};
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> LOGIN DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+pub struct LoginDto {
+    #[validate(email(message = "El email debe ser válido."))]
+    pub email: String,
+
+    #[validate(length(min = 1, max = 100, message = "La contraseña es obligatoria."))]
+    pub password: String,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> TWO FACTOR LOGIN DTO <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Segundo paso del login: el token de desafío recibido al validar la
+/// contraseña junto a un código TOTP o, en su defecto, un código de recuperación.
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_two_factor_login"))]
+pub struct TwoFactorLoginDto {
+    #[validate(length(min = 1, message = "El token de desafío es obligatorio."))]
+    pub challenge_token: String,
+
+    #[validate(length(equal = 6, message = "El código debe tener 6 dígitos."))]
+    pub code: Option<String>,
+
+    #[validate(length(min = 1, max = 32, message = "El código de recuperación es inválido."))]
+    pub recovery_code: Option<String>,
+}
+
+fn validate_two_factor_login(dto: &TwoFactorLoginDto) -> Result<(), ValidationError> {
+    if dto.code.is_some() == dto.recovery_code.is_some() {
+        return Err(ValidationError::new(
+            "Debe indicar un código de verificación o un código de recuperación",
+        ));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> TWO FACTOR CODE DTO <<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+pub struct TwoFactorCodeDto {
+    #[validate(length(equal = 6, message = "El código debe tener 6 dígitos."))]
+    pub code: String,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> REFRESH SESSION DTO <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshSessionDto {
+    #[validate(length(min = 1, message = "El token de refresco es obligatorio."))]
+    pub refresh_token: String,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> FORGOT PASSWORD DTO <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+pub struct ForgotPasswordDto {
+    #[validate(email(message = "El email debe ser válido."))]
+    pub email: String,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> RESET PASSWORD DTO <<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_reset_password_pairs"))]
+pub struct ResetPasswordDto {
+    #[validate(length(min = 1, message = "El token de recuperación es obligatorio."))]
+    pub token: String,
+
+    #[validate(custom(function = "password_schema"))]
+    pub password: String,
+
+    #[validate(custom(function = "password_schema"))]
+    pub confirm_password: String,
+}
+
+fn validate_reset_password_pairs(dto: &ResetPasswordDto) -> Result<(), ValidationError> {
+    if dto.password != dto.confirm_password {
+        return Err(ValidationError::new("Passwords must match"));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> CHANGE PASSWORD DTO <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_change_password_pairs"))]
+pub struct ChangePasswordDto {
+    #[validate(length(min = 1, max = 100, message = "La contraseña actual es obligatoria."))]
+    pub current_password: String,
+
+    #[validate(custom(function = "password_schema"))]
+    pub password: String,
+
+    #[validate(custom(function = "password_schema"))]
+    pub confirm_password: String,
+}
+
+fn validate_change_password_pairs(dto: &ChangePasswordDto) -> Result<(), ValidationError> {
+    if dto.password != dto.confirm_password {
+        return Err(ValidationError::new("Passwords must match"));
+    }
+
+    if dto.password == dto.current_password {
+        return Err(ValidationError::new("La nueva contraseña debe ser distinta a la actual"));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> AUTH RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthResponse {
+    pub session_token: String,
+    pub refresh_token: Option<String>,
+    pub must_change_password: bool,
+    pub two_factor_setup_required: bool,
+    pub impersonated_by: Option<Uuid>,
+    pub user: UserResponse,
+}
+
+impl From<AuthSession> for AuthResponse {
+    fn from(session: AuthSession) -> Self {
+        AuthResponse {
+            session_token: session.session_token,
+            refresh_token: session.refresh_token,
+            must_change_password: session.user.must_change_password,
+            two_factor_setup_required: session.scope == TokenScope::TwoFactorSetup,
+            impersonated_by: session.impersonator_id,
+            user: UserResponse::from(session.user),
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> LOGIN RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Respuesta del login: la sesión emitida o, si la cuenta tiene activada la
+/// verificación en dos pasos, el token de desafío para completar el segundo paso.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+
+    #[serde(rename_all = "camelCase")]
+    TwoFactorRequired {
+        two_factor_required: bool,
+        challenge_token: String,
+    },
+}
+
+impl From<LoginOutcome> for LoginResponse {
+    fn from(outcome: LoginOutcome) -> Self {
+        match outcome {
+            LoginOutcome::Authenticated(session) => {
+                LoginResponse::Authenticated(AuthResponse::from(session))
+            }
+            LoginOutcome::TwoFactorRequired { challenge_token } => {
+                LoginResponse::TwoFactorRequired {
+                    two_factor_required: true,
+                    challenge_token,
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> TWO FACTOR RESPONSES <<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+impl From<TwoFactorSetup> for TwoFactorSetupResponse {
+    fn from(setup: TwoFactorSetup) -> Self {
+        TwoFactorSetupResponse {
+            secret: setup.secret,
+            provisioning_uri: setup.provisioning_uri,
+        }
+    }
+}
+
+/// Los códigos de recuperación solo se muestran una vez, al generarse.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorActivationResponse {
+    pub recovery_codes: Vec<String>,
+
+    #[serde(flatten)]
+    pub session: AuthResponse,
+}
+
+impl From<TwoFactorActivation> for TwoFactorActivationResponse {
+    fn from(activation: TwoFactorActivation) -> Self {
+        TwoFactorActivationResponse {
+            recovery_codes: activation.recovery_codes,
+            session: AuthResponse::from(activation.session),
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> SESSION RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub impersonated_by: Option<Uuid>,
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: Session, current_id: &Uuid) -> Self {
+        SessionResponse {
+            current: session.id == *current_id,
+            id: session.id,
+            device: session.device,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            impersonated_by: session.impersonator_id,
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_query::Iden;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RefreshToken {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[allow(dead_code)]
+pub enum RefreshTokens {
+    Table,
+    Id,
+    UserId,
+    SessionId,
+    ExpiresAt,
+    RevokedAt,
+    CreatedAt,
+}
+
+impl Iden for RefreshTokens {
+    fn unquoted(&self) -> &str {
+        match self {
+            RefreshTokens::Table => "refresh_tokens",
+            RefreshTokens::Id => "id",
+            RefreshTokens::UserId => "user_id",
+            RefreshTokens::SessionId => "session_id",
+            RefreshTokens::ExpiresAt => "expires_at",
+            RefreshTokens::RevokedAt => "revoked_at",
+            RefreshTokens::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Token de recuperación de contraseña. Solo se almacena el hash del secreto
+/// enviado por correo, el identificador viaja junto a él como `<id>.<secreto>`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordResetToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[allow(dead_code)]
+pub enum PasswordResetTokens {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
+
+impl Iden for PasswordResetTokens {
+    fn unquoted(&self) -> &str {
+        match self {
+            PasswordResetTokens::Table => "password_reset_tokens",
+            PasswordResetTokens::Id => "id",
+            PasswordResetTokens::UserId => "user_id",
+            PasswordResetTokens::TokenHash => "token_hash",
+            PasswordResetTokens::ExpiresAt => "expires_at",
+            PasswordResetTokens::UsedAt => "used_at",
+            PasswordResetTokens::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Contador de intentos fallidos de inicio de sesión para una clave,
+/// ya sea un email (`email:<email>`) o una IP de cliente (`ip:<ip>`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginAttempt {
+    pub key: String,
+    pub failures: i32,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub locked_at: Option<DateTime<Utc>>,
+}
+
+impl LoginAttempt {
+    pub fn new(key: String) -> Self {
+        LoginAttempt {
+            key,
+            failures: 0,
+            last_failed_at: None,
+            locked_at: None,
+        }
+    }
+
+    pub fn email_key(email: &str) -> String {
+        format!("email:{}", email.trim().to_lowercase())
+    }
+
+    pub fn ip_key(ip: &str) -> String {
+        format!("ip:{ip}")
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_at.is_some()
+    }
+
+    /// Momento a partir del cual se acepta un nuevo intento. Tras los intentos
+    /// gratuitos la espera se duplica con cada fallo, hasta `max_ms`.
+    pub fn retry_at(&self, free_attempts: i32, base_ms: u64, max_ms: u64) -> Option<DateTime<Utc>> {
+        let last_failed_at = self.last_failed_at?;
+        let exceeded = self.failures - free_attempts;
+
+        if exceeded <= 0 {
+            return None;
+        }
+
+        let factor = 2u64.saturating_pow(exceeded as u32 - 1);
+        let wait_ms = base_ms.saturating_mul(factor).min(max_ms);
+
+        Some(last_failed_at + Duration::milliseconds(wait_ms as i64))
+    }
+}
+
+#[allow(dead_code)]
+pub enum LoginAttempts {
+    Table,
+    Key,
+    Failures,
+    LastFailedAt,
+    LockedAt,
+}
+
+impl Iden for LoginAttempts {
+    fn unquoted(&self) -> &str {
+        match self {
+            LoginAttempts::Table => "login_attempts",
+            LoginAttempts::Key => "key",
+            LoginAttempts::Failures => "failures",
+            LoginAttempts::LastFailedAt => "last_failed_at",
+            LoginAttempts::LockedAt => "locked_at",
+        }
+    }
+}
+
+/// Sesión abierta de un usuario. Se almacena en el `SessionStore` y su id
+/// viaja en los tokens (`sid`), por lo que eliminarla invalida ambos tokens.
+/// Las sesiones de suplantación registran al administrador que las abrió.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub impersonator_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn is_impersonated(&self) -> bool {
+        self.impersonator_id.is_some()
+    }
+}
+
+/// Segundo factor TOTP de un usuario. Queda pendiente hasta que el usuario
+/// confirma un primer código generado por su aplicación autenticadora.
+/// `last_used_step` evita que un mismo código se utilice dos veces.
+#[derive(Debug, Clone, FromRow)]
+pub struct TwoFactor {
+    pub user_id: Uuid,
+    pub secret: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TwoFactor {
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+#[allow(dead_code)]
+pub enum TwoFactors {
+    Table,
+    UserId,
+    Secret,
+    ConfirmedAt,
+    LastUsedStep,
+    CreatedAt,
+}
+
+impl Iden for TwoFactors {
+    fn unquoted(&self) -> &str {
+        match self {
+            TwoFactors::Table => "user_two_factor",
+            TwoFactors::UserId => "user_id",
+            TwoFactors::Secret => "secret",
+            TwoFactors::ConfirmedAt => "confirmed_at",
+            TwoFactors::LastUsedStep => "last_used_step",
+            TwoFactors::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Código de recuperación de un solo uso, alternativo al código TOTP
+/// cuando el usuario pierde su dispositivo. Solo se almacena su hash.
+#[derive(Debug, Clone, FromRow)]
+pub struct RecoveryCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code_hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RecoveryCode {
+    /// Los códigos se comparan sin guiones ni mayúsculas.
+    pub fn normalize(code: &str) -> String {
+        code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+#[allow(dead_code)]
+pub enum RecoveryCodes {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedAt,
+}
+
+impl Iden for RecoveryCodes {
+    fn unquoted(&self) -> &str {
+        match self {
+            RecoveryCodes::Table => "two_factor_recovery_codes",
+            RecoveryCodes::Id => "id",
+            RecoveryCodes::UserId => "user_id",
+            RecoveryCodes::CodeHash => "code_hash",
+            RecoveryCodes::UsedAt => "used_at",
+            RecoveryCodes::CreatedAt => "created_at",
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::net::SocketAddr;
+
+use axum::{extract::ConnectInfo, http::HeaderValue, response::Response};
+use sword::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    audit::AuditContext,
+    auth::{AuthService, ClientInfo, Session},
+    container::AppModule,
+    shared::errors::AppError,
+    users::{Role, User},
+};
+
+/// Valida el token de sesión enviado en el header `Authorization: Bearer <token>`
+/// y deja el usuario autenticado y su sesión disponibles en las extensiones del contexto.
+
+pub struct SessionMiddleware;
+
+impl Middleware for SessionMiddleware {
+    async fn handle(mut ctx: Context, next: Next) -> MiddlewareResult {
+        let (user, session) = authenticate(&ctx).await?;
+
+        let impersonator_id = session.impersonator_id;
+
+        ctx.extensions.insert::<User>(user);
+        ctx.extensions.insert::<Session>(session);
+
+        let response = next!(ctx, next)?;
+
+        Ok(flag_impersonation(response, impersonator_id))
+    }
+}
+
+/// Igual que `SessionMiddleware`, pero además exige que el usuario posea
+/// al menos uno de los roles configurados en la ruta:
+///
+/// `#[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]`
+
+pub struct RoleMiddleware;
+
+impl MiddlewareWithConfig<&'static [Role]> for RoleMiddleware {
+    async fn handle(roles: &'static [Role], mut ctx: Context, next: Next) -> MiddlewareResult {
+        let (user, session) = authenticate(&ctx).await?;
+
+        if !user.has_any_role(roles) {
+            let message = "No tienes permisos para realizar esta acción".to_string();
+            return Err(AppError::Forbidden(message).into());
+        }
+
+        let impersonator_id = session.impersonator_id;
+
+        ctx.extensions.insert::<User>(user);
+        ctx.extensions.insert::<Session>(session);
+
+        let response = next!(ctx, next)?;
+
+        Ok(flag_impersonation(response, impersonator_id))
+    }
+}
+
+/// Acepta además los tokens restringidos emitidos a cuentas que deben
+/// cambiar su contraseña. Usado únicamente por la ruta de cambio de contraseña.
+
+pub struct PasswordChangeMiddleware;
+
+impl Middleware for PasswordChangeMiddleware {
+    async fn handle(mut ctx: Context, next: Next) -> MiddlewareResult {
+        let Some(token) = bearer_token(&ctx) else {
+            return Err(AppError::Unauthorized("Sesión no encontrada".to_string()).into());
+        };
+
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+        let (user, session) = service.authenticate_password_change(&token).await?;
+
+        let impersonator_id = session.impersonator_id;
+
+        ctx.extensions.insert::<User>(user);
+        ctx.extensions.insert::<Session>(session);
+
+        let response = next!(ctx, next)?;
+
+        Ok(flag_impersonation(response, impersonator_id))
+    }
+}
+
+/// Acepta además los tokens restringidos emitidos a cuentas cuyo rol exige
+/// verificación en dos pasos y aún no la configuran. Usado por las rutas de
+/// configuración del segundo factor.
+
+pub struct TwoFactorSetupMiddleware;
+
+impl Middleware for TwoFactorSetupMiddleware {
+    async fn handle(mut ctx: Context, next: Next) -> MiddlewareResult {
+        let Some(token) = bearer_token(&ctx) else {
+            return Err(AppError::Unauthorized("Sesión no encontrada".to_string()).into());
+        };
+
+        let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+        let (user, session) = service.authenticate_two_factor_setup(&token).await?;
+
+        let impersonator_id = session.impersonator_id;
+
+        ctx.extensions.insert::<User>(user);
+        ctx.extensions.insert::<Session>(session);
+
+        let response = next!(ctx, next)?;
+
+        Ok(flag_impersonation(response, impersonator_id))
+    }
+}
+
+/// Header presente en toda respuesta emitida a una sesión de suplantación,
+/// con el id del administrador que la abrió.
+pub const IMPERSONATED_BY_HEADER: &str = "x-impersonated-by";
+
+fn flag_impersonation(mut response: Response, impersonator_id: Option<Uuid>) -> Response {
+    if let Some(id) = impersonator_id.and_then(|id| HeaderValue::from_str(&id.to_string()).ok()) {
+        response.headers_mut().insert(IMPERSONATED_BY_HEADER, id);
+    }
+
+    response
+}
+
+async fn authenticate(ctx: &Context) -> Result<(User, Session), HttpResponse> {
+    let Some(token) = bearer_token(ctx) else {
+        return Err(AppError::Unauthorized("Sesión no encontrada".to_string()).into());
+    };
+
+    let service = ctx.get_dependency::<AppModule, dyn AuthService>()?;
+
+    Ok(service.authenticate(&token).await?)
+}
+
+fn bearer_token(ctx: &Context) -> Option<String> {
+    ctx.header("Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Extractor del usuario autenticado y su sesión para los handlers protegidos
+/// por `SessionMiddleware` o `RoleMiddleware`, junto a los datos del cliente.
+
+pub trait SessionContext {
+    fn session_user(&self) -> Result<User, HttpResponse>;
+    fn current_session(&self) -> Result<Session, HttpResponse>;
+
+    fn client_info(&self) -> ClientInfo;
+    fn audit_context(&self) -> AuditContext;
+}
+
+impl SessionContext for Context {
+    fn session_user(&self) -> Result<User, HttpResponse> {
+        self.extensions
+            .get::<User>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("Sesión no encontrada".to_string()).into())
+    }
+
+    fn current_session(&self) -> Result<Session, HttpResponse> {
+        self.extensions
+            .get::<Session>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("Sesión no encontrada".to_string()).into())
+    }
+
+    /// IP del cliente y dispositivo según su `User-Agent`. Con `trusted_proxy` la IP
+    /// es la que informa el proxy reverso: `X-Real-IP` o, en su defecto, el último
+    /// salto de `X-Forwarded-For`, el único que agrega el proxy. En otro caso esos
+    /// headers los envía el propio cliente y se usa la dirección de la conexión.
+    fn client_info(&self) -> ClientInfo {
+        let trusted_proxy = self
+            .get_dependency::<AppModule, dyn AuthService>()
+            .is_ok_and(|service| service.trusts_proxy_headers());
+
+        let forwarded_ip = || {
+            self.header("X-Real-IP")
+                .or_else(|| {
+                    self.header("X-Forwarded-For").and_then(|value| value.rsplit(',').next())
+                })
+                .map(|ip| ip.trim().to_string())
+                .filter(|ip| !ip.is_empty())
+        };
+
+        let peer_ip = self
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        let ip = trusted_proxy.then(forwarded_ip).flatten().or(peer_ip);
+
+        let device = self
+            .header("User-Agent")
+            .map(|agent| agent.chars().take(255).collect::<String>())
+            .filter(|agent| !agent.is_empty());
+
+        ClientInfo { ip, device }
+    }
+
+    /// Autor de la operación en curso; sin usuario en las rutas públicas.
+    /// En una suplantación el autor registrado es el administrador real.
+    fn audit_context(&self) -> AuditContext {
+        let user = self.extensions.get::<User>();
+        let impersonator_id = self.extensions.get::<Session>().and_then(|s| s.impersonator_id);
+
+        match (impersonator_id, user) {
+            (Some(impersonator_id), Some(user)) => {
+                AuditContext::impersonated(impersonator_id, user, &self.client_info())
+            }
+            _ => AuditContext::new(user, &self.client_info()),
+        }
+    }
+}
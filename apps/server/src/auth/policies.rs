@@ -0,0 +1,104 @@
+use crate::{
+    auth::Session,
+    courses::{CourseStaff, StaffRole},
+    enrollments::Enrollment,
+    shared::errors::AppError,
+    users::User,
+};
+
+/// Reglas de acceso a recursos según el usuario que realiza la acción.
+/// Los servicios las aplican luego de cargar el recurso, de modo que
+/// no dependen de los parámetros de la ruta.
+
+fn forbidden() -> AppError {
+    AppError::Forbidden("No tienes acceso a este recurso".to_string())
+}
+
+/// Rol del usuario en el equipo docente del curso, si forma parte de él.
+fn staff_role(actor: &User, staff: &[CourseStaff]) -> Option<StaffRole> {
+    staff.iter().find(|member| member.user_id == actor.id).map(|member| member.role)
+}
+
+/// Administradores y secretarías gestionan todos los cursos, el equipo
+/// docente solo aquellos de los que forma parte.
+pub fn can_manage_course(actor: &User, staff: &[CourseStaff]) -> bool {
+    actor.is_administrator() || actor.is_secretary() || staff_role(actor, staff).is_some()
+}
+
+/// Solo el coordinador del curso (o un administrador) puede aprobar
+/// o rechazar prácticas desde la plataforma.
+pub fn ensure_practice_review(actor: &User, staff: &[CourseStaff]) -> Result<(), AppError> {
+    if !actor.is_administrator() && staff_role(actor, staff) != Some(StaffRole::Coordinator) {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+pub fn ensure_course_access(actor: &User, staff: &[CourseStaff]) -> Result<(), AppError> {
+    if !can_manage_course(actor, staff) {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+/// Las notas las registran los docentes del curso, incluidos los ayudantes.
+pub fn ensure_score_entry(actor: &User, staff: &[CourseStaff]) -> Result<(), AppError> {
+    if !staff_role(actor, staff).is_some_and(|role| role.can_enter_scores()) {
+        return Err(AppError::Forbidden(
+            "Solo los docentes del curso pueden modificar las notas".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn ensure_course_closing(actor: &User, staff: &[CourseStaff]) -> Result<(), AppError> {
+    let allowed = actor.is_administrator()
+        || actor.is_secretary()
+        || staff_role(actor, staff).is_some_and(|role| role.can_close_course());
+
+    if !allowed {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+pub fn ensure_staff_management(actor: &User, staff: &[CourseStaff]) -> Result<(), AppError> {
+    let allowed = actor.is_administrator()
+        || actor.is_secretary()
+        || staff_role(actor, staff).is_some_and(|role| role.can_manage_staff());
+
+    if !allowed {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+/// Un estudiante solo accede a sus propias inscripciones (y a la práctica
+/// asociada a ellas), el resto según la gestión del curso.
+pub fn ensure_enrollment_access(
+    actor: &User,
+    enrollment: &Enrollment,
+    staff: &[CourseStaff],
+) -> Result<(), AppError> {
+    if enrollment.student_id != actor.id && !can_manage_course(actor, staff) {
+        return Err(forbidden());
+    }
+
+    Ok(())
+}
+
+/// Las acciones sensibles sobre la cuenta (contraseña, sesiones) solo
+/// puede realizarlas su titular, nunca un administrador suplantándolo.
+pub fn ensure_not_impersonating(session: &Session) -> Result<(), AppError> {
+    if session.is_impersonated() {
+        let message = "Esta acción no está permitida durante una suplantación".to_string();
+        return Err(AppError::Forbidden(message));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,473 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_query::{Expr, ExprTrait, PostgresQueryBuilder, Query};
+use sea_query_sqlx::SqlxBinder;
+use shaku::{Component, Interface};
+use sqlx::{query_as_with as sqlx_query, Postgres};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::entity::{
+        LoginAttempt, LoginAttempts, PasswordResetToken, PasswordResetTokens, RecoveryCode,
+        RecoveryCodes, RefreshToken, RefreshTokens, TwoFactor, TwoFactors,
+    },
+    shared::{database::DatabaseConnection, errors::AppError},
+};
+
+#[derive(Component)]
+#[shaku(interface = RefreshTokenRepository)]
+pub struct PostgresRefreshTokenRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository: Interface {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<RefreshToken>, AppError>;
+    async fn save(&self, token: RefreshToken) -> Result<RefreshToken, AppError>;
+
+    /// Revoca el token solo si seguía vigente; indica si esta llamada lo consumió.
+    async fn consume(&self, id: &Uuid) -> Result<bool, AppError>;
+
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), AppError>;
+    async fn revoke_all(&self, user_id: &Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PostgresRefreshTokenRepository {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<RefreshToken>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(RefreshTokens::Table)
+            .and_where(Expr::col(RefreshTokens::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let token = sqlx_query::<Postgres, RefreshToken, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn save(&self, token: RefreshToken) -> Result<RefreshToken, AppError> {
+        let query = r#"
+            INSERT INTO refresh_tokens (id, user_id, session_id, expires_at, revoked_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO UPDATE SET
+                revoked_at = EXCLUDED.revoked_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, RefreshToken>(query)
+            .bind(token.id)
+            .bind(token.user_id)
+            .bind(token.session_id)
+            .bind(token.expires_at)
+            .bind(token.revoked_at)
+            .bind(token.created_at)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn consume(&self, id: &Uuid) -> Result<bool, AppError> {
+        let (sql, values) = Query::update()
+            .table(RefreshTokens::Table)
+            .value(RefreshTokens::RevokedAt, Utc::now())
+            .and_where(Expr::col(RefreshTokens::Id).eq(*id))
+            .and_where(Expr::col(RefreshTokens::RevokedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(RefreshTokens::Table)
+            .value(RefreshTokens::RevokedAt, Utc::now())
+            .and_where(Expr::col(RefreshTokens::SessionId).eq(*session_id))
+            .and_where(Expr::col(RefreshTokens::RevokedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+
+    async fn revoke_all(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(RefreshTokens::Table)
+            .value(RefreshTokens::RevokedAt, Utc::now())
+            .and_where(Expr::col(RefreshTokens::UserId).eq(*user_id))
+            .and_where(Expr::col(RefreshTokens::RevokedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = PasswordResetTokenRepository)]
+pub struct PostgresPasswordResetTokenRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[async_trait]
+pub trait PasswordResetTokenRepository: Interface {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<PasswordResetToken>, AppError>;
+    async fn save(&self, token: PasswordResetToken) -> Result<PasswordResetToken, AppError>;
+
+    async fn invalidate_all(&self, user_id: &Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for PostgresPasswordResetTokenRepository {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<PasswordResetToken>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(PasswordResetTokens::Table)
+            .and_where(Expr::col(PasswordResetTokens::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let token = sqlx_query::<Postgres, PasswordResetToken, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn save(&self, token: PasswordResetToken) -> Result<PasswordResetToken, AppError> {
+        let query = r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO UPDATE SET
+                used_at = EXCLUDED.used_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, PasswordResetToken>(query)
+            .bind(token.id)
+            .bind(token.user_id)
+            .bind(&token.token_hash)
+            .bind(token.expires_at)
+            .bind(token.used_at)
+            .bind(token.created_at)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn invalidate_all(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(PasswordResetTokens::Table)
+            .value(PasswordResetTokens::UsedAt, Utc::now())
+            .and_where(Expr::col(PasswordResetTokens::UserId).eq(*user_id))
+            .and_where(Expr::col(PasswordResetTokens::UsedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = LoginAttemptRepository)]
+pub struct PostgresLoginAttemptRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[async_trait]
+pub trait LoginAttemptRepository: Interface {
+    async fn find_by_key(&self, key: &str) -> Result<Option<LoginAttempt>, AppError>;
+    async fn record_failure(&self, key: &str) -> Result<LoginAttempt, AppError>;
+
+    async fn lock(&self, key: &str) -> Result<(), AppError>;
+    async fn reset(&self, key: &str) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl LoginAttemptRepository for PostgresLoginAttemptRepository {
+    async fn find_by_key(&self, key: &str) -> Result<Option<LoginAttempt>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(LoginAttempts::Table)
+            .and_where(Expr::col(LoginAttempts::Key).eq(key))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let attempt = sqlx_query::<Postgres, LoginAttempt, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(attempt)
+    }
+
+    async fn record_failure(&self, key: &str) -> Result<LoginAttempt, AppError> {
+        let query = r#"
+            INSERT INTO login_attempts (key, failures, last_failed_at)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (key) DO UPDATE SET
+                failures = login_attempts.failures + 1,
+                last_failed_at = EXCLUDED.last_failed_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, LoginAttempt>(query)
+            .bind(key)
+            .bind(Utc::now())
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn lock(&self, key: &str) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(LoginAttempts::Table)
+            .value(LoginAttempts::LockedAt, Utc::now())
+            .and_where(Expr::col(LoginAttempts::Key).eq(key))
+            .and_where(Expr::col(LoginAttempts::LockedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+
+    async fn reset(&self, key: &str) -> Result<(), AppError> {
+        let (sql, values) = Query::delete()
+            .from_table(LoginAttempts::Table)
+            .and_where(Expr::col(LoginAttempts::Key).eq(key))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = TwoFactorRepository)]
+pub struct PostgresTwoFactorRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+/// Configuración TOTP y códigos de recuperación de cada usuario. El consumo
+/// de pasos y códigos es atómico, de modo que un código no se acepta dos veces
+/// aunque llegue en peticiones concurrentes.
+#[async_trait]
+pub trait TwoFactorRepository: Interface {
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Option<TwoFactor>, AppError>;
+    async fn save(&self, two_factor: TwoFactor) -> Result<TwoFactor, AppError>;
+    async fn delete(&self, user_id: &Uuid) -> Result<(), AppError>;
+
+    async fn consume_step(&self, user_id: &Uuid, step: i64) -> Result<bool, AppError>;
+
+    async fn find_recovery_codes(&self, user_id: &Uuid) -> Result<Vec<RecoveryCode>, AppError>;
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &Uuid,
+        codes: Vec<RecoveryCode>,
+    ) -> Result<(), AppError>;
+    async fn use_recovery_code(&self, id: &Uuid) -> Result<bool, AppError>;
+}
+
+#[async_trait]
+impl TwoFactorRepository for PostgresTwoFactorRepository {
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Option<TwoFactor>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(TwoFactors::Table)
+            .and_where(Expr::col(TwoFactors::UserId).eq(*user_id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let two_factor = sqlx_query::<Postgres, TwoFactor, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(two_factor)
+    }
+
+    async fn save(&self, two_factor: TwoFactor) -> Result<TwoFactor, AppError> {
+        let query = r#"
+            INSERT INTO user_two_factor (user_id, secret, confirmed_at, last_used_step, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE SET
+                secret = EXCLUDED.secret,
+                confirmed_at = EXCLUDED.confirmed_at,
+                last_used_step = EXCLUDED.last_used_step,
+                created_at = EXCLUDED.created_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, TwoFactor>(query)
+            .bind(two_factor.user_id)
+            .bind(&two_factor.secret)
+            .bind(two_factor.confirmed_at)
+            .bind(two_factor.last_used_step)
+            .bind(two_factor.created_at)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let pool = self.db_connection.get_pool();
+
+        for (sql, values) in [
+            Query::delete()
+                .from_table(RecoveryCodes::Table)
+                .and_where(Expr::col(RecoveryCodes::UserId).eq(*user_id))
+                .build_sqlx(PostgresQueryBuilder),
+            Query::delete()
+                .from_table(TwoFactors::Table)
+                .and_where(Expr::col(TwoFactors::UserId).eq(*user_id))
+                .build_sqlx(PostgresQueryBuilder),
+        ] {
+            sqlx::query_with(&sql, values).execute(pool).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn consume_step(&self, user_id: &Uuid, step: i64) -> Result<bool, AppError> {
+        let (sql, values) = Query::update()
+            .table(TwoFactors::Table)
+            .value(TwoFactors::LastUsedStep, step)
+            .and_where(Expr::col(TwoFactors::UserId).eq(*user_id))
+            .and_where(
+                Expr::col(TwoFactors::LastUsedStep)
+                    .is_null()
+                    .or(Expr::col(TwoFactors::LastUsedStep).lt(step)),
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_recovery_codes(&self, user_id: &Uuid) -> Result<Vec<RecoveryCode>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(RecoveryCodes::Table)
+            .and_where(Expr::col(RecoveryCodes::UserId).eq(*user_id))
+            .and_where(Expr::col(RecoveryCodes::UsedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        let codes = sqlx_query::<Postgres, RecoveryCode, _>(&sql, values)
+            .fetch_all(self.db_connection.get_pool())
+            .await?;
+
+        Ok(codes)
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &Uuid,
+        codes: Vec<RecoveryCode>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.db_connection.get_pool().begin().await?;
+
+        let (sql, values) = Query::delete()
+            .from_table(RecoveryCodes::Table)
+            .and_where(Expr::col(RecoveryCodes::UserId).eq(*user_id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+
+        let insert_query = r#"
+            INSERT INTO two_factor_recovery_codes (id, user_id, code_hash, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+        "#;
+
+        for code in codes {
+            sqlx::query(insert_query)
+                .bind(code.id)
+                .bind(code.user_id)
+                .bind(code.code_hash)
+                .bind(code.used_at)
+                .bind(code.created_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn use_recovery_code(&self, id: &Uuid) -> Result<bool, AppError> {
+        let (sql, values) = Query::update()
+            .table(RecoveryCodes::Table)
+            .value(RecoveryCodes::UsedAt, Utc::now())
+            .and_where(Expr::col(RecoveryCodes::Id).eq(*id))
+            .and_where(Expr::col(RecoveryCodes::UsedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Implementación en memoria de los contadores de intentos, pensada para
+/// pruebas o despliegues de una sola instancia.
+#[derive(Default)]
+pub struct InMemoryLoginAttemptRepository {
+    attempts: Mutex<HashMap<String, LoginAttempt>>,
+}
+
+impl InMemoryLoginAttemptRepository {
+    fn attempts(&self) -> std::sync::MutexGuard<'_, HashMap<String, LoginAttempt>> {
+        self.attempts.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for InMemoryLoginAttemptRepository {
+    async fn find_by_key(&self, key: &str) -> Result<Option<LoginAttempt>, AppError> {
+        Ok(self.attempts().get(key).cloned())
+    }
+
+    async fn record_failure(&self, key: &str) -> Result<LoginAttempt, AppError> {
+        let mut attempts = self.attempts();
+
+        let attempt = attempts
+            .entry(key.to_string())
+            .or_insert_with(|| LoginAttempt::new(key.to_string()));
+
+        attempt.failures += 1;
+        attempt.last_failed_at = Some(Utc::now());
+
+        Ok(attempt.clone())
+    }
+
+    async fn lock(&self, key: &str) -> Result<(), AppError> {
+        if let Some(attempt) = self.attempts().get_mut(key) {
+            attempt.locked_at.get_or_insert_with(Utc::now);
+        }
+
+        Ok(())
+    }
+
+    async fn reset(&self, key: &str) -> Result<(), AppError> {
+        self.attempts().remove(key);
+
+        Ok(())
+    }
+}
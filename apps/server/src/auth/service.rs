@@ -0,0 +1,1000 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use shaku::{Component, Interface};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    audit::{AuditContext, AuditEntry, AuditService},
+    auth::{
+        ChangePasswordDto, ForgotPasswordDto, LoginAttempt, LoginAttemptRepository, LoginDto,
+        PasswordResetToken, PasswordResetTokenRepository, RecoveryCode, RefreshToken,
+        RefreshTokenRepository, ResetPasswordDto, Session, SessionStore, TwoFactor,
+        TwoFactorLoginDto, TwoFactorRepository,
+    },
+    config::AuthConfig,
+    shared::{
+        errors::{AppError, Input},
+        services::{
+            event_queue::{Event, EventQueue},
+            hasher::PasswordHasher,
+            jwt::{JwtService, TokenKind, TokenScope},
+            totp::TotpService,
+        },
+    },
+    user_filter,
+    users::{User, UserFilter, UserRepository},
+};
+
+/// Resultado de abrir o rotar una sesión: el usuario autenticado
+/// junto al par de tokens (sesión + refresco) recién emitidos.
+/// Una sesión restringida (`scope`) o de suplantación no posee refresh token.
+pub struct AuthSession {
+    pub user: User,
+    pub session_token: String,
+    pub refresh_token: Option<String>,
+    pub scope: TokenScope,
+    pub impersonator_id: Option<Uuid>,
+}
+
+/// Resultado de validar las credenciales. Si la cuenta tiene activada la
+/// verificación en dos pasos, la sesión se emite recién al validar el código.
+pub enum LoginOutcome {
+    Authenticated(AuthSession),
+    TwoFactorRequired { challenge_token: String },
+}
+
+/// Secreto TOTP pendiente de confirmación y su URI de aprovisionamiento.
+pub struct TwoFactorSetup {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+/// Verificación en dos pasos recién activada: la nueva sesión y los
+/// códigos de recuperación en texto plano, que solo se entregan una vez.
+pub struct TwoFactorActivation {
+    pub session: AuthSession,
+    pub recovery_codes: Vec<String>,
+}
+
+/// Datos del cliente que abre una sesión, obtenidos de los headers de la petición.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub device: Option<String>,
+}
+
+#[derive(Component)]
+#[shaku(interface = AuthService)]
+pub struct AuthServiceImpl {
+    #[shaku(inject)]
+    users: Arc<dyn UserRepository>,
+
+    #[shaku(inject)]
+    refresh_tokens: Arc<dyn RefreshTokenRepository>,
+
+    #[shaku(inject)]
+    hasher: Arc<dyn PasswordHasher>,
+
+    #[shaku(inject)]
+    jwt: Arc<dyn JwtService>,
+
+    #[shaku(inject)]
+    reset_tokens: Arc<dyn PasswordResetTokenRepository>,
+
+    #[shaku(inject)]
+    event_queue: Arc<dyn EventQueue>,
+
+    #[shaku(inject)]
+    login_attempts: Arc<dyn LoginAttemptRepository>,
+
+    #[shaku(inject)]
+    sessions: Arc<dyn SessionStore>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
+
+    #[shaku(inject)]
+    two_factor: Arc<dyn TwoFactorRepository>,
+
+    #[shaku(inject)]
+    totp: Arc<dyn TotpService>,
+
+    config: AuthConfig,
+}
+
+impl From<AuthConfig> for AuthServiceImplParameters {
+    fn from(config: AuthConfig) -> Self {
+        AuthServiceImplParameters { config }
+    }
+}
+
+#[async_trait]
+pub trait AuthService: Interface {
+    async fn login(&self, input: LoginDto, client: ClientInfo) -> Result<LoginOutcome, AppError>;
+    async fn login_two_factor(
+        &self,
+        input: TwoFactorLoginDto,
+        client: ClientInfo,
+    ) -> Result<AuthSession, AppError>;
+
+    async fn refresh(&self, refresh_token: &str) -> Result<AuthSession, AppError>;
+    async fn logout(&self, refresh_token: &str) -> Result<(), AppError>;
+
+    async fn authenticate(&self, session_token: &str) -> Result<(User, Session), AppError>;
+    async fn authenticate_password_change(
+        &self,
+        session_token: &str,
+    ) -> Result<(User, Session), AppError>;
+    async fn authenticate_two_factor_setup(
+        &self,
+        session_token: &str,
+    ) -> Result<(User, Session), AppError>;
+
+    async fn change_password(
+        &self,
+        user: User,
+        input: ChangePasswordDto,
+        client: ClientInfo,
+    ) -> Result<AuthSession, AppError>;
+
+    async fn forgot_password(&self, input: ForgotPasswordDto) -> Result<(), AppError>;
+    async fn reset_password(&self, input: ResetPasswordDto) -> Result<(), AppError>;
+
+    async fn list_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, AppError>;
+    async fn revoke_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), AppError>;
+    async fn revoke_sessions(&self, user_id: &Uuid) -> Result<(), AppError>;
+
+    async fn impersonate(
+        &self,
+        admin: &User,
+        user_id: &Uuid,
+        client: ClientInfo,
+        audit: &AuditContext,
+    ) -> Result<AuthSession, AppError>;
+
+    async fn stop_impersonation(
+        &self,
+        session: &Session,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
+
+    async fn setup_two_factor(&self, user: &User) -> Result<TwoFactorSetup, AppError>;
+    async fn confirm_two_factor(
+        &self,
+        user: User,
+        code: &str,
+        client: ClientInfo,
+        audit: &AuditContext,
+    ) -> Result<TwoFactorActivation, AppError>;
+    async fn disable_two_factor(
+        &self,
+        user: &User,
+        code: &str,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
+    async fn regenerate_recovery_codes(
+        &self,
+        user: &User,
+        code: &str,
+        audit: &AuditContext,
+    ) -> Result<Vec<String>, AppError>;
+
+    /// Si la IP del cliente se toma de los headers del proxy reverso.
+    fn trusts_proxy_headers(&self) -> bool;
+}
+
+/// Intentos fallidos permitidos antes de comenzar a aplicar la espera exponencial.
+const LOGIN_FREE_ATTEMPTS: i32 = 3;
+
+/// Códigos de recuperación emitidos al activar la verificación en dos pasos.
+const RECOVERY_CODES_COUNT: usize = 10;
+
+/// Intervalo mínimo entre actualizaciones de `last_seen_at`, para no escribir
+/// en el almacén de sesiones en cada petición autenticada.
+const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Credenciales inválidas".to_string())
+}
+
+fn invalid_session() -> AppError {
+    AppError::Unauthorized("Sesión inválida o expirada".to_string())
+}
+
+fn password_change_required() -> AppError {
+    AppError::Forbidden("Debes cambiar tu contraseña antes de continuar".to_string())
+}
+
+fn login_throttled() -> AppError {
+    AppError::TooManyRequests(
+        "Demasiados intentos fallidos, espera un momento antes de reintentar".to_string(),
+    )
+}
+
+fn account_locked() -> AppError {
+    AppError::Forbidden(
+        "La cuenta fue bloqueada por intentos fallidos, contacta a un administrador".to_string(),
+    )
+}
+
+fn two_factor_setup_required() -> AppError {
+    AppError::Forbidden(
+        "Debes configurar la verificación en dos pasos antes de continuar".to_string(),
+    )
+}
+
+fn invalid_two_factor_code() -> AppError {
+    AppError::Unauthorized("El código de verificación es inválido".to_string())
+}
+
+fn invalid_code_input() -> AppError {
+    AppError::InvalidInput(Input {
+        field: "code".to_string(),
+        message: "El código de verificación es inválido".to_string(),
+        ..Input::default()
+    })
+}
+
+fn two_factor_not_enabled() -> AppError {
+    AppError::InvalidOperation("La verificación en dos pasos no está activada".to_string())
+}
+
+fn not_impersonating() -> AppError {
+    AppError::InvalidOperation("La sesión actual no es una suplantación".to_string())
+}
+
+fn invalid_reset_token() -> AppError {
+    AppError::InvalidOperation("El enlace de recuperación es inválido o expiró".to_string())
+}
+
+impl AuthServiceImpl {
+    /// Abre la sesión que corresponde al estado de la cuenta: restringida si
+    /// debe cambiar su contraseña o configurar la verificación en dos pasos
+    /// exigida para su rol, completa en otro caso.
+    async fn open_authorized_session(
+        &self,
+        user: User,
+        client: ClientInfo,
+    ) -> Result<AuthSession, AppError> {
+        if user.must_change_password {
+            return self.open_restricted_session(user, client, TokenScope::PasswordChange).await;
+        }
+
+        if self.requires_two_factor_setup(&user).await? {
+            return self.open_restricted_session(user, client, TokenScope::TwoFactorSetup).await;
+        }
+
+        self.open_session(user, client).await
+    }
+
+    async fn open_session(&self, user: User, client: ClientInfo) -> Result<AuthSession, AppError> {
+        let expires_in = Duration::milliseconds(self.config.refresh_jwt_exp_ms as i64);
+        let session = self.store_session(&user, client, expires_in, None).await?;
+
+        self.issue_tokens(user, session.id).await
+    }
+
+    async fn store_session(
+        &self,
+        user: &User,
+        client: ClientInfo,
+        expires_in: Duration,
+        impersonator_id: Option<Uuid>,
+    ) -> Result<Session, AppError> {
+        let now = Utc::now();
+
+        self.sessions
+            .save(Session {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                device: client.device,
+                ip: client.ip,
+                impersonator_id,
+                created_at: now,
+                last_seen_at: now,
+                expires_at: now + expires_in,
+            })
+            .await
+    }
+
+    async fn issue_tokens(&self, user: User, session_id: Uuid) -> Result<AuthSession, AppError> {
+        let session_claims = self.jwt.claims(TokenKind::Session, &user.id, &session_id);
+        let refresh_claims = self.jwt.claims(TokenKind::Refresh, &user.id, &session_id);
+
+        self.refresh_tokens
+            .save(RefreshToken {
+                id: refresh_claims.jti,
+                user_id: user.id,
+                session_id,
+                expires_at: refresh_claims.expires_at(),
+                revoked_at: None,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(AuthSession {
+            session_token: self.jwt.sign(TokenKind::Session, &session_claims)?,
+            refresh_token: Some(self.jwt.sign(TokenKind::Refresh, &refresh_claims)?),
+            scope: TokenScope::Full,
+            impersonator_id: None,
+            user,
+        })
+    }
+
+    /// Sesión sin refresh token cuyo único uso permitido es cambiar la contraseña
+    /// o configurar la verificación en dos pasos, según `scope`.
+    async fn open_restricted_session(
+        &self,
+        user: User,
+        client: ClientInfo,
+        scope: TokenScope,
+    ) -> Result<AuthSession, AppError> {
+        let expires_in = Duration::milliseconds(self.config.session_jwt_exp_ms as i64);
+        let session = self.store_session(&user, client, expires_in, None).await?;
+
+        let mut claims = self.jwt.claims(TokenKind::Session, &user.id, &session.id);
+        claims.scope = scope;
+
+        Ok(AuthSession {
+            session_token: self.jwt.sign(TokenKind::Session, &claims)?,
+            refresh_token: None,
+            scope,
+            impersonator_id: None,
+            user,
+        })
+    }
+
+    /// Token de corta duración que solo permite completar el segundo paso del
+    /// login. Su `sid` no corresponde a ninguna sesión almacenada.
+    fn issue_two_factor_challenge(&self, user: &User) -> Result<String, AppError> {
+        let mut claims = self.jwt.claims(TokenKind::Session, &user.id, &Uuid::new_v4());
+        claims.scope = TokenScope::TwoFactorChallenge;
+
+        Ok(self.jwt.sign(TokenKind::Session, &claims)?)
+    }
+
+    async fn find_enabled_two_factor(&self, user_id: &Uuid) -> Result<Option<TwoFactor>, AppError> {
+        Ok(self.two_factor.find_by_user(user_id).await?.filter(TwoFactor::is_enabled))
+    }
+
+    async fn requires_two_factor_setup(&self, user: &User) -> Result<bool, AppError> {
+        if !user.has_any_role(&self.config.two_factor_required_roles) {
+            return Ok(false);
+        }
+
+        Ok(self.find_enabled_two_factor(&user.id).await?.is_none())
+    }
+
+    /// Acepta el código solo si es válido y su paso de tiempo aún no fue utilizado.
+    async fn consume_totp_code(
+        &self,
+        two_factor: &TwoFactor,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let step = self.totp.verify(&two_factor.secret, code, two_factor.last_used_step)?;
+
+        match step {
+            Some(step) => self.two_factor.consume_step(&two_factor.user_id, step).await,
+            None => Ok(false),
+        }
+    }
+
+    async fn consume_recovery_code(&self, user_id: &Uuid, code: &str) -> Result<bool, AppError> {
+        let code = RecoveryCode::normalize(code);
+
+        for stored in self.two_factor.find_recovery_codes(user_id).await? {
+            if self.hasher.verify(&code, &stored.code_hash)? {
+                return self.two_factor.use_recovery_code(&stored.id).await;
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Reemplaza los códigos de recuperación del usuario, retornando los
+    /// nuevos en su forma legible `xxxxx-xxxxx`.
+    async fn issue_recovery_codes(&self, user_id: &Uuid) -> Result<Vec<String>, AppError> {
+        let mut codes = Vec::with_capacity(RECOVERY_CODES_COUNT);
+        let mut stored = Vec::with_capacity(RECOVERY_CODES_COUNT);
+
+        for _ in 0..RECOVERY_CODES_COUNT {
+            let secret = Uuid::new_v4().simple().to_string();
+            let code = format!("{}-{}", &secret[..5], &secret[5..10]);
+
+            stored.push(RecoveryCode {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                code_hash: self.hasher.hash(&RecoveryCode::normalize(&code))?,
+                used_at: None,
+                created_at: Utc::now(),
+            });
+
+            codes.push(code);
+        }
+
+        self.two_factor.replace_recovery_codes(user_id, stored).await?;
+
+        Ok(codes)
+    }
+
+    /// Valida el token y que su sesión siga registrada en el almacén,
+    /// de modo que revocar la sesión invalide también los tokens ya emitidos.
+    async fn verify_session(
+        &self,
+        session_token: &str,
+    ) -> Result<(User, Session, TokenScope), AppError> {
+        let claims = self
+            .jwt
+            .verify(TokenKind::Session, session_token)
+            .map_err(|_| invalid_session())?;
+
+        let mut session = match self.sessions.find_by_id(&claims.sid).await? {
+            Some(session) if session.user_id == claims.sub => session,
+            _ => return Err(invalid_session()),
+        };
+
+        let user = self.find_active_user(&claims.sub).await?;
+
+        if Utc::now() - session.last_seen_at > Duration::seconds(SESSION_TOUCH_INTERVAL_SECS) {
+            session.last_seen_at = Utc::now();
+            session = self.sessions.save(session).await?;
+        }
+
+        Ok((user, session, claims.scope))
+    }
+
+    /// Separa un token `<id>.<secreto>` y retorna el registro asociado
+    /// solo si el secreto coincide y el token sigue vigente.
+    async fn find_reset_token(&self, token: &str) -> Result<PasswordResetToken, AppError> {
+        let Some((id, secret)) = token.split_once('.') else {
+            return Err(invalid_reset_token());
+        };
+
+        let id = Uuid::parse_str(id).map_err(|_| invalid_reset_token())?;
+
+        let Some(stored) = self.reset_tokens.find_by_id(&id).await? else {
+            return Err(invalid_reset_token());
+        };
+
+        if stored.is_used() || stored.is_expired() {
+            return Err(invalid_reset_token());
+        }
+
+        if !self.hasher.verify(secret, &stored.token_hash)? {
+            return Err(invalid_reset_token());
+        }
+
+        Ok(stored)
+    }
+
+    /// Rechaza el intento si la cuenta está bloqueada o si el email o la IP
+    /// aún se encuentran dentro de la espera impuesta por fallos anteriores.
+    async fn ensure_login_allowed(
+        &self,
+        email_key: &str,
+        ip_key: Option<&str>,
+    ) -> Result<(), AppError> {
+        let keys = std::iter::once(email_key).chain(ip_key);
+
+        for key in keys {
+            let Some(attempt) = self.login_attempts.find_by_key(key).await? else {
+                continue;
+            };
+
+            if attempt.is_locked() {
+                return Err(account_locked());
+            }
+
+            let retry_at = attempt.retry_at(
+                LOGIN_FREE_ATTEMPTS,
+                self.config.login_backoff_base_ms,
+                self.config.login_backoff_max_ms,
+            );
+
+            if retry_at.is_some_and(|retry_at| retry_at > Utc::now()) {
+                return Err(login_throttled());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_login_failure(
+        &self,
+        email_key: &str,
+        ip_key: Option<&str>,
+    ) -> Result<(), AppError> {
+        let attempt = self.login_attempts.record_failure(email_key).await?;
+
+        if attempt.failures >= self.config.login_lockout_threshold {
+            self.login_attempts.lock(email_key).await?;
+        }
+
+        if let Some(ip_key) = ip_key {
+            self.login_attempts.record_failure(ip_key).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn find_active_user(&self, id: &Uuid) -> Result<User, AppError> {
+        match self.users.find_by_id(id).await? {
+            Some(user) if user.deleted_at.is_none() => Ok(user),
+            _ => Err(invalid_session()),
+        }
+    }
+}
+
+#[async_trait]
+impl AuthService for AuthServiceImpl {
+    async fn login(&self, input: LoginDto, client: ClientInfo) -> Result<LoginOutcome, AppError> {
+        let email_key = LoginAttempt::email_key(&input.email);
+        let ip_key = client.ip.as_deref().map(LoginAttempt::ip_key);
+
+        self.ensure_login_allowed(&email_key, ip_key.as_deref()).await?;
+
+        // Los emails inexistentes también suman fallos, así la respuesta
+        // no permite distinguir cuentas registradas.
+
+        let Some(mut user) = self.users.find_one(user_filter! { email: input.email }).await? else {
+            self.record_login_failure(&email_key, ip_key.as_deref()).await?;
+            return Err(invalid_credentials());
+        };
+
+        if user.deleted_at.is_some() || !self.hasher.verify(&input.password, &user.password)? {
+            self.record_login_failure(&email_key, ip_key.as_deref()).await?;
+            return Err(invalid_credentials());
+        }
+
+        // Los hashes heredados (p. ej. bcrypt) se migran al algoritmo configurado
+        // aprovechando que en este punto se conoce la contraseña en texto plano.
+
+        if self.hasher.needs_rehash(&user.password) {
+            user.password = self.hasher.hash(&input.password)?;
+            user = self.users.save(user).await?;
+        }
+
+        // Con la verificación en dos pasos activa los fallos no se reinician
+        // hasta validar el código, así estos también cuentan para el bloqueo.
+
+        if self.find_enabled_two_factor(&user.id).await?.is_some() {
+            let challenge_token = self.issue_two_factor_challenge(&user)?;
+            return Ok(LoginOutcome::TwoFactorRequired { challenge_token });
+        }
+
+        self.login_attempts.reset(&email_key).await?;
+
+        let session = self.open_authorized_session(user, client).await?;
+
+        Ok(LoginOutcome::Authenticated(session))
+    }
+
+    async fn login_two_factor(
+        &self,
+        input: TwoFactorLoginDto,
+        client: ClientInfo,
+    ) -> Result<AuthSession, AppError> {
+        let claims = self
+            .jwt
+            .verify(TokenKind::Session, &input.challenge_token)
+            .map_err(|_| invalid_session())?;
+
+        if claims.scope != TokenScope::TwoFactorChallenge {
+            return Err(invalid_session());
+        }
+
+        let user = self.find_active_user(&claims.sub).await?;
+
+        let email_key = LoginAttempt::email_key(&user.email);
+        let ip_key = client.ip.as_deref().map(LoginAttempt::ip_key);
+
+        self.ensure_login_allowed(&email_key, ip_key.as_deref()).await?;
+
+        let Some(two_factor) = self.find_enabled_two_factor(&user.id).await? else {
+            return Err(invalid_session());
+        };
+
+        let verified = match (&input.code, &input.recovery_code) {
+            (Some(code), _) => self.consume_totp_code(&two_factor, code).await?,
+            (None, Some(code)) => self.consume_recovery_code(&user.id, code).await?,
+            (None, None) => false,
+        };
+
+        if !verified {
+            self.record_login_failure(&email_key, ip_key.as_deref()).await?;
+            return Err(invalid_two_factor_code());
+        }
+
+        self.login_attempts.reset(&email_key).await?;
+
+        self.open_authorized_session(user, client).await
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<AuthSession, AppError> {
+        let claims = self
+            .jwt
+            .verify(TokenKind::Refresh, refresh_token)
+            .map_err(|_| invalid_session())?;
+
+        // Un refresh token firmado que ya no existe, o que ya fue rotado, indica
+        // que fue filtrado, por lo que se invalida la sesión completa. El token se
+        // marca como usado de forma atómica: de dos renovaciones simultáneas con el
+        // mismo token solo una puede reclamarlo y la otra se trata como reutilización.
+
+        let Some(stored) = self.refresh_tokens.find_by_id(&claims.jti).await? else {
+            self.refresh_tokens.revoke_session(&claims.sid).await?;
+            self.sessions.remove(&claims.sid).await?;
+            return Err(invalid_session());
+        };
+
+        if !self.refresh_tokens.consume(&stored.id).await? {
+            self.refresh_tokens.revoke_session(&stored.session_id).await?;
+            self.sessions.remove(&stored.session_id).await?;
+            return Err(invalid_session());
+        }
+
+        if stored.is_expired() {
+            return Err(invalid_session());
+        }
+
+        let user = self.find_active_user(&stored.user_id).await?;
+
+        if user.must_change_password || self.requires_two_factor_setup(&user).await? {
+            return Err(invalid_session());
+        }
+
+        // Una sesión revocada desde el almacén ya no puede renovarse
+
+        let Some(mut session) = self.sessions.find_by_id(&stored.session_id).await? else {
+            self.refresh_tokens.revoke_session(&stored.session_id).await?;
+            return Err(invalid_session());
+        };
+
+        session.last_seen_at = Utc::now();
+        session.expires_at =
+            session.last_seen_at + Duration::milliseconds(self.config.refresh_jwt_exp_ms as i64);
+
+        let session = self.sessions.save(session).await?;
+
+        self.issue_tokens(user, session.id).await
+    }
+
+    async fn logout(&self, refresh_token: &str) -> Result<(), AppError> {
+        let claims = self
+            .jwt
+            .verify(TokenKind::Refresh, refresh_token)
+            .map_err(|_| invalid_session())?;
+
+        self.refresh_tokens.revoke_session(&claims.sid).await?;
+        self.sessions.remove(&claims.sid).await
+    }
+
+    async fn authenticate(&self, session_token: &str) -> Result<(User, Session), AppError> {
+        let (user, session, scope) = self.verify_session(session_token).await?;
+
+        if scope == TokenScope::TwoFactorSetup {
+            return Err(two_factor_setup_required());
+        }
+
+        if scope != TokenScope::Full || user.must_change_password {
+            return Err(password_change_required());
+        }
+
+        Ok((user, session))
+    }
+
+    async fn authenticate_password_change(
+        &self,
+        session_token: &str,
+    ) -> Result<(User, Session), AppError> {
+        let (user, session, _) = self.verify_session(session_token).await?;
+
+        Ok((user, session))
+    }
+
+    async fn authenticate_two_factor_setup(
+        &self,
+        session_token: &str,
+    ) -> Result<(User, Session), AppError> {
+        let (user, session, scope) = self.verify_session(session_token).await?;
+
+        let allowed = matches!(scope, TokenScope::Full | TokenScope::TwoFactorSetup);
+
+        if !allowed || user.must_change_password {
+            return Err(password_change_required());
+        }
+
+        Ok((user, session))
+    }
+
+    async fn change_password(
+        &self,
+        mut user: User,
+        input: ChangePasswordDto,
+        client: ClientInfo,
+    ) -> Result<AuthSession, AppError> {
+        if !self.hasher.verify(&input.current_password, &user.password)? {
+            return Err(AppError::InvalidInput(Input {
+                field: "currentPassword".to_string(),
+                message: "La contraseña actual es incorrecta".to_string(),
+                ..Input::default()
+            }));
+        }
+
+        user.password = self.hasher.hash(&input.password)?;
+        user.must_change_password = false;
+        user.password_changed_at = Some(Utc::now());
+
+        let user = self.users.save(user).await?;
+
+        self.revoke_sessions(&user.id).await?;
+
+        self.open_authorized_session(user, client).await
+    }
+
+    async fn forgot_password(&self, input: ForgotPasswordDto) -> Result<(), AppError> {
+        // Se responde igual exista o no la cuenta, para no revelar qué emails están registrados.
+
+        let Some(user) = self.users.find_one(user_filter! { email: input.email }).await? else {
+            return Ok(());
+        };
+
+        if user.deleted_at.is_some() {
+            return Ok(());
+        }
+
+        self.reset_tokens.invalidate_all(&user.id).await?;
+
+        let secret = Uuid::new_v4().simple().to_string();
+        let now = Utc::now();
+
+        let token = self
+            .reset_tokens
+            .save(PasswordResetToken {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                token_hash: self.hasher.hash(&secret)?,
+                expires_at: now + Duration::milliseconds(self.config.password_reset_exp_ms as i64),
+                used_at: None,
+                created_at: now,
+            })
+            .await?;
+
+        let event_data = (user.name, user.email, format!("{}.{secret}", token.id));
+
+        self.event_queue.publish(Event::PasswordResetRequested(event_data)).await;
+
+        Ok(())
+    }
+
+    async fn reset_password(&self, input: ResetPasswordDto) -> Result<(), AppError> {
+        let mut token = self.find_reset_token(&input.token).await?;
+
+        let mut user = match self.find_active_user(&token.user_id).await {
+            Ok(user) => user,
+            Err(_) => return Err(invalid_reset_token()),
+        };
+
+        token.used_at = Some(Utc::now());
+        self.reset_tokens.save(token).await?;
+
+        user.password = self.hasher.hash(&input.password)?;
+        user.must_change_password = false;
+        user.password_changed_at = Some(Utc::now());
+
+        let user = self.users.save(user).await?;
+
+        // Recuperar la contraseña también desbloquea la cuenta
+        self.login_attempts.reset(&LoginAttempt::email_key(&user.email)).await?;
+
+        // Las sesiones abiertas con la contraseña anterior dejan de ser válidas
+        self.revoke_sessions(&user.id).await
+    }
+
+    async fn list_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, AppError> {
+        self.sessions.find_by_user(user_id).await
+    }
+
+    async fn revoke_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), AppError> {
+        match self.sessions.find_by_id(session_id).await? {
+            Some(session) if session.user_id == *user_id => {}
+            _ => return Err(AppError::ResourceNotFound(*session_id)),
+        }
+
+        self.refresh_tokens.revoke_session(session_id).await?;
+        self.sessions.remove(session_id).await
+    }
+
+    async fn revoke_sessions(&self, user_id: &Uuid) -> Result<(), AppError> {
+        self.refresh_tokens.revoke_all(user_id).await?;
+        self.sessions.remove_all(user_id).await
+    }
+
+    /// Abre una sesión de corta duración y sin refresh token que actúa como
+    /// el usuario indicado, registrando al administrador que la solicitó.
+    async fn impersonate(
+        &self,
+        admin: &User,
+        user_id: &Uuid,
+        client: ClientInfo,
+        audit: &AuditContext,
+    ) -> Result<AuthSession, AppError> {
+        let user = match self.users.find_by_id(user_id).await? {
+            Some(user) if user.deleted_at.is_none() => user,
+            _ => return Err(AppError::ResourceNotFound(*user_id)),
+        };
+
+        if user.is_administrator() {
+            let message = "No es posible suplantar a un administrador".to_string();
+            return Err(AppError::Forbidden(message));
+        }
+
+        if user.must_change_password {
+            let message = "El usuario debe cambiar su contraseña antes de ser suplantado";
+            return Err(AppError::InvalidOperation(message.to_string()));
+        }
+
+        let expires_in = Duration::milliseconds(self.config.session_jwt_exp_ms as i64);
+        let session = self.store_session(&user, client, expires_in, Some(admin.id)).await?;
+
+        let claims = self.jwt.claims(TokenKind::Session, &user.id, &session.id);
+
+        self.audit_log
+            .record(
+                AuditEntry::new(audit, "auth.impersonation_start", "user", Some(user.id))
+                    .after(&json!({ "sessionId": session.id })),
+            )
+            .await?;
+
+        Ok(AuthSession {
+            session_token: self.jwt.sign(TokenKind::Session, &claims)?,
+            refresh_token: None,
+            scope: TokenScope::Full,
+            impersonator_id: Some(admin.id),
+            user,
+        })
+    }
+
+    async fn stop_impersonation(
+        &self,
+        session: &Session,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        if !session.is_impersonated() {
+            return Err(not_impersonating());
+        }
+
+        self.sessions.remove(&session.id).await?;
+
+        self.audit_log
+            .record(
+                AuditEntry::new(audit, "auth.impersonation_stop", "user", Some(session.user_id))
+                    .before(&json!({ "sessionId": session.id })),
+            )
+            .await
+    }
+
+    /// Genera un nuevo secreto pendiente de confirmación, reemplazando
+    /// cualquier configuración anterior que no haya sido confirmada.
+    async fn setup_two_factor(&self, user: &User) -> Result<TwoFactorSetup, AppError> {
+        if self.find_enabled_two_factor(&user.id).await?.is_some() {
+            let message = "La verificación en dos pasos ya está activada".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        let secret = self.totp.generate_secret();
+        let provisioning_uri = self.totp.provisioning_uri(&secret, &user.email)?;
+
+        self.two_factor
+            .save(TwoFactor {
+                user_id: user.id,
+                secret: secret.clone(),
+                confirmed_at: None,
+                last_used_step: None,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(TwoFactorSetup {
+            secret,
+            provisioning_uri,
+        })
+    }
+
+    /// Activa la verificación en dos pasos con un primer código válido. Las
+    /// sesiones abiertas sin segundo factor se cierran y se emite una nueva.
+    async fn confirm_two_factor(
+        &self,
+        user: User,
+        code: &str,
+        client: ClientInfo,
+        audit: &AuditContext,
+    ) -> Result<TwoFactorActivation, AppError> {
+        let pending = self.two_factor.find_by_user(&user.id).await?;
+
+        let Some(mut two_factor) = pending.filter(|two_factor| !two_factor.is_enabled()) else {
+            let message = "No existe una configuración pendiente de confirmar".to_string();
+            return Err(AppError::InvalidOperation(message));
+        };
+
+        let Some(step) = self.totp.verify(&two_factor.secret, code, None)? else {
+            return Err(invalid_code_input());
+        };
+
+        two_factor.confirmed_at = Some(Utc::now());
+        two_factor.last_used_step = Some(step);
+
+        self.two_factor.save(two_factor).await?;
+
+        let recovery_codes = self.issue_recovery_codes(&user.id).await?;
+
+        self.audit_log
+            .record(AuditEntry::new(audit, "auth.two_factor_enable", "user", Some(user.id)))
+            .await?;
+
+        self.revoke_sessions(&user.id).await?;
+
+        let session = self.open_authorized_session(user, client).await?;
+
+        Ok(TwoFactorActivation {
+            session,
+            recovery_codes,
+        })
+    }
+
+    async fn disable_two_factor(
+        &self,
+        user: &User,
+        code: &str,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        if user.has_any_role(&self.config.two_factor_required_roles) {
+            let message = "La verificación en dos pasos es obligatoria para tu rol".to_string();
+            return Err(AppError::Forbidden(message));
+        }
+
+        let Some(two_factor) = self.find_enabled_two_factor(&user.id).await? else {
+            return Err(two_factor_not_enabled());
+        };
+
+        if !self.consume_totp_code(&two_factor, code).await? {
+            return Err(invalid_code_input());
+        }
+
+        self.two_factor.delete(&user.id).await?;
+
+        self.audit_log
+            .record(AuditEntry::new(audit, "auth.two_factor_disable", "user", Some(user.id)))
+            .await
+    }
+
+    /// Invalida los códigos de recuperación anteriores y emite otros nuevos.
+    async fn regenerate_recovery_codes(
+        &self,
+        user: &User,
+        code: &str,
+        audit: &AuditContext,
+    ) -> Result<Vec<String>, AppError> {
+        let Some(two_factor) = self.find_enabled_two_factor(&user.id).await? else {
+            return Err(two_factor_not_enabled());
+        };
+
+        if !self.consume_totp_code(&two_factor, code).await? {
+            return Err(invalid_code_input());
+        }
+
+        let recovery_codes = self.issue_recovery_codes(&user.id).await?;
+
+        self.audit_log
+            .record(AuditEntry::new(audit, "auth.recovery_codes_regenerate", "user", Some(user.id)))
+            .await?;
+
+        Ok(recovery_codes)
+    }
+
+    fn trusts_proxy_headers(&self) -> bool {
+        self.config.trusted_proxy
+    }
+}
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use shaku::{Component, Interface};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Mutex, MutexGuard},
+};
+use uuid::Uuid;
+
+use crate::{auth::Session, config::RedisConfig, shared::errors::AppError};
+
+#[async_trait]
+pub trait SessionStore: Interface {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Session>, AppError>;
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<Session>, AppError>;
+    async fn save(&self, session: Session) -> Result<Session, AppError>;
+
+    async fn remove(&self, id: &Uuid) -> Result<(), AppError>;
+    async fn remove_all(&self, user_id: &Uuid) -> Result<(), AppError>;
+}
+
+/// Sesiones almacenadas en Redis como `session:<id>` (JSON con expiración)
+/// junto a un set `user_sessions:<user_id>` con los ids de cada usuario.
+#[derive(Component)]
+#[shaku(interface = SessionStore)]
+pub struct RedisSessionStore {
+    connection: ConnectionManager,
+}
+
+impl RedisSessionStore {
+    pub async fn new(config: &RedisConfig) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(config.url.as_str())?;
+        let connection = ConnectionManager::new(client).await?;
+
+        Ok(Self { connection })
+    }
+}
+
+impl From<RedisSessionStore> for RedisSessionStoreParameters {
+    fn from(value: RedisSessionStore) -> Self {
+        RedisSessionStoreParameters {
+            connection: value.connection,
+        }
+    }
+}
+
+fn session_key(id: impl Display) -> String {
+    format!("session:{id}")
+}
+
+fn user_sessions_key(user_id: impl Display) -> String {
+    format!("user_sessions:{user_id}")
+}
+
+fn parse_session(payload: &str) -> Result<Session, AppError> {
+    serde_json::from_str(payload).map_err(|e| AppError::InternalServerError(Box::new(e)))
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Session>, AppError> {
+        let mut connection = self.connection.clone();
+
+        let payload: Option<String> =
+            redis::cmd("GET").arg(session_key(id)).query_async(&mut connection).await?;
+
+        payload.as_deref().map(parse_session).transpose()
+    }
+
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<Session>, AppError> {
+        let mut connection = self.connection.clone();
+
+        let ids: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(user_sessions_key(user_id))
+            .query_async(&mut connection)
+            .await?;
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let keys = ids.iter().map(session_key).collect::<Vec<_>>();
+        let payloads: Vec<Option<String>> =
+            redis::cmd("MGET").arg(&keys).query_async(&mut connection).await?;
+
+        let mut sessions = vec![];
+        let mut expired = vec![];
+
+        for (id, payload) in ids.into_iter().zip(payloads) {
+            match payload {
+                Some(payload) => sessions.push(parse_session(&payload)?),
+                None => expired.push(id),
+            }
+        }
+
+        // Las sesiones expiradas por TTL se limpian del índice del usuario
+
+        if !expired.is_empty() {
+            redis::cmd("SREM")
+                .arg(user_sessions_key(user_id))
+                .arg(&expired)
+                .query_async::<()>(&mut connection)
+                .await?;
+        }
+
+        sessions.sort_by(|a, b| b.last_seen_at.cmp(&a.last_seen_at));
+
+        Ok(sessions)
+    }
+
+    async fn save(&self, session: Session) -> Result<Session, AppError> {
+        let mut connection = self.connection.clone();
+
+        let ttl_ms = (session.expires_at - Utc::now()).num_milliseconds().max(1);
+        let payload = serde_json::to_string(&session)
+            .map_err(|e| AppError::InternalServerError(Box::new(e)))?;
+
+        redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(session_key(&session.id))
+            .arg(payload)
+            .arg("PX")
+            .arg(ttl_ms)
+            .ignore()
+            .cmd("SADD")
+            .arg(user_sessions_key(&session.user_id))
+            .arg(session.id.to_string())
+            .ignore()
+            .query_async::<()>(&mut connection)
+            .await?;
+
+        Ok(session)
+    }
+
+    async fn remove(&self, id: &Uuid) -> Result<(), AppError> {
+        let Some(session) = self.find_by_id(id).await? else {
+            return Ok(());
+        };
+
+        let mut connection = self.connection.clone();
+
+        redis::pipe()
+            .atomic()
+            .cmd("DEL")
+            .arg(session_key(id))
+            .ignore()
+            .cmd("SREM")
+            .arg(user_sessions_key(&session.user_id))
+            .arg(id.to_string())
+            .ignore()
+            .query_async::<()>(&mut connection)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_all(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let mut connection = self.connection.clone();
+
+        let ids: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(user_sessions_key(user_id))
+            .query_async(&mut connection)
+            .await?;
+
+        let mut keys = ids.iter().map(session_key).collect::<Vec<_>>();
+        keys.push(user_sessions_key(user_id));
+
+        redis::cmd("DEL").arg(&keys).query_async::<()>(&mut connection).await?;
+
+        Ok(())
+    }
+}
+
+/// Implementación en memoria de las sesiones, pensada para pruebas
+/// o despliegues de una sola instancia.
+#[derive(Default)]
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<Uuid, Session>>,
+}
+
+impl InMemorySessionStore {
+    fn sessions(&self) -> MutexGuard<'_, HashMap<Uuid, Session>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        sessions.retain(|_, session| !session.is_expired());
+
+        sessions
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Session>, AppError> {
+        Ok(self.sessions().get(id).cloned())
+    }
+
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<Session>, AppError> {
+        let mut sessions = self
+            .sessions()
+            .values()
+            .filter(|session| session.user_id == *user_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        sessions.sort_by(|a, b| b.last_seen_at.cmp(&a.last_seen_at));
+
+        Ok(sessions)
+    }
+
+    async fn save(&self, session: Session) -> Result<Session, AppError> {
+        self.sessions().insert(session.id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn remove(&self, id: &Uuid) -> Result<(), AppError> {
+        self.sessions().remove(id);
+
+        Ok(())
+    }
+
+    async fn remove_all(&self, user_id: &Uuid) -> Result<(), AppError> {
+        self.sessions().retain(|_, session| session.user_id != *user_id);
+
+        Ok(())
+    }
+}
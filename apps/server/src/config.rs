@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use sword::prelude::config;
 
+use crate::{shared::services::hasher::HashAlgorithm, users::Role};
+
 #[derive(Debug, Deserialize)]
 #[config(key = "application")]
 pub struct ApplicationConfig {
@@ -19,14 +21,31 @@ pub struct PostgresDbConfig {
     pub acquire_timeout_ms: u64,
 }
 
-// #[derive(Debug, Deserialize)]
-// #[config(key = "auth")]
-// pub struct AuthConfig {
-//     pub session_jwt_secret: String,
-//     pub session_jwt_exp_ms: u32,
-//     pub refresh_jwt_secret: String,
-//     pub refresh_jwt_exp_ms: u32,
-// }
+#[derive(Debug, Deserialize)]
+#[config(key = "redis")]
+pub struct RedisConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[config(key = "auth")]
+pub struct AuthConfig {
+    pub session_jwt_secret: String,
+    pub session_jwt_exp_ms: u64,
+    pub refresh_jwt_secret: String,
+    pub refresh_jwt_exp_ms: u64,
+    pub password_reset_exp_ms: u64,
+    pub email_change_exp_ms: u64,
+    pub supervisor_link_exp_ms: u64,
+    pub login_lockout_threshold: i32,
+    pub login_backoff_base_ms: u64,
+    pub login_backoff_max_ms: u64,
+    pub password_hasher: HashAlgorithm,
+    pub bcrypt_cost: u32,
+    pub totp_issuer: String,
+    pub two_factor_required_roles: Vec<Role>,
+    pub trusted_proxy: bool,
+}
 
 #[derive(Debug, Deserialize)]
 #[config(key = "cors")]
@@ -1,10 +1,15 @@
-use shaku::module;
+use shaku::{module, ModuleBuilder};
 
-use crate::{courses, enrollments, imports, practices, shared::database::PostgresDatabase, users};
+use crate::{
+    audit, auth, config::AuthConfig, courses, enrollments, imports, periods, practices,
+    shared::database::PostgresDatabase, users,
+};
 
 use crate::shared::services::{
     event_queue::{TokioEventQueue, TokioEventSender},
-    hasher::BcryptPasswordHasher,
+    hasher::ConfigurablePasswordHasher,
+    jwt::JsonWebTokenService,
+    totp::RfcTotpService,
 };
 
 pub struct DependencyContainer {
@@ -12,21 +17,46 @@ pub struct DependencyContainer {
 }
 
 impl DependencyContainer {
-    pub fn new(postgres_conn: PostgresDatabase, sender: TokioEventSender) -> Self {
-        let module = AppModule::builder()
-            .with_component_parameters::<PostgresDatabase>(postgres_conn.into())
-            .with_component_parameters::<TokioEventSender>(sender.into())
+    pub fn new(
+        postgres_conn: PostgresDatabase,
+        session_store: auth::RedisSessionStore,
+        sender: TokioEventSender,
+        auth_config: AuthConfig,
+    ) -> Self {
+        let module = Self::builder(postgres_conn, sender, auth_config)
+            .with_component_parameters::<auth::RedisSessionStore>(session_store.into())
             .build();
 
         DependencyContainer { module }
     }
+
+    /// Builder del módulo con los parámetros ya configurados, permite
+    /// reemplazar componentes (p. ej. las sesiones y los contadores de login
+    /// en pruebas). El `RedisSessionStore` debe proveerse o reemplazarse.
+    pub fn builder(
+        postgres_conn: PostgresDatabase,
+        sender: TokioEventSender,
+        auth_config: AuthConfig,
+    ) -> ModuleBuilder<AppModule> {
+        AppModule::builder()
+            .with_component_parameters::<PostgresDatabase>(postgres_conn.into())
+            .with_component_parameters::<TokioEventSender>(sender.into())
+            .with_component_parameters::<ConfigurablePasswordHasher>(auth_config.clone().into())
+            .with_component_parameters::<JsonWebTokenService>(auth_config.clone().into())
+            .with_component_parameters::<RfcTotpService>(auth_config.clone().into())
+            .with_component_parameters::<auth::AuthServiceImpl>(auth_config.clone().into())
+            .with_component_parameters::<users::UserServiceImpl>(auth_config.clone().into())
+            .with_component_parameters::<practices::PracticeServiceImpl>(auth_config.into())
+    }
 }
 
 module! {
     pub AppModule {
         components = [
             PostgresDatabase,
-            BcryptPasswordHasher,
+            ConfigurablePasswordHasher,
+            JsonWebTokenService,
+            RfcTotpService,
 
             TokioEventSender,
 
@@ -35,16 +65,31 @@ module! {
             courses::PostgresCourseRepository,
             courses::CourseServiceImpl,
 
+            periods::PostgresPeriodRepository,
+            periods::PeriodServiceImpl,
+
             enrollments::PostgresEnrollmentRepository,
             enrollments::EnrollmentServiceImpl,
 
             users::PostgresUserRepository,
+            users::PostgresEmailChangeRepository,
             users::UserServiceImpl,
 
             imports::ImportServiceImpl,
 
             practices::PostgresPracticeRepository,
             practices::PracticeServiceImpl,
+            practices::PostgresSupervisorTokenRepository,
+
+            audit::PostgresAuditRepository,
+            audit::AuditServiceImpl,
+
+            auth::PostgresRefreshTokenRepository,
+            auth::PostgresPasswordResetTokenRepository,
+            auth::PostgresLoginAttemptRepository,
+            auth::PostgresTwoFactorRepository,
+            auth::RedisSessionStore,
+            auth::AuthServiceImpl,
         ],
         providers = []
     }
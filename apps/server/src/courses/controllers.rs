@@ -1,13 +1,19 @@
+use serde_json::json;
 use sword::prelude::*;
 use uuid::Uuid;
 
 use crate::{
+    auth::{RoleMiddleware, SessionContext, SessionMiddleware},
     container::AppModule,
-    courses::{CourseResponse, CourseService, CreateCourseDto, UpdateCourseDto},
+    courses::{
+        AddStaffMemberDto, CloneCourseDto, ClonedCourseResponse, CourseResponse, CourseService,
+        CreateCourseDto, FinalGradeResponse, GetCoursesQueryDto, StaffMemberResponse,
+        UpdateCourseDto, UpdatedCourseResponse,
+    },
     enrollments::{
-        CreateEnrollmentDto, EnrollmentFilter, EnrollmentResponse, EnrollmentService,
-        UpdateEnrollmentDto,
+        CreateEnrollmentDto, EnrollmentResponse, EnrollmentService, UpdateEnrollmentDto,
     },
+    users::Role,
 };
 
 #[controller("/courses")]
@@ -15,37 +21,46 @@ pub struct CoursesController {}
 
 #[routes]
 impl CoursesController {
-    /// Obtener todos los cursos registrados en el sistema
+    /// Obtener los cursos registrados en el sistema, filtrados y paginados
     /// Se transforma el output sumando los miembros del staff a cada curso (teacher + coord)
 
     #[get("/")]
+    #[middleware(SessionMiddleware)]
     async fn get_courses(ctx: Context) -> HttpResult<HttpResponse> {
+        let query = ctx.validated_query::<GetCoursesQueryDto>()?;
         let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
-        let asignatures = service
-            .get_all()
-            .await?
-            .into_iter()
-            .map(CourseResponse::from)
-            .collect::<Vec<_>>();
 
-        Ok(HttpResponse::Ok().data(asignatures))
+        let data = service.get_all(query.into()).await?;
+        let courses = data.items.into_iter().map(CourseResponse::from).collect::<Vec<_>>();
+
+        let json = json!({
+            "courses": courses,
+            "total": data.total,
+            "currentPage": data.current_page,
+            "totalPages": data.total_pages,
+            "hasNext": data.has_next,
+            "hasPrevious": data.has_previous,
+        });
+
+        Ok(HttpResponse::Ok().data(json))
     }
 
     /// Obtener todos las inscripciones de un curso, incluye la información
-    /// de cada estudiante, sus notas y demás.
+    /// de cada estudiante, sus notas y demás. Un profesor o coordinador solo
+    /// puede ver los cursos que tiene a cargo.
 
     #[get("/{id}/students")]
+    #[middleware(
+        RoleMiddleware,
+        config = &[Role::Administrator, Role::Secretary, Role::Teacher, Role::Coordinator]
+    )]
     async fn get_course_enrollments(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
         let course_id = ctx.param::<Uuid>("id")?;
         let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
 
-        let filter = EnrollmentFilter {
-            course_id: Some(course_id),
-            ..Default::default()
-        };
-
         let enrollments = service
-            .get_all(filter)
+            .get_by_course(&user, &course_id)
             .await?
             .into_iter()
             .map(EnrollmentResponse::from)
@@ -57,51 +72,146 @@ impl CoursesController {
     /// Crear y registrar un nuevo curso en el sistema
 
     #[post("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn create_course(ctx: Context) -> HttpResult<HttpResponse> {
         let input = ctx.validated_body::<CreateCourseDto>()?;
         let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
 
-        let asignature = service.create(input).await?;
+        let asignature = service.create(input, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Created().data(asignature))
     }
 
+    /// Crear una copia del curso para otro año, con las mismas evaluaciones
+    /// y, opcionalmente, los estudiantes que deben repetirlo.
+
+    #[post("/{id}/clone")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
+    async fn clone_course(ctx: Context) -> HttpResult<HttpResponse> {
+        let course_id = ctx.param::<Uuid>("id")?;
+        let input = ctx.validated_body::<CloneCourseDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
+
+        let (course, enrollments) =
+            service.clone_course(&course_id, input, &ctx.audit_context()).await?;
+
+        let response = ClonedCourseResponse {
+            course,
+            carried_over_students: enrollments.iter().map(|e| e.student_id).collect(),
+        };
+
+        Ok(HttpResponse::Created().data(response))
+    }
+
     /// Registrar un estudiante en un curso (enrollment)
 
     #[post("/enroll")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn create_enrollment(ctx: Context) -> HttpResult<HttpResponse> {
         let input = ctx.validated_body::<CreateEnrollmentDto>()?;
         let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
 
-        let enrollment = service.create(input).await?;
+        let enrollment = service.create(input, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Created().data(enrollment))
     }
 
     /// Actualizar la información de un curso
-    /// Profesor, cordinador a cargo y/o estado del curso
+    /// Profesor, cordinador a cargo y/o evaluaciones del curso
 
     #[patch("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn update_course(ctx: Context) -> HttpResult<HttpResponse> {
         let asignature_id = ctx.param::<Uuid>("id")?;
         let input = ctx.validated_body::<UpdateCourseDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
-        let updated_asignature = service.update(&asignature_id, input).await?;
+        let (course, affected_enrollments) =
+            service.update(&asignature_id, input, &ctx.audit_context()).await?;
+
+        let response = UpdatedCourseResponse {
+            course,
+            affected_enrollments,
+        };
+
+        Ok(HttpResponse::Ok().data(response))
+    }
+
+    /// Cerrar un curso: calcula y guarda las notas finales ponderadas, que
+    /// desde entonces no pueden modificarse. Requiere todas las notas registradas.
+
+    #[post("/{id}/close")]
+    #[middleware(
+        RoleMiddleware,
+        config = &[Role::Administrator, Role::Secretary, Role::Teacher, Role::Coordinator]
+    )]
+    async fn close_course(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let course_id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
+
+        let (course, final_grades) = service.close(&user, &course_id, &ctx.audit_context()).await?;
+        let final_grades =
+            final_grades.into_iter().map(FinalGradeResponse::from).collect::<Vec<_>>();
+
+        let json = json!({
+            "course": course,
+            "finalGrades": final_grades,
+        });
 
-        Ok(HttpResponse::Ok().data(updated_asignature))
+        Ok(HttpResponse::Ok().data(json))
+    }
+
+    /// Agregar un docente colaborador o ayudante al equipo del curso, o
+    /// cambiar su rol. Solo el profesor a cargo, secretaría o administración.
+
+    #[post("/{id}/staff")]
+    #[middleware(
+        RoleMiddleware,
+        config = &[Role::Administrator, Role::Secretary, Role::Teacher]
+    )]
+    async fn add_staff_member(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let course_id = ctx.param::<Uuid>("id")?;
+        let input = ctx.validated_body::<AddStaffMemberDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
+
+        let member = service.add_staff(&user, &course_id, input, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Created().data(StaffMemberResponse::from(member)))
+    }
+
+    /// Quitar a un docente colaborador o ayudante del equipo del curso.
+
+    #[delete("/{id}/staff/{user_id}")]
+    #[middleware(
+        RoleMiddleware,
+        config = &[Role::Administrator, Role::Secretary, Role::Teacher]
+    )]
+    async fn remove_staff_member(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let course_id = ctx.param::<Uuid>("id")?;
+        let user_id = ctx.param::<Uuid>("user_id")?;
+        let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
+
+        service.remove_staff(&user, &course_id, &user_id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
     }
 
     /// Actualizar la información de un estudiante inscrito en un curso
-    /// Notas, práctica, etc.
+    /// Notas, práctica, etc. Solo los docentes del curso que registran notas.
 
     #[patch("/enrollments/{enrollment_id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Teacher])]
     async fn update_enrollment(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
         let enrollment_id = ctx.param::<Uuid>("enrollment_id")?;
         let input = ctx.validated_body::<UpdateEnrollmentDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
-        let updated_enrollment = service.update(&enrollment_id, input).await?;
+        let updated_enrollment =
+            service.update(&user, &enrollment_id, input, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok().data(updated_enrollment))
     }
@@ -110,11 +220,12 @@ impl CoursesController {
     /// estudiantes inscritos en el.
 
     #[delete("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn remove(ctx: Context) -> HttpResult<HttpResponse> {
         let course_id = ctx.param::<Uuid>("id")?;
         let service = ctx.get_dependency::<AppModule, dyn CourseService>()?;
 
-        service.remove(&course_id).await?;
+        service.remove(&course_id, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok())
     }
@@ -122,11 +233,12 @@ impl CoursesController {
     /// Eliminar inscripción de un estudiante en un curso.
 
     #[delete("/enrollments/{enrollment_id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn remove_enrollment(ctx: Context) -> HttpResult<HttpResponse> {
         let enrollment_id = ctx.param::<Uuid>("enrollment_id")?;
         let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
 
-        service.remove(&enrollment_id).await?;
+        service.remove(&enrollment_id, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok())
     }
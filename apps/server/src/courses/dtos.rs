@@ -1,15 +1,16 @@
 use serde::{Deserialize, Serialize};
-use sqlx::prelude::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    courses::{Course, CourseEvaluation, CourseStatus},
+    courses::{Course, CourseEvaluation, CourseFilter, CourseStaff, CourseStatus, StaffRole},
+    enrollments::{Enrollment, StudentScore},
     shared::{
+        entities::MAX_PAGE_SIZE,
         errors::{AppError, Input},
         validators::validate_uuid,
     },
-    users::User,
+    users::{User, UserResponse},
 };
 
 // ============================================================================
@@ -47,6 +48,13 @@ pub struct CreateCourseDto {
 
     #[validate(custom(function = validate_uuid))]
     pub teacher_id: String,
+
+    #[validate(custom(function = validate_uuid))]
+    pub coordinator_id: Option<String>,
+
+    /// Semestre del curso, debe corresponder al año indicado.
+    #[validate(custom(function = validate_uuid))]
+    pub period_id: Option<String>,
 }
 
 impl From<CreateCourseDto> for Course {
@@ -59,6 +67,8 @@ impl From<CreateCourseDto> for Course {
             evaluations: dto.evaluations.into_iter().map(CourseEvaluation::from).collect(),
 
             teacher_id: Uuid::parse_str(&dto.teacher_id).unwrap(),
+            coordinator_id: dto.coordinator_id.map(|id| Uuid::parse_str(&id).unwrap()),
+            period_id: dto.period_id.map(|id| Uuid::parse_str(&id).unwrap()),
             course_status: CourseStatus::Active,
         }
     }
@@ -117,16 +127,26 @@ pub struct UpdateCourseDto {
     #[validate(custom(function = validate_uuid))]
     pub teacher_id: Option<String>,
 
+    #[validate(custom(function = validate_uuid))]
+    pub coordinator_id: Option<String>,
+
+    #[validate(custom(function = validate_uuid))]
+    pub period_id: Option<String>,
+
     #[validate(
         custom(function = validate_course_status)
     )]
     pub status: Option<String>,
 
+    /// Las evaluaciones con `id` conservan sus notas; las que se omiten se eliminan.
     #[validate(
         nested,
-        custom(function = validate_update_evaluation_weights)
+        custom(function = validate_update_evaluations)
     )]
     pub evaluations: Option<Vec<UpdateEvaluationDto>>,
+
+    /// Confirma que se descarten las notas de las evaluaciones eliminadas.
+    pub drop_removed_scores: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Validate)]
@@ -149,22 +169,145 @@ pub struct UpdateEvaluationDto {
     pub weight: i32,
 }
 
+impl UpdateEvaluationDto {
+    pub fn evaluation_id(&self) -> Option<Uuid> {
+        self.id.as_deref().and_then(|id| Uuid::parse_str(id).ok())
+    }
+}
+
 impl From<UpdateEvaluationDto> for CourseEvaluation {
     fn from(dto: UpdateEvaluationDto) -> Self {
         CourseEvaluation {
-            id: Uuid::new_v4(),
+            id: dto.evaluation_id().unwrap_or_else(Uuid::new_v4),
             name: dto.name,
             weight: dto.weight,
         }
     }
 }
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> CLONE COURSE DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneCourseDto {
+    #[validate(range(min = 2000, max = 2100, message = "El año debe tener 4 dígitos."))]
+    pub year: i32,
+
+    /// Profesor del nuevo curso, por defecto el del curso original.
+    #[validate(custom(function = validate_uuid))]
+    pub teacher_id: Option<String>,
+
+    #[validate(custom(function = validate_uuid))]
+    pub period_id: Option<String>,
+
+    /// Inscribir a los estudiantes que reprobaron o no tienen nota final.
+    pub carry_over_students: Option<bool>,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> CLONED COURSE RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Curso creado a partir de otro, junto a los estudiantes traspasados.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClonedCourseResponse {
+    #[serde(flatten)]
+    pub course: Course,
+    pub carried_over_students: Vec<Uuid>,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> GET COURSES QUERY <<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCoursesQueryDto {
+    /// Busca en el código y el nombre del curso.
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "El término de búsqueda debe tener entre 1 y 100 caracteres."
+    ))]
+    pub search: Option<String>,
+
+    #[validate(range(min = 1, message = "La página debe ser mayor o igual a 1."))]
+    pub page: Option<u64>,
+
+    #[validate(range(
+        min = 1,
+        max = MAX_PAGE_SIZE,
+        message = "El tamaño de página debe estar entre 1 y 100."
+    ))]
+    pub per_page: Option<u64>,
+
+    #[validate(regex(
+        path = *ASIGNATURE_CODE_REGEX,
+        message = "El código debe seguir el formato INFO{NNNN}."
+    ))]
+    pub code: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "El nombre debe tener entre 1 y 100 caracteres."
+    ))]
+    pub name: Option<String>,
+
+    #[validate(range(min = 2000, max = 2100, message = "El año debe tener 4 dígitos."))]
+    pub year: Option<i32>,
+
+    pub teacher_id: Option<Uuid>,
+
+    /// Cursos en que el usuario participa con cualquier rol del equipo.
+    pub staff_id: Option<Uuid>,
+
+    pub period_id: Option<Uuid>,
+
+    #[validate(custom(function = validate_course_status))]
+    pub status: Option<String>,
+}
+
+impl From<GetCoursesQueryDto> for CourseFilter {
+    fn from(dto: GetCoursesQueryDto) -> Self {
+        CourseFilter {
+            page: dto.page.unwrap_or(1),
+            per_page: dto.per_page,
+            search: dto.search,
+            code: dto.code,
+            name: dto.name,
+            year: dto.year,
+            teacher_id: dto.teacher_id,
+            staff_id: dto.staff_id,
+            period_id: dto.period_id,
+            status: dto.status.and_then(|status| CourseStatus::from_str(&status).ok()),
+            ..CourseFilter::default()
+        }
+    }
+}
+
 // ============================================================================
 // >>>>>>>>>>>>>>>>>>>>>>>>>>>> COURSE RESPONSE DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<
 // ============================================================================
 
-pub type CourseWithStaff = (Course, User);
+/// Integrante del equipo docente junto a su usuario.
+pub type StaffMember = (CourseStaff, User);
+
+/// Curso junto a su equipo docente completo, ordenado por rol.
+pub type CourseWithStaff = (Course, Vec<StaffMember>);
+
+/// Usuario del equipo con el rol indicado (profesor a cargo o coordinador).
+pub fn staff_user(staff: &[StaffMember], role: StaffRole) -> Option<User> {
+    staff
+        .iter()
+        .find(|(member, _)| member.role == role)
+        .map(|(_, user)| user.clone())
+}
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseResponse {
     pub id: Uuid,
@@ -174,11 +317,15 @@ pub struct CourseResponse {
     pub course_status: CourseStatus,
     pub evaluations: Vec<CourseEvaluation>,
     pub teacher_id: Uuid,
-    pub teacher: User,
+    pub teacher: Option<UserResponse>,
+    pub coordinator_id: Option<Uuid>,
+    pub coordinator: Option<UserResponse>,
+    pub period_id: Option<Uuid>,
+    pub staff: Vec<StaffMemberResponse>,
 }
 
 impl From<CourseWithStaff> for CourseResponse {
-    fn from((course, teacher): CourseWithStaff) -> Self {
+    fn from((course, staff): CourseWithStaff) -> Self {
         CourseResponse {
             id: course.id,
             year: course.year,
@@ -187,7 +334,89 @@ impl From<CourseWithStaff> for CourseResponse {
             evaluations: course.evaluations,
             teacher_id: course.teacher_id,
             course_status: course.course_status,
-            teacher,
+            coordinator_id: course.coordinator_id,
+            period_id: course.period_id,
+            teacher: staff_user(&staff, StaffRole::Lead).map(UserResponse::from),
+            coordinator: staff_user(&staff, StaffRole::Coordinator).map(UserResponse::from),
+            staff: staff.into_iter().map(StaffMemberResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaffMemberResponse {
+    pub user_id: Uuid,
+    pub role: StaffRole,
+    pub user: UserResponse,
+}
+
+impl From<StaffMember> for StaffMemberResponse {
+    fn from((member, user): StaffMember) -> Self {
+        StaffMemberResponse {
+            user_id: member.user_id,
+            role: member.role,
+            user: UserResponse::from(user),
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> ADD STAFF MEMBER DTO <<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// El profesor a cargo y el coordinador se cambian al actualizar el curso.
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AddStaffMemberDto {
+    #[validate(custom(function = validate_uuid))]
+    pub user_id: String,
+
+    #[validate(custom(function = validate_assignable_role))]
+    pub role: StaffRole,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>> UPDATED COURSE RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Curso actualizado junto a las inscripciones cuyas notas se descartaron
+/// al eliminar evaluaciones.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatedCourseResponse {
+    #[serde(flatten)]
+    pub course: Course,
+    pub affected_enrollments: Vec<Uuid>,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> FINAL GRADE RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Inscripción con su nota final calculada al cerrar el curso.
+pub type FinalGrade = (Enrollment, User);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalGradeResponse {
+    pub enrollment_id: Uuid,
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub student_rut: String,
+    pub student_scores: Vec<StudentScore>,
+    pub final_grade: Option<f64>,
+}
+
+impl From<FinalGrade> for FinalGradeResponse {
+    fn from((enrollment, student): FinalGrade) -> Self {
+        FinalGradeResponse {
+            enrollment_id: enrollment.id,
+            student_id: student.id,
+            student_name: student.name,
+            student_rut: student.rut,
+            student_scores: enrollment.student_scores,
+            final_grade: enrollment.final_grade,
         }
     }
 }
@@ -218,12 +447,18 @@ fn validate_evaluation_weights(evaluations: &[CourseEvaluationDto]) -> Result<()
     validate_weights(&weights)
 }
 
-fn validate_update_evaluation_weights(
-    evaluations: &[UpdateEvaluationDto],
-) -> Result<(), ValidationError> {
+fn validate_update_evaluations(evaluations: &[UpdateEvaluationDto]) -> Result<(), ValidationError> {
     let weights: Vec<i32> = evaluations.iter().map(|e| e.weight).collect();
 
-    validate_weights(&weights)
+    validate_weights(&weights)?;
+
+    let ids = evaluations.iter().filter_map(|e| e.id.as_deref()).collect::<Vec<_>>();
+
+    if ids.iter().enumerate().any(|(i, id)| ids[..i].contains(id)) {
+        return Err(ValidationError::new("Una evaluación no puede repetirse."));
+    }
+
+    Ok(())
 }
 
 fn validate_course_status(status: &String) -> Result<(), ValidationError> {
@@ -235,3 +470,11 @@ fn validate_course_status(status: &String) -> Result<(), ValidationError> {
 
     Ok(())
 }
+
+fn validate_assignable_role(role: &StaffRole) -> Result<(), ValidationError> {
+    if !role.is_assignable() {
+        return Err(ValidationError::new("El rol debe ser 'co_teacher' o 'assistant'."));
+    }
+
+    Ok(())
+}
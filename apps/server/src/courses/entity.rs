@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use uuid::Uuid;
 
+use crate::{enrollments::StudentScore, users::Role};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct Course {
@@ -13,6 +15,106 @@ pub struct Course {
     pub course_status: CourseStatus,
     pub evaluations: Vec<CourseEvaluation>,
     pub teacher_id: Uuid,
+    pub coordinator_id: Option<Uuid>,
+    pub period_id: Option<Uuid>,
+}
+
+impl Course {
+    /// Un curso cerrado tiene sus notas finales calculadas y ya no admite
+    /// cambios en las notas ni en las evaluaciones.
+    pub fn is_closed(&self) -> bool {
+        matches!(self.course_status, CourseStatus::Completed)
+    }
+
+    /// Promedio ponderado de las notas según el peso de cada evaluación,
+    /// redondeado a un decimal. `None` si falta la nota de alguna evaluación.
+    pub fn final_grade(&self, scores: &[StudentScore]) -> Option<f64> {
+        let mut total = 0.0;
+
+        for evaluation in &self.evaluations {
+            let score = scores.iter().find(|s| s.evaluation_id == evaluation.id)?;
+            total += score.score * f64::from(evaluation.weight) / 100.0;
+        }
+
+        Some((total * 10.0).round() / 10.0)
+    }
+
+    /// Profesor a cargo y coordinador, que se guardan también en el curso.
+    pub fn assigned_staff(&self) -> Vec<CourseStaff> {
+        let lead = CourseStaff {
+            course_id: self.id,
+            user_id: self.teacher_id,
+            role: StaffRole::Lead,
+        };
+
+        let coordinator = self.coordinator_id.map(|user_id| CourseStaff {
+            course_id: self.id,
+            user_id,
+            role: StaffRole::Coordinator,
+        });
+
+        std::iter::once(lead).chain(coordinator).collect()
+    }
+}
+
+/// Integrante del equipo docente de un curso.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseStaff {
+    pub course_id: Uuid,
+    pub user_id: Uuid,
+    pub role: StaffRole,
+}
+
+/// El profesor a cargo y el coordinador se asignan en el curso; los
+/// docentes colaboradores y ayudantes se agregan al equipo por separado.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq, PartialOrd, Ord)]
+#[sqlx(type_name = "course_staff_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum StaffRole {
+    Lead,
+    CoTeacher,
+    Assistant,
+    Coordinator,
+}
+
+impl StaffRole {
+    /// Rol de usuario que se exige para integrar el equipo con este rol.
+    pub fn required_role(&self) -> Role {
+        match self {
+            StaffRole::Coordinator => Role::Coordinator,
+            _ => Role::Teacher,
+        }
+    }
+
+    /// El coordinador supervisa el curso pero no registra notas.
+    pub fn can_enter_scores(&self) -> bool {
+        !matches!(self, StaffRole::Coordinator)
+    }
+
+    /// Un ayudante registra notas, pero no puede cerrar el curso.
+    pub fn can_close_course(&self) -> bool {
+        !matches!(self, StaffRole::Assistant)
+    }
+
+    pub fn can_manage_staff(&self) -> bool {
+        matches!(self, StaffRole::Lead)
+    }
+
+    /// Roles que se agregan o quitan del equipo sin modificar el curso.
+    pub fn is_assignable(&self) -> bool {
+        matches!(self, StaffRole::CoTeacher | StaffRole::Assistant)
+    }
+
+    /// Nombre del rol en los correos.
+    pub fn label(&self) -> &'static str {
+        match self {
+            StaffRole::Lead => "docente encargado",
+            StaffRole::CoTeacher => "docente colaborador",
+            StaffRole::Assistant => "ayudante",
+            StaffRole::Coordinator => "coordinador",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -31,6 +133,16 @@ pub enum CourseStatus {
     Completed,
 }
 
+impl CourseStatus {
+    /// Nombre del estado en el tipo `course_status` de la base de datos.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CourseStatus::Active => "active",
+            CourseStatus::Completed => "completed",
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub enum Courses {
     Table,
@@ -40,6 +152,8 @@ pub enum Courses {
     Name,
     CourseStatus,
     TeacherId,
+    CoordinatorId,
+    PeriodId,
 }
 
 impl Iden for Courses {
@@ -52,6 +166,26 @@ impl Iden for Courses {
             Courses::Name => "name",
             Courses::CourseStatus => "course_status",
             Courses::TeacherId => "teacher_id",
+            Courses::CoordinatorId => "coordinator_id",
+            Courses::PeriodId => "period_id",
+        }
+    }
+}
+
+pub enum CourseStaffs {
+    Table,
+    CourseId,
+    UserId,
+    Role,
+}
+
+impl Iden for CourseStaffs {
+    fn unquoted(&self) -> &str {
+        match self {
+            CourseStaffs::Table => "course_staff",
+            CourseStaffs::CourseId => "course_id",
+            CourseStaffs::UserId => "user_id",
+            CourseStaffs::Role => "role",
         }
     }
 }
@@ -1,5 +1,8 @@
 use async_trait::async_trait;
-use sea_query::{Expr, ExprTrait, Order, PostgresQueryBuilder, Query};
+use sea_query::{
+    extension::postgres::PgExpr, Expr, ExprTrait, Order, PostgresQueryBuilder, Query,
+    SelectStatement,
+};
 use sea_query_sqlx::SqlxBinder;
 use shaku::{Component, Interface};
 use sqlx::{query_as_with as sqlx_query, Postgres};
@@ -7,8 +10,9 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    courses::entity::{Course, Courses},
-    shared::{database::DatabaseConnection, errors::AppError},
+    courses::entity::{Course, CourseStaff, CourseStaffs, CourseStatus, Courses},
+    enrollments::{Enrollment, Enrollments},
+    shared::{database::DatabaseConnection, entities::DEFAULT_PAGE_SIZE, errors::AppError},
 };
 
 #[derive(Component)]
@@ -18,20 +22,92 @@ pub struct PostgresCourseRepository {
     db_connection: Arc<dyn DatabaseConnection>,
 }
 
+/// Con `page` en 0 no se pagina; así lo usan las búsquedas internas.
 #[derive(Debug, Clone, Default)]
 pub struct CourseFilter {
+    pub page: u64,
+    pub per_page: Option<u64>,
+    pub ids: Option<Vec<Uuid>>,
     pub code: Option<String>,
     pub name: Option<String>,
+    pub search: Option<String>,
+    pub status: Option<CourseStatus>,
     pub teacher_id: Option<Uuid>,
+    pub coordinator_id: Option<Uuid>,
+    pub staff_id: Option<Uuid>,
+    pub period_id: Option<Uuid>,
     pub year: Option<i32>,
 }
 
+/// Cursos en los que el usuario participa con cualquier rol del equipo.
+const STAFF_CONDITION: &str = "id IN (SELECT course_id FROM course_staff WHERE user_id = ?)";
+
+impl CourseFilter {
+    pub fn page_size(&self) -> u64 {
+        self.per_page.unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+
+    fn apply(&self, query: &mut SelectStatement) {
+        query
+            .apply_if(self.ids.clone(), |q, value| {
+                q.and_where(Expr::col(Courses::Id).is_in(value));
+            })
+            .apply_if(self.code.clone(), |q, value| {
+                q.and_where(Expr::col(Courses::Code).eq(value));
+            })
+            .apply_if(self.name.clone(), |q, value| {
+                q.and_where(Expr::col(Courses::Name).like(value));
+            })
+            .apply_if(self.search.clone(), |q, value| {
+                let pattern = format!("%{value}%");
+
+                q.and_where(
+                    Expr::col(Courses::Code)
+                        .ilike(pattern.clone())
+                        .or(Expr::col(Courses::Name).ilike(pattern)),
+                );
+            })
+            .apply_if(self.status.clone(), |q, value| {
+                q.and_where(Expr::cust_with_values(
+                    "course_status = CAST(? AS course_status)",
+                    [value.as_str()],
+                ));
+            })
+            .apply_if(self.teacher_id, |q, value| {
+                q.and_where(Expr::col(Courses::TeacherId).eq(value));
+            })
+            .apply_if(self.coordinator_id, |q, value| {
+                q.and_where(Expr::col(Courses::CoordinatorId).eq(value));
+            })
+            .apply_if(self.staff_id, |q, value| {
+                q.and_where(Expr::cust_with_values(STAFF_CONDITION, [value]));
+            })
+            .apply_if(self.period_id, |q, value| {
+                q.and_where(Expr::col(Courses::PeriodId).eq(value));
+            })
+            .apply_if(self.year, |q, value| {
+                q.and_where(Expr::col(Courses::Year).eq(value));
+            });
+    }
+}
+
 #[async_trait]
 pub trait CourseRepository: Interface {
     async fn find_many(&self, filter: CourseFilter) -> Result<Vec<Course>, AppError>;
+    async fn count(&self, filter: CourseFilter) -> Result<i64, AppError>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Course>, AppError>;
     async fn save(&self, course: Course) -> Result<Course, AppError>;
+    async fn save_with_scores(
+        &self,
+        course: Course,
+        enrollments: &[Enrollment],
+    ) -> Result<Course, AppError>;
     async fn delete(&self, id: &Uuid) -> Result<(), AppError>;
+    async fn close(&self, id: &Uuid, final_grades: &[(Uuid, f64)]) -> Result<bool, AppError>;
+
+    async fn find_staff(&self, course_ids: &[Uuid]) -> Result<Vec<CourseStaff>, AppError>;
+    async fn add_staff(&self, member: CourseStaff) -> Result<CourseStaff, AppError>;
+    async fn remove_staff(&self, course_id: &Uuid, user_id: &Uuid) -> Result<(), AppError>;
 }
 
 #[async_trait]
@@ -39,19 +115,19 @@ impl CourseRepository for PostgresCourseRepository {
     async fn find_many(&self, filter: CourseFilter) -> Result<Vec<Course>, AppError> {
         let mut query = Query::select().expr(Expr::cust("*")).from(Courses::Table).to_owned();
 
-        if let Some(code) = filter.code {
-            query.and_where(Expr::col(Courses::Code).eq(code));
-        }
+        filter.apply(&mut query);
 
-        if let Some(ref name) = filter.name {
-            query.and_where(Expr::col(Courses::Name).like(name));
-        }
+        query
+            .order_by(Courses::Year, Order::Desc)
+            .order_by(Courses::Code, Order::Asc)
+            .order_by(Courses::Id, Order::Asc);
 
-        if let Some(teacher_id) = filter.teacher_id {
-            query.and_where(Expr::col(Courses::TeacherId).eq(teacher_id));
-        }
+        if filter.page > 0 {
+            let page_size = filter.page_size();
 
-        query.order_by(Courses::Year, Order::Desc);
+            query.limit(page_size);
+            query.offset((filter.page - 1) * page_size);
+        }
 
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 
@@ -62,6 +138,23 @@ impl CourseRepository for PostgresCourseRepository {
         Ok(result)
     }
 
+    async fn count(&self, filter: CourseFilter) -> Result<i64, AppError> {
+        let mut query = Query::select()
+            .expr(Expr::count(Expr::col(Courses::Id)))
+            .from(Courses::Table)
+            .to_owned();
+
+        filter.apply(&mut query);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        let count: (i64,) = sqlx::query_as_with(&sql, values)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(count.0)
+    }
+
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Course>, AppError> {
         let (sql, values) = Query::select()
             .expr(Expr::cust("*"))
@@ -77,16 +170,30 @@ impl CourseRepository for PostgresCourseRepository {
     }
 
     async fn save(&self, course: Course) -> Result<Course, AppError> {
+        self.save_with_scores(course, &[]).await
+    }
+
+    /// Guarda el curso junto a las notas de las inscripciones indicadas, de
+    /// modo que un cambio de evaluaciones no deja notas huérfanas.
+    async fn save_with_scores(
+        &self,
+        course: Course,
+        enrollments: &[Enrollment],
+    ) -> Result<Course, AppError> {
         let query = r#"
-            INSERT INTO courses (id, year, code, name, course_status, teacher_id, evaluations)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO courses (id, year, code, name, course_status, teacher_id, evaluations, coordinator_id, period_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ON CONFLICT (id) DO UPDATE SET
                 teacher_id = EXCLUDED.teacher_id,
+                coordinator_id = EXCLUDED.coordinator_id,
+                period_id = EXCLUDED.period_id,
                 course_status = EXCLUDED.course_status,
                 evaluations = EXCLUDED.evaluations
             RETURNING *
         "#;
 
+        let mut tx = self.db_connection.get_pool().begin().await?;
+
         let result = sqlx::query_as::<_, Course>(query)
             .bind(course.id)
             .bind(course.year)
@@ -95,9 +202,45 @@ impl CourseRepository for PostgresCourseRepository {
             .bind(course.course_status)
             .bind(course.teacher_id)
             .bind(&course.evaluations)
-            .fetch_one(self.db_connection.get_pool())
+            .bind(course.coordinator_id)
+            .bind(course.period_id)
+            .fetch_one(&mut *tx)
             .await?;
 
+        // El profesor a cargo y el coordinador reemplazan a los anteriores y a
+        // cualquier otro rol que tuvieran en el equipo
+        let assigned = course.assigned_staff();
+        let assigned_ids = assigned.iter().map(|member| member.user_id).collect::<Vec<_>>();
+
+        sqlx::query(
+            "DELETE FROM course_staff WHERE course_id = $1 AND (role IN ('lead', 'coordinator') OR user_id = ANY($2))",
+        )
+        .bind(course.id)
+        .bind(&assigned_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        for member in assigned {
+            sqlx::query(
+                "INSERT INTO course_staff (course_id, user_id, role) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            )
+            .bind(member.course_id)
+            .bind(member.user_id)
+            .bind(member.role)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for enrollment in enrollments {
+            sqlx::query("UPDATE enrollments SET student_scores = $2 WHERE id = $1")
+                .bind(enrollment.id)
+                .bind(&enrollment.student_scores)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
         Ok(result)
     }
 
@@ -111,4 +254,83 @@ impl CourseRepository for PostgresCourseRepository {
 
         Ok(())
     }
+
+    /// Guarda las notas finales y marca el curso como cerrado en una sola
+    /// transacción. Devuelve `false` si el curso ya estaba cerrado.
+    async fn close(&self, id: &Uuid, final_grades: &[(Uuid, f64)]) -> Result<bool, AppError> {
+        let mut tx = self.db_connection.get_pool().begin().await?;
+
+        let (sql, values) = Query::update()
+            .table(Courses::Table)
+            .value(Courses::CourseStatus, Expr::cust("'completed'::course_status"))
+            .and_where(Expr::col(Courses::Id).eq(*id))
+            .and_where(Expr::cust("course_status = 'active'::course_status"))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        for (enrollment_id, final_grade) in final_grades {
+            let (sql, values) = Query::update()
+                .table(Enrollments::Table)
+                .value(Enrollments::FinalGrade, *final_grade)
+                .and_where(Expr::col(Enrollments::Id).eq(*enrollment_id))
+                .build_sqlx(PostgresQueryBuilder);
+
+            sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn find_staff(&self, course_ids: &[Uuid]) -> Result<Vec<CourseStaff>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(CourseStaffs::Table)
+            .and_where(Expr::col(CourseStaffs::CourseId).is_in(course_ids.to_vec()))
+            .order_by(CourseStaffs::Role, Order::Asc)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx_query::<Postgres, CourseStaff, _>(&sql, values)
+            .fetch_all(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn add_staff(&self, member: CourseStaff) -> Result<CourseStaff, AppError> {
+        let query = r#"
+            INSERT INTO course_staff (course_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (course_id, user_id) DO UPDATE SET
+                role = EXCLUDED.role
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, CourseStaff>(query)
+            .bind(member.course_id)
+            .bind(member.user_id)
+            .bind(member.role)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn remove_staff(&self, course_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::delete()
+            .from_table(CourseStaffs::Table)
+            .and_where(Expr::col(CourseStaffs::CourseId).eq(*course_id))
+            .and_where(Expr::col(CourseStaffs::UserId).eq(*user_id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
 }
@@ -4,14 +4,19 @@ use std::{collections::HashMap, sync::Arc};
 use uuid::Uuid;
 
 use crate::{
+    audit::{AuditContext, AuditEntry, AuditService},
+    auth::{ensure_course_closing, ensure_staff_management},
     course_filter,
     courses::{
-        Course, CourseEvaluation, CourseFilter, CourseRepository, CourseWithStaff, CreateCourseDto,
-        UpdateCourseDto,
+        AddStaffMemberDto, CloneCourseDto, Course, CourseEvaluation, CourseFilter,
+        CourseRepository, CourseStaff, CourseStatus, CourseWithStaff, CreateCourseDto, FinalGrade,
+        StaffMember, UpdateCourseDto, UpdateEvaluationDto,
     },
     enrollment_filter,
-    enrollments::{EnrollmentFilter, EnrollmentRepository},
+    enrollments::{Enrollment, EnrollmentFilter, EnrollmentRepository},
+    periods::{AcademicPeriod, PeriodRepository},
     shared::{
+        entities::Pagination,
         errors::{AppError, Input},
         services::event_queue::{Event, EventQueue},
     },
@@ -31,107 +36,441 @@ pub struct CourseServiceImpl {
     #[shaku(inject)]
     enrollments: Arc<dyn EnrollmentRepository>,
 
+    #[shaku(inject)]
+    periods: Arc<dyn PeriodRepository>,
+
     #[shaku(inject)]
     event_queue: Arc<dyn EventQueue>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
 }
 
 #[async_trait]
 pub trait CourseService: Interface {
-    async fn get_all(&self) -> Result<Vec<CourseWithStaff>, AppError>;
+    async fn get_all(&self, filter: CourseFilter) -> Result<Pagination<CourseWithStaff>, AppError>;
     async fn get_by_id(&self, id: &Uuid) -> Result<CourseWithStaff, AppError>;
 
-    async fn create(&self, input: CreateCourseDto) -> Result<Course, AppError>;
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError>;
+    async fn create(
+        &self,
+        input: CreateCourseDto,
+        audit: &AuditContext,
+    ) -> Result<Course, AppError>;
+    async fn clone_course(
+        &self,
+        id: &Uuid,
+        input: CloneCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<Enrollment>), AppError>;
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError>;
+    async fn close(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<FinalGrade>), AppError>;
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        input: UpdateCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<Uuid>), AppError>;
+
+    async fn add_staff(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        input: AddStaffMemberDto,
+        audit: &AuditContext,
+    ) -> Result<StaffMember, AppError>;
+    async fn remove_staff(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        user_id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
+}
 
-    async fn update(&self, id: &Uuid, input: UpdateCourseDto) -> Result<Course, AppError>;
+/// Equipo inicial del curso: su profesor a cargo y, si tiene, su coordinador.
+fn initial_staff(course: &Course, teacher: User, coordinator: Option<User>) -> Vec<StaffMember> {
+    let users = std::iter::once(teacher).chain(coordinator);
+    course.assigned_staff().into_iter().zip(users).collect()
 }
 
-#[async_trait]
-impl CourseService for CourseServiceImpl {
-    async fn get_all(&self) -> Result<Vec<CourseWithStaff>, AppError> {
-        let courses = self.courses.find_many(CourseFilter::default()).await?;
-        let teacher_ids = courses.iter().map(|c| c.teacher_id).collect::<Vec<_>>();
+impl CourseServiceImpl {
+    async fn ensure_unique(&self, course: &Course) -> Result<(), AppError> {
+        let filter = course_filter! {
+            code: course.code.clone(),
+            name: course.name.clone(),
+            year: course.year,
+        };
 
-        let teachers = self.users.find_many(user_filter! { ids: teacher_ids }).await?;
+        if !self.courses.find_many(filter).await?.is_empty() {
+            return Err(AppError::Conflict(Input {
+                message: "Ya existe un curso con el mismo código o nombre y año".to_string(),
+                ..Default::default()
+            }));
+        }
 
-        let teachers_map: HashMap<Uuid, &User> = teachers.iter().map(|t| (t.id, t)).collect();
+        Ok(())
+    }
 
-        let mut result = vec![];
+    async fn find_teacher(&self, id: &Uuid) -> Result<User, AppError> {
+        let Some(teacher) = self.users.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
 
-        for course in courses {
-            if let Some(teacher) = teachers_map.get(&course.teacher_id) {
-                result.push((course, (*teacher).clone()));
-            }
+        if !teacher.is_teacher() {
+            return Err(AppError::InvalidInput(Input {
+                field: "teacherId".to_string(),
+                message: "El usuario no es un profesor".to_string(),
+                value: id.to_string(),
+            }));
+        }
+
+        Ok(teacher)
+    }
+
+    async fn find_coordinator(&self, id: &Uuid) -> Result<User, AppError> {
+        let Some(coordinator) = self.users.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        if !coordinator.is_coordinator() {
+            return Err(AppError::InvalidInput(Input {
+                field: "coordinatorId".to_string(),
+                message: "El usuario no es un coordinador".to_string(),
+                value: id.to_string(),
+            }));
         }
 
+        Ok(coordinator)
+    }
+
+    /// Carga el equipo docente de cada curso, incluidos los usuarios eliminados.
+    async fn with_staff(&self, courses: Vec<Course>) -> Result<Vec<CourseWithStaff>, AppError> {
+        let course_ids = courses.iter().map(|c| c.id).collect::<Vec<_>>();
+        let members = self.courses.find_staff(&course_ids).await?;
+
+        let user_ids = members.iter().map(|m| m.user_id).collect::<Vec<_>>();
+        let users = self.users.find_many(user_filter! { ids: user_ids }.with_deleted()).await?;
+
+        let users_map: HashMap<Uuid, &User> = users.iter().map(|u| (u.id, u)).collect();
+
+        let result = courses
+            .into_iter()
+            .map(|course| {
+                let staff = members
+                    .iter()
+                    .filter(|member| member.course_id == course.id)
+                    .filter_map(|member| {
+                        let user = users_map.get(&member.user_id)?;
+                        Some((member.clone(), (*user).clone()))
+                    })
+                    .collect();
+
+                (course, staff)
+            })
+            .collect();
+
         Ok(result)
     }
 
+    /// El periodo debe existir y pertenecer al mismo año del curso.
+    async fn find_period(&self, id: &Uuid, year: i32) -> Result<AcademicPeriod, AppError> {
+        let Some(period) = self.periods.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        if period.year != year {
+            return Err(AppError::InvalidInput(Input {
+                field: "periodId".to_string(),
+                message: "El periodo no corresponde al año del curso".to_string(),
+                value: id.to_string(),
+            }));
+        }
+
+        Ok(period)
+    }
+
+    /// Reemplaza las evaluaciones del curso conservando los ids de las que se
+    /// editan. Las notas de las evaluaciones eliminadas solo se descartan con
+    /// confirmación; devuelve las inscripciones cuyas notas cambiaron.
+    async fn replace_evaluations(
+        &self,
+        course: &mut Course,
+        evaluations: Vec<UpdateEvaluationDto>,
+        drop_removed_scores: bool,
+    ) -> Result<Vec<Enrollment>, AppError> {
+        let unknown = evaluations.iter().find(|evaluation| {
+            evaluation
+                .evaluation_id()
+                .is_some_and(|id| !course.evaluations.iter().any(|e| e.id == id))
+        });
+
+        if let Some(evaluation) = unknown {
+            return Err(AppError::InvalidInput(Input {
+                field: "evaluations".to_string(),
+                message: "La evaluación no pertenece al curso".to_string(),
+                value: evaluation.id.clone().unwrap_or_default(),
+            }));
+        }
+
+        let evaluations = evaluations.into_iter().map(CourseEvaluation::from).collect::<Vec<_>>();
+
+        let removed = course
+            .evaluations
+            .iter()
+            .map(|evaluation| evaluation.id)
+            .filter(|id| !evaluations.iter().any(|e| e.id == *id))
+            .collect::<Vec<_>>();
+
+        course.evaluations = evaluations;
+
+        if removed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let affected = self
+            .enrollments
+            .find_many(enrollment_filter! { course_id: course.id })
+            .await?
+            .into_iter()
+            .filter(|e| e.student_scores.iter().any(|s| removed.contains(&s.evaluation_id)))
+            .collect::<Vec<_>>();
+
+        if !affected.is_empty() && !drop_removed_scores {
+            return Err(AppError::Conflict(Input {
+                field: "evaluations".to_string(),
+                message: format!(
+                    "{} inscripción(es) tienen notas en las evaluaciones eliminadas, \
+                     confirma con dropRemovedScores para descartarlas",
+                    affected.len()
+                ),
+                value: removed.iter().map(Uuid::to_string).collect::<Vec<_>>().join(","),
+            }));
+        }
+
+        let affected = affected
+            .into_iter()
+            .map(|mut enrollment| {
+                enrollment.student_scores.retain(|s| !removed.contains(&s.evaluation_id));
+                enrollment
+            })
+            .collect();
+
+        Ok(affected)
+    }
+}
+
+#[async_trait]
+impl CourseService for CourseServiceImpl {
+    async fn get_all(&self, filter: CourseFilter) -> Result<Pagination<CourseWithStaff>, AppError> {
+        let (courses, total) = tokio::try_join!(
+            self.courses.find_many(filter.clone()),
+            self.courses.count(filter.clone())
+        )?;
+
+        let result = self.with_staff(courses).await?;
+
+        Ok(Pagination::new(result, filter.page, filter.page_size(), total))
+    }
+
     async fn get_by_id(&self, id: &Uuid) -> Result<CourseWithStaff, AppError> {
         let Some(course) = self.courses.find_by_id(id).await? else {
             return Err(AppError::ResourceNotFound(*id));
         };
 
-        let teacher = self
-            .users
-            .find_by_id(&course.teacher_id)
+        self.with_staff(vec![course])
             .await?
-            .ok_or(AppError::ResourceNotFound(course.teacher_id))?;
-
-        Ok((course, teacher))
+            .pop()
+            .ok_or(AppError::ResourceNotFound(*id))
     }
 
-    async fn create(&self, input: CreateCourseDto) -> Result<Course, AppError> {
+    async fn create(
+        &self,
+        input: CreateCourseDto,
+        audit: &AuditContext,
+    ) -> Result<Course, AppError> {
         let course = Course::from(input);
 
-        let filter = course_filter! {
-            code: course.code.clone(),
-            name: course.name.clone(),
-            year: course.year,
+        self.ensure_unique(&course).await?;
+        let teacher = self.find_teacher(&course.teacher_id).await?;
+
+        let coordinator = match course.coordinator_id {
+            Some(coordinator_id) => Some(self.find_coordinator(&coordinator_id).await?),
+            None => None,
         };
 
-        if !self.courses.find_many(filter).await?.is_empty() {
-            return Err(AppError::Conflict(Input {
-                message: "Ya existe un curso con el mismo código o nombre y año".to_string(),
-                ..Default::default()
-            }));
+        if let Some(period_id) = course.period_id {
+            self.find_period(&period_id, course.year).await?;
         }
 
-        let Some(teacher) = self.users.find_by_id(&course.teacher_id).await? else {
-            return Err(AppError::ResourceNotFound(course.teacher_id));
+        let course = self.courses.save(course).await?;
+
+        let staff = initial_staff(&course, teacher, coordinator);
+        self.event_queue.publish(Event::CourseCreated((course.clone(), staff))).await;
+
+        let entry =
+            AuditEntry::new(audit, "course.create", "course", Some(course.id)).after(&course);
+        self.audit_log.record(entry).await?;
+
+        Ok(course)
+    }
+
+    /// Crea el curso en otro año con las mismas evaluaciones (con ids nuevos).
+    /// Opcionalmente traspasa a los estudiantes que reprobaron o quedaron sin
+    /// nota final en el curso original.
+    async fn clone_course(
+        &self,
+        id: &Uuid,
+        input: CloneCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<Enrollment>), AppError> {
+        let Some(source) = self.courses.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
         };
 
-        if !teacher.is_teacher() {
-            return Err(AppError::InvalidInput(Input {
-                field: "teacherId".to_string(),
-                message: "El usuario no es un profesor".to_string(),
-                value: course.teacher_id.to_string(),
-            }));
+        let teacher_id = match input.teacher_id {
+            Some(teacher_id) => Uuid::parse_str(&teacher_id).unwrap(),
+            None => source.teacher_id,
+        };
+
+        // El coordinador se conserva solo si sigue activo como tal
+        let coordinator = match source.coordinator_id {
+            Some(coordinator_id) => self.find_coordinator(&coordinator_id).await.ok(),
+            None => None,
+        };
+
+        let course = Course {
+            id: Uuid::new_v4(),
+            year: input.year,
+            course_status: CourseStatus::Active,
+            evaluations: source
+                .evaluations
+                .iter()
+                .map(|evaluation| CourseEvaluation {
+                    id: Uuid::new_v4(),
+                    ..evaluation.clone()
+                })
+                .collect(),
+            teacher_id,
+            coordinator_id: coordinator.as_ref().map(|coordinator| coordinator.id),
+            period_id: input.period_id.map(|id| Uuid::parse_str(&id).unwrap()),
+            ..source.clone()
+        };
+
+        self.ensure_unique(&course).await?;
+        let teacher = self.find_teacher(&course.teacher_id).await?;
+
+        if let Some(period_id) = course.period_id {
+            self.find_period(&period_id, course.year).await?;
+        }
+
+        let mut carried_over = vec![];
+
+        if input.carry_over_students.unwrap_or(false) {
+            let pending = self
+                .enrollments
+                .find_many(enrollment_filter! { course_id: source.id })
+                .await?
+                .into_iter()
+                .filter(|enrollment| !enrollment.is_approved())
+                .collect::<Vec<_>>();
+
+            // Los estudiantes eliminados no se traspasan
+            let student_ids = pending.iter().map(|e| e.student_id).collect::<Vec<_>>();
+            let students = self.users.find_many(user_filter! { ids: student_ids }).await?;
+
+            carried_over = pending
+                .into_iter()
+                .filter(|enrollment| students.iter().any(|s| s.id == enrollment.student_id))
+                .map(|enrollment| Enrollment {
+                    id: Uuid::new_v4(),
+                    student_id: enrollment.student_id,
+                    course_id: course.id,
+                    practice_id: None,
+                    student_scores: vec![],
+                    final_grade: None,
+                })
+                .collect();
         }
 
-        let event_data = (course.clone(), teacher.clone());
+        let course = self.courses.save(course).await?;
+        let carried_over = self.enrollments.create_many(carried_over).await?;
 
-        self.event_queue.publish(Event::CourseCreated(event_data)).await;
+        let staff = initial_staff(&course, teacher, coordinator);
+        self.event_queue.publish(Event::CourseCreated((course.clone(), staff))).await;
 
-        Ok(self.courses.save(course).await?)
+        let entry = AuditEntry::new(audit, "course.clone", "course", Some(course.id))
+            .diff(Some(&source), Some(&course));
+        self.audit_log.record(entry).await?;
+
+        Ok((course, carried_over))
     }
 
-    async fn update(&self, id: &Uuid, input: UpdateCourseDto) -> Result<Course, AppError> {
+    async fn update(
+        &self,
+        id: &Uuid,
+        input: UpdateCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<Uuid>), AppError> {
         let Some(mut course) = self.courses.find_by_id(id).await? else {
             return Err(AppError::ResourceNotFound(*id));
         };
 
+        let before = course.clone();
+
+        // El cierre solo se realiza con `close`, que calcula las notas finales
+        if input.status.is_some_and(|status| status != course.course_status.as_str()) {
+            let message = "El estado del curso solo cambia al cerrarlo".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        if course.is_closed() && input.evaluations.is_some() {
+            let message =
+                "No es posible modificar las evaluaciones de un curso cerrado".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
         if let Some(teacher_id) = input.teacher_id {
-            course.teacher_id = Uuid::parse_str(&teacher_id).unwrap();
+            let teacher = self.find_teacher(&Uuid::parse_str(&teacher_id).unwrap()).await?;
+            course.teacher_id = teacher.id;
         }
 
-        if let Some(evaluation) = input.evaluations {
-            course.evaluations = evaluation.into_iter().map(CourseEvaluation::from).collect();
+        if let Some(coordinator_id) = input.coordinator_id {
+            let coordinator =
+                self.find_coordinator(&Uuid::parse_str(&coordinator_id).unwrap()).await?;
+            course.coordinator_id = Some(coordinator.id);
         }
-        Ok(self.courses.save(course).await?)
+
+        if let Some(period_id) = input.period_id {
+            let period =
+                self.find_period(&Uuid::parse_str(&period_id).unwrap(), course.year).await?;
+            course.period_id = Some(period.id);
+        }
+
+        let affected = match input.evaluations {
+            Some(evaluations) => {
+                let drop_removed_scores = input.drop_removed_scores.unwrap_or(false);
+                self.replace_evaluations(&mut course, evaluations, drop_removed_scores).await?
+            }
+            None => vec![],
+        };
+
+        let course = self.courses.save_with_scores(course, &affected).await?;
+        let affected = affected.iter().map(|enrollment| enrollment.id).collect::<Vec<_>>();
+
+        let entry = AuditEntry::new(audit, "course.update", "course", Some(course.id))
+            .diff(Some(&before), Some(&course));
+        self.audit_log.record(entry).await?;
+
+        Ok((course, affected))
     }
 
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError> {
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError> {
         let Some(course) = self.courses.find_by_id(id).await? else {
             return Err(AppError::ResourceNotFound(*id));
         };
@@ -148,6 +487,191 @@ impl CourseService for CourseServiceImpl {
             }));
         }
 
-        self.courses.delete(id).await
+        self.courses.delete(id).await?;
+
+        let entry =
+            AuditEntry::new(audit, "course.delete", "course", Some(course.id)).before(&course);
+        self.audit_log.record(entry).await
+    }
+
+    /// Cierra el curso: exige la nota de cada evaluación en todas las
+    /// inscripciones, guarda las notas finales ponderadas y congela las notas.
+    async fn close(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<(Course, Vec<FinalGrade>), AppError> {
+        let Some(course) = self.courses.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+        ensure_course_closing(actor, &staff)?;
+
+        if course.is_closed() {
+            return Err(AppError::InvalidOperation("El curso ya está cerrado".to_string()));
+        }
+
+        // Sin evaluaciones todas las notas finales serían 0.0
+        if course.evaluations.is_empty() {
+            let message = "No es posible cerrar un curso sin evaluaciones".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        let enrollments = self.enrollments.find_many(enrollment_filter! { course_id: *id }).await?;
+
+        let mut graded = Vec::with_capacity(enrollments.len());
+        let mut incomplete = 0;
+
+        for mut enrollment in enrollments {
+            match course.final_grade(&enrollment.student_scores) {
+                Some(grade) => {
+                    enrollment.final_grade = Some(grade);
+                    graded.push(enrollment);
+                }
+                None => incomplete += 1,
+            }
+        }
+
+        if incomplete > 0 {
+            let message = format!(
+                "No es posible cerrar el curso, faltan notas en {incomplete} inscripción(es)"
+            );
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        let final_grades = graded
+            .iter()
+            .filter_map(|enrollment| enrollment.final_grade.map(|grade| (enrollment.id, grade)))
+            .collect::<Vec<_>>();
+
+        if !self.courses.close(id, &final_grades).await? {
+            return Err(AppError::InvalidOperation("El curso ya está cerrado".to_string()));
+        }
+
+        let teacher = self
+            .users
+            .find_one(user_filter! { id: course.teacher_id }.with_deleted())
+            .await?
+            .ok_or(AppError::ResourceNotFound(course.teacher_id))?;
+
+        let student_ids = graded.iter().map(|e| e.student_id).collect::<Vec<_>>();
+        let students =
+            self.users.find_many(user_filter! { ids: student_ids }.with_deleted()).await?;
+
+        let mut results = Vec::with_capacity(graded.len());
+
+        for enrollment in graded {
+            if let Some(student) = students.iter().find(|s| s.id == enrollment.student_id) {
+                results.push((enrollment, student.clone()));
+            }
+        }
+
+        results.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+        let closed = Course {
+            course_status: CourseStatus::Completed,
+            ..course.clone()
+        };
+
+        let event_data = (closed.clone(), teacher, results.clone());
+        self.event_queue.publish(Event::CourseClosed(event_data)).await;
+
+        let entry = AuditEntry::new(audit, "course.close", "course", Some(closed.id))
+            .diff(Some(&course), Some(&closed));
+        self.audit_log.record(entry).await?;
+
+        Ok((closed, results))
+    }
+
+    /// Agrega un profesor colaborador o ayudante al curso, o cambia su rol si
+    /// ya forma parte del equipo.
+    async fn add_staff(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        input: AddStaffMemberDto,
+        audit: &AuditContext,
+    ) -> Result<StaffMember, AppError> {
+        let Some(course) = self.courses.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+        ensure_staff_management(actor, &staff)?;
+
+        let user_id = Uuid::parse_str(&input.user_id).unwrap();
+        let before = staff.iter().find(|member| member.user_id == user_id).cloned();
+
+        if before.as_ref().is_some_and(|member| !member.role.is_assignable()) {
+            return Err(AppError::Conflict(Input {
+                field: "userId".to_string(),
+                message: "El usuario ya es el profesor a cargo o el coordinador del curso"
+                    .to_string(),
+                value: input.user_id,
+            }));
+        }
+
+        let Some(user) = self.users.find_by_id(&user_id).await? else {
+            return Err(AppError::ResourceNotFound(user_id));
+        };
+
+        if !user.is_teacher() {
+            return Err(AppError::InvalidInput(Input {
+                field: "userId".to_string(),
+                message: "El usuario no es un profesor".to_string(),
+                value: input.user_id,
+            }));
+        }
+
+        let member = self
+            .courses
+            .add_staff(CourseStaff {
+                course_id: course.id,
+                user_id,
+                role: input.role,
+            })
+            .await?;
+
+        let event_data = (course.clone(), (member.clone(), user.clone()));
+        self.event_queue.publish(Event::StaffAssigned(event_data)).await;
+
+        let entry = AuditEntry::new(audit, "course.staff.add", "course", Some(course.id))
+            .diff(before.as_ref(), Some(&member));
+        self.audit_log.record(entry).await?;
+
+        Ok((member, user))
+    }
+
+    async fn remove_staff(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        user_id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        let Some(course) = self.courses.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+        ensure_staff_management(actor, &staff)?;
+
+        let Some(member) = staff.into_iter().find(|member| member.user_id == *user_id) else {
+            return Err(AppError::ResourceNotFound(*user_id));
+        };
+
+        if !member.role.is_assignable() {
+            let message = "El profesor a cargo y el coordinador se cambian al actualizar el curso"
+                .to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        self.courses.remove_staff(&course.id, user_id).await?;
+
+        let entry = AuditEntry::new(audit, "course.staff.remove", "course", Some(course.id))
+            .before(&member);
+        self.audit_log.record(entry).await
     }
 }
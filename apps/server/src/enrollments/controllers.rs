@@ -5,8 +5,14 @@ use sword::prelude::*;
 use uuid::Uuid;
 
 use crate::{
+    auth::{RoleMiddleware, SessionContext, SessionMiddleware},
     container::AppModule,
-    practices::{CreatePracticeDto, PracticeService, PracticeStatus, UpdatePracticeDto},
+    enrollments::{EnrollmentResponse, EnrollmentService, GetEnrollmentsDto},
+    practices::{
+        CreatePracticeDto, PracticeService, PracticeStatus, ReviewPracticeDto,
+        SupervisorTokenQueryDto, UpdatePracticeDto,
+    },
+    users::Role,
 };
 
 #[controller("/enrollments")]
@@ -14,64 +20,109 @@ pub struct EnrollmentsController {}
 
 #[routes]
 impl EnrollmentsController {
+    /// Obtener las inscripciones, filtradas por estudiante, curso o periodo.
+
+    #[get("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
+    async fn get_enrollments(ctx: Context) -> HttpResult<HttpResponse> {
+        let query = ctx.validated_query::<GetEnrollmentsDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
+
+        let enrollments = service
+            .get_all(query.into())
+            .await?
+            .into_iter()
+            .map(EnrollmentResponse::from)
+            .collect::<Vec<_>>();
+
+        Ok(HttpResponse::Ok().data(enrollments))
+    }
+
     #[post("/{id}/practice")]
+    #[middleware(RoleMiddleware, config = &[Role::Student, Role::Administrator, Role::Secretary])]
     async fn create_practice(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
         let enrollment_id = ctx.param::<Uuid>("id")?;
         let dto = ctx.validated_body::<CreatePracticeDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
 
-        let practice = service.create(&enrollment_id, dto).await?;
+        let practice = service.create(&user, &enrollment_id, dto, &ctx.audit_context()).await?;
         Ok(HttpResponse::Created().data(practice))
     }
 
+    /// Aprobar la práctica desde el enlace enviado al supervisor de la empresa.
+    /// Requiere el token de un solo uso incluido en el enlace.
+
     #[post("/{id}/practice/{practice_id}/approve")]
     async fn approve_practice(ctx: Context) -> HttpResult<HttpResponse> {
         let enrollment_id = ctx.param::<Uuid>("id")?;
         let practice_id = ctx.param::<Uuid>("practice_id")?;
+        let query = ctx.validated_query::<SupervisorTokenQueryDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
 
-        let Some(practice) = service.get_by_id(&practice_id).await? else {
-            return Err(HttpResponse::NotFound());
-        };
-
-        if practice.practice_status != PracticeStatus::Pending {
-            return Err(HttpResponse::BadRequest());
-        }
-
         service
-            .update_status(&enrollment_id, &practice_id, PracticeStatus::Approved)
+            .supervisor_review(
+                &enrollment_id,
+                &practice_id,
+                &query.token,
+                PracticeStatus::Approved,
+                &ctx.audit_context(),
+            )
             .await?;
 
         Ok(HttpResponse::Ok())
     }
 
+    /// Rechazar la práctica desde el enlace enviado al supervisor de la empresa.
+    /// Requiere el token de un solo uso incluido en el enlace.
+
     #[post("/{id}/practice/{practice_id}/decline")]
     async fn decline_practice(ctx: Context) -> HttpResult<HttpResponse> {
         let enrollment_id = ctx.param::<Uuid>("id")?;
         let practice_id = ctx.param::<Uuid>("practice_id")?;
+        let query = ctx.validated_query::<SupervisorTokenQueryDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
 
-        let Some(practice) = service.get_by_id(&practice_id).await? else {
-            return Err(HttpResponse::NotFound());
-        };
-
-        if practice.practice_status != PracticeStatus::Pending {
-            return Err(HttpResponse::BadRequest());
-        }
-
         service
-            .update_status(&enrollment_id, &practice_id, PracticeStatus::Declined)
+            .supervisor_review(
+                &enrollment_id,
+                &practice_id,
+                &query.token,
+                PracticeStatus::Declined,
+                &ctx.audit_context(),
+            )
             .await?;
 
         Ok(HttpResponse::Ok())
     }
 
+    /// Aprobar o rechazar la práctica de una inscripción desde la plataforma,
+    /// reservado al coordinador del curso.
+
+    #[patch("/{id}/practice/status")]
+    #[middleware(RoleMiddleware, config = &[Role::Coordinator, Role::Administrator])]
+    async fn review_practice(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let enrollment_id = ctx.param::<Uuid>("id")?;
+        let dto = ctx.validated_body::<ReviewPracticeDto>()?;
+
+        let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
+        let practice =
+            service.review(&user, &enrollment_id, dto.status, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok().data(practice))
+    }
+
+    /// Subir la autorización firmada por el supervisor, usando el token
+    /// del enlace recibido al aprobarse la práctica.
+
     #[post("/{id}/practice/{practice_id}/authorize")]
     async fn authorize_practice(ctx: Context) -> HttpResult<HttpResponse> {
         let practice_id = ctx.param::<Uuid>("practice_id")?;
+        let query = ctx.validated_query::<SupervisorTokenQueryDto>()?;
         let form_data = ctx.multipart().await?;
 
         let Some(field) = form_data.first() else {
@@ -84,19 +135,26 @@ impl EnrollmentsController {
 
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
 
-        service.authorize(&practice_id, field.data.bytes()).await?;
+        service
+            .authorize(&practice_id, &query.token, field.data.bytes(), &ctx.audit_context())
+            .await?;
 
         Ok(HttpResponse::Ok())
     }
 
     #[get("/practice/{practice_id}/docs")]
+    #[middleware(SessionMiddleware)]
     async fn get_practice_docs(ctx: Context) -> Result<impl IntoResponse, HttpResponse> {
+        let user = ctx.session_user()?;
         let practice_id = ctx.param::<Uuid>("practice_id")?;
 
+        let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
+        let practice = service.get_authorized(&user, &practice_id).await?;
+
         let file_path = format!(
             "{}/practices/{}/authorization.pdf",
             std::env::var("DOCUMENTS_DIR").unwrap_or(".".to_string()),
-            practice_id
+            practice.id
         );
 
         let buff = tokio::fs::read(&file_path).await.map_err(|e| {
@@ -108,27 +166,37 @@ impl EnrollmentsController {
     }
 
     #[post("/{id}/practice/{practice_id}/evaluate")]
-    async fn evaluate_practice(_: Context) -> HttpResult<HttpResponse> {
+    async fn evaluate_practice(ctx: Context) -> HttpResult<HttpResponse> {
+        let practice_id = ctx.param::<Uuid>("practice_id")?;
+        let query = ctx.validated_query::<SupervisorTokenQueryDto>()?;
+
+        let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
+
+        service.evaluate(&practice_id, &query.token, &ctx.audit_context()).await?;
+
         Ok(HttpResponse::Ok())
     }
 
     #[patch("/{id}/practice")]
+    #[middleware(RoleMiddleware, config = &[Role::Student, Role::Administrator, Role::Secretary])]
     async fn update_practice(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
         let enrollment_id = ctx.param::<Uuid>("id")?;
         let dto = ctx.validated_body::<UpdatePracticeDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
-        let practice = service.update(&enrollment_id, dto).await?;
+        let practice = service.update(&user, &enrollment_id, dto, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok().data(practice))
     }
 
     #[delete("/practice/{practice_id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn delete_practice(ctx: Context) -> HttpResult<HttpResponse> {
         let practice_id = ctx.param::<Uuid>("practice_id")?;
         let service = ctx.get_dependency::<AppModule, dyn PracticeService>()?;
 
-        service.remove(&practice_id).await?;
+        service.remove(&practice_id, &ctx.audit_context()).await?;
         Ok(HttpResponse::NoContent())
     }
 }
@@ -3,6 +3,7 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
+    courses::Course,
     enrollments::{Enrollment, EnrollmentFilter, StudentScore},
     practices::Practice,
     shared::validators::validate_uuid,
@@ -14,6 +15,7 @@ use crate::{
 // ============================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct GetEnrollmentsDto {
     #[validate(
         custom(function = validate_uuid, message = "Identificador de estudiante inválido")
@@ -24,6 +26,11 @@ pub struct GetEnrollmentsDto {
         custom(function = validate_uuid, message = "Identificador de curso inválido")
     )]
     pub course_id: Option<String>,
+
+    #[validate(
+        custom(function = validate_uuid, message = "Identificador de periodo inválido")
+    )]
+    pub period_id: Option<String>,
 }
 
 impl From<GetEnrollmentsDto> for EnrollmentFilter {
@@ -31,10 +38,19 @@ impl From<GetEnrollmentsDto> for EnrollmentFilter {
         EnrollmentFilter {
             student_id: dto.student_id.map(|id| Uuid::parse_str(&id).unwrap()),
             course_id: dto.course_id.map(|id| Uuid::parse_str(&id).unwrap()),
+            period_id: dto.period_id.map(|id| Uuid::parse_str(&id).unwrap()),
+            ..Default::default()
         }
     }
 }
 
+/// Inscripciones del propio estudiante, opcionalmente de un solo periodo.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentEnrollmentsQueryDto {
+    pub period_id: Option<Uuid>,
+}
+
 // ============================================================================
 // >>>>>>>>>>>>>>>>>>>>>>>>>> CREATE INSCRIPTION DTO <<<<<<<<<<<<<<<<<<<<<<<<<<
 // ============================================================================
@@ -61,6 +77,7 @@ impl From<CreateEnrollmentDto> for Enrollment {
             course_id: Uuid::parse_str(&dto.course_id).unwrap(),
             student_scores: vec![],
             practice_id: None,
+            final_grade: None,
         }
     }
 }
@@ -115,6 +132,7 @@ pub struct EnrollmentResponse {
     pub student_id: String,
     pub course_id: String,
     pub student_scores: Vec<StudentScore>,
+    pub final_grade: Option<f64>,
     pub practice_id: Option<String>,
 
     pub student: User,
@@ -130,9 +148,44 @@ impl From<EnrollmentWithStudentAndPractice> for EnrollmentResponse {
             student_id: enrollment.student_id.to_string(),
             course_id: enrollment.course_id.to_string(),
             student_scores: enrollment.student_scores,
+            final_grade: enrollment.final_grade,
             practice_id: enrollment.practice_id.map(|id| id.to_string()),
             student,
             practice,
         }
     }
 }
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>> Student Enrollment Response <<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Inscripción vista por el propio estudiante, junto al curso al que pertenece.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudentEnrollmentResponse {
+    pub id: String,
+    pub course_id: String,
+    pub student_scores: Vec<StudentScore>,
+    pub final_grade: Option<f64>,
+    pub practice_id: Option<String>,
+
+    pub course: Course,
+    pub practice: Option<Practice>,
+}
+
+pub type EnrollmentWithCourseAndPractice = (Enrollment, Course, Option<Practice>);
+
+impl From<EnrollmentWithCourseAndPractice> for StudentEnrollmentResponse {
+    fn from((enrollment, course, practice): EnrollmentWithCourseAndPractice) -> Self {
+        StudentEnrollmentResponse {
+            id: enrollment.id.to_string(),
+            course_id: enrollment.course_id.to_string(),
+            student_scores: enrollment.student_scores,
+            final_grade: enrollment.final_grade,
+            practice_id: enrollment.practice_id.map(|id| id.to_string()),
+            course,
+            practice,
+        }
+    }
+}
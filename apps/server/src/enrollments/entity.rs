@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use uuid::Uuid;
 
+/// Nota final mínima para aprobar un curso.
+pub const PASSING_GRADE: f64 = 4.0;
+
 #[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct Enrollment {
@@ -11,6 +14,14 @@ pub struct Enrollment {
     pub course_id: Uuid,
     pub practice_id: Option<Uuid>,
     pub student_scores: Vec<StudentScore>,
+    pub final_grade: Option<f64>,
+}
+
+impl Enrollment {
+    /// Sin nota final (curso no cerrado) el estudiante no cuenta como aprobado.
+    pub fn is_approved(&self) -> bool {
+        self.final_grade.is_some_and(|grade| grade >= PASSING_GRADE)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
@@ -29,6 +40,7 @@ pub enum Enrollments {
     CourseId,
     PracticeId,
     StudentScores,
+    FinalGrade,
 }
 
 impl Iden for Enrollments {
@@ -40,6 +52,7 @@ impl Iden for Enrollments {
             Enrollments::CourseId => "course_id",
             Enrollments::PracticeId => "practice_id",
             Enrollments::StudentScores => "student_scores",
+            Enrollments::FinalGrade => "final_grade",
         }
     }
 }
@@ -22,8 +22,13 @@ pub struct PostgresEnrollmentRepository {
 pub struct EnrollmentFilter {
     pub student_id: Option<Uuid>,
     pub course_id: Option<Uuid>,
+    pub practice_id: Option<Uuid>,
+    pub period_id: Option<Uuid>,
 }
 
+/// Las inscripciones no guardan el periodo, se obtiene desde su curso.
+const PERIOD_CONDITION: &str = "course_id IN (SELECT id FROM courses WHERE period_id = ?)";
+
 #[async_trait]
 pub trait EnrollmentRepository: Interface {
     async fn find_many(&self, filter: EnrollmentFilter) -> Result<Vec<Enrollment>, AppError>;
@@ -47,6 +52,14 @@ impl EnrollmentRepository for PostgresEnrollmentRepository {
             query.and_where(Expr::col(Enrollments::CourseId).eq(course_id));
         }
 
+        if let Some(practice_id) = filter.practice_id {
+            query.and_where(Expr::col(Enrollments::PracticeId).eq(practice_id));
+        }
+
+        if let Some(period_id) = filter.period_id {
+            query.and_where(Expr::cust_with_values(PERIOD_CONDITION, [period_id]));
+        }
+
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 
         let result = sqlx_query::<Postgres, Enrollment, _>(&sql, values)
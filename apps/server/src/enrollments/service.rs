@@ -4,14 +4,18 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    courses::CourseRepository,
+    audit::{AuditContext, AuditEntry, AuditService},
+    auth::{ensure_course_access, ensure_enrollment_access, ensure_score_entry},
+    course_filter,
+    courses::{Course, CourseFilter, CourseRepository},
     enrollment_filter,
     enrollments::*,
+    periods::{AcademicPeriod, PeriodRepository},
     practice_filter,
     practices::{PracticeFilter, PracticeRepository},
     shared::errors::{AppError, Input},
     user_filter,
-    users::{UserFilter, UserRepository},
+    users::{User, UserFilter, UserRepository},
 };
 
 #[derive(Component)]
@@ -28,6 +32,12 @@ pub struct EnrollmentServiceImpl {
 
     #[shaku(inject)]
     practices: Arc<dyn PracticeRepository>,
+
+    #[shaku(inject)]
+    periods: Arc<dyn PeriodRepository>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
 }
 
 #[async_trait]
@@ -39,13 +49,66 @@ pub trait EnrollmentService: Interface {
 
     async fn get_by_id(&self, id: &Uuid) -> Result<EnrollmentWithStudentAndPractice, AppError>;
 
-    async fn create(&self, input: CreateEnrollmentDto) -> Result<Enrollment, AppError>;
+    async fn get_by_student(
+        &self,
+        student_id: &Uuid,
+        period_id: Option<Uuid>,
+    ) -> Result<Vec<EnrollmentWithCourseAndPractice>, AppError>;
 
-    async fn create_many(&self, course_id: &Uuid, students: Vec<Uuid>) -> Result<(), AppError>;
+    async fn get_by_course(
+        &self,
+        actor: &User,
+        course_id: &Uuid,
+    ) -> Result<Vec<EnrollmentWithStudentAndPractice>, AppError>;
 
-    async fn update(&self, id: &Uuid, input: UpdateEnrollmentDto) -> Result<Enrollment, AppError>;
+    async fn get_authorized(
+        &self,
+        actor: &User,
+        id: &Uuid,
+    ) -> Result<EnrollmentWithStudentAndPractice, AppError>;
 
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError>;
+    async fn create(
+        &self,
+        input: CreateEnrollmentDto,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError>;
+
+    async fn create_many(
+        &self,
+        course_id: &Uuid,
+        students: Vec<Uuid>,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
+
+    async fn update(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        input: UpdateEnrollmentDto,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError>;
+
+    async fn set_practice(
+        &self,
+        id: &Uuid,
+        practice_id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError>;
+
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError>;
+}
+
+impl EnrollmentServiceImpl {
+    async fn find_course(&self, id: &Uuid) -> Result<Course, AppError> {
+        self.courses.find_by_id(id).await?.ok_or(AppError::ResourceNotFound(*id))
+    }
+
+    async fn find_period(&self, course: &Course) -> Result<Option<AcademicPeriod>, AppError> {
+        match course.period_id {
+            Some(period_id) => self.periods.find_by_id(&period_id).await,
+            None => Ok(None),
+        }
+    }
 }
 
 #[async_trait]
@@ -57,9 +120,11 @@ impl EnrollmentService for EnrollmentServiceImpl {
         let mut result = Vec::new();
         let enrollments = self.enrollments.find_many(filter).await?;
 
+        // Las inscripciones históricas conservan a sus estudiantes aunque hayan sido eliminados
         let student_filter = user_filter! {
             ids: enrollments.iter().map(|e| e.student_id).collect::<Vec<_>>()
-        };
+        }
+        .with_deleted();
 
         let practice_filter = practice_filter! {
             ids: enrollments.iter().filter_map(|e| e.practice_id).collect::<Vec<_>>()
@@ -93,7 +158,7 @@ impl EnrollmentService for EnrollmentServiceImpl {
 
         let student = self
             .users
-            .find_by_id(&enrollment.student_id)
+            .find_one(user_filter! { id: enrollment.student_id }.with_deleted())
             .await?
             .ok_or(AppError::ResourceNotFound(enrollment.student_id))?;
 
@@ -105,7 +170,83 @@ impl EnrollmentService for EnrollmentServiceImpl {
         Ok((enrollment, student, practice))
     }
 
-    async fn create(&self, input: CreateEnrollmentDto) -> Result<Enrollment, AppError> {
+    async fn get_by_student(
+        &self,
+        student_id: &Uuid,
+        period_id: Option<Uuid>,
+    ) -> Result<Vec<EnrollmentWithCourseAndPractice>, AppError> {
+        let filter = EnrollmentFilter {
+            student_id: Some(*student_id),
+            period_id,
+            ..Default::default()
+        };
+
+        let enrollments = self.enrollments.find_many(filter).await?;
+
+        let course_filter = course_filter! {
+            ids: enrollments.iter().map(|e| e.course_id).collect::<Vec<_>>()
+        };
+
+        let practice_filter = practice_filter! {
+            ids: enrollments.iter().filter_map(|e| e.practice_id).collect::<Vec<_>>()
+        };
+
+        let (courses, practices) = tokio::try_join!(
+            self.courses.find_many(course_filter),
+            self.practices.find_many(practice_filter)
+        )?;
+
+        enrollments
+            .into_iter()
+            .map(|enrollment| {
+                let course = courses
+                    .iter()
+                    .find(|c| c.id == enrollment.course_id)
+                    .cloned()
+                    .ok_or(AppError::ResourceNotFound(enrollment.course_id))?;
+
+                let practice = enrollment.practice_id.and_then(|practice_id| {
+                    practices.iter().find(|p| p.id == practice_id).cloned()
+                });
+
+                Ok((enrollment, course, practice))
+            })
+            .collect()
+    }
+
+    async fn get_by_course(
+        &self,
+        actor: &User,
+        course_id: &Uuid,
+    ) -> Result<Vec<EnrollmentWithStudentAndPractice>, AppError> {
+        let course = self.find_course(course_id).await?;
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+        ensure_course_access(actor, &staff)?;
+
+        self.get_all(enrollment_filter! { course_id: course.id }).await
+    }
+
+    async fn get_authorized(
+        &self,
+        actor: &User,
+        id: &Uuid,
+    ) -> Result<EnrollmentWithStudentAndPractice, AppError> {
+        let (enrollment, student, practice) = self.get_by_id(id).await?;
+        let course = self.find_course(&enrollment.course_id).await?;
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+
+        ensure_enrollment_access(actor, &enrollment, &staff)?;
+
+        Ok((enrollment, student, practice))
+    }
+
+    async fn create(
+        &self,
+        input: CreateEnrollmentDto,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError> {
         let enrollment = Enrollment::from(input);
 
         let filter = enrollment_filter! {
@@ -133,10 +274,24 @@ impl EnrollmentService for EnrollmentServiceImpl {
             return Err(AppError::ResourceNotFound(enrollment.student_id));
         };
 
-        if course_exists.is_none() {
+        let Some(course) = course_exists else {
             return Err(AppError::ResourceNotFound(enrollment.course_id));
         };
 
+        if course.is_closed() {
+            let message = "El curso está cerrado, no admite nuevas inscripciones".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        if self
+            .find_period(&course)
+            .await?
+            .is_some_and(|period| !period.is_enrollment_open())
+        {
+            let message = "El plazo de inscripción del periodo ya terminó".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
         if !student.is_student() {
             return Err(AppError::InvalidInput(Input {
                 field: "studentId".to_string(),
@@ -145,17 +300,28 @@ impl EnrollmentService for EnrollmentServiceImpl {
             }));
         }
 
-        self.enrollments.save(enrollment).await
+        let enrollment = self.enrollments.save(enrollment).await?;
+
+        let entry = AuditEntry::new(audit, "enrollment.create", "enrollment", Some(enrollment.id))
+            .after(&enrollment);
+        self.audit_log.record(entry).await?;
+
+        Ok(enrollment)
     }
 
-    async fn create_many(&self, couse_id: &Uuid, students: Vec<Uuid>) -> Result<(), AppError> {
+    async fn create_many(
+        &self,
+        couse_id: &Uuid,
+        students: Vec<Uuid>,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
         for student_id in students {
             let input = CreateEnrollmentDto {
                 student_id: student_id.to_string(),
                 course_id: couse_id.to_string(),
             };
 
-            if let Err(e) = self.create(input).await {
+            if let Err(e) = self.create(input, audit).await {
                 tracing::error!("Error creating enrollment for student {student_id}: {e}");
 
                 continue;
@@ -165,11 +331,36 @@ impl EnrollmentService for EnrollmentServiceImpl {
         Ok(())
     }
 
-    async fn update(&self, id: &Uuid, input: UpdateEnrollmentDto) -> Result<Enrollment, AppError> {
+    async fn update(
+        &self,
+        actor: &User,
+        id: &Uuid,
+        input: UpdateEnrollmentDto,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError> {
         let Some(mut enrollment) = self.enrollments.find_by_id(id).await? else {
             return Err(AppError::ResourceNotFound(*id));
         };
 
+        let before = enrollment.clone();
+
+        let course = self.find_course(&enrollment.course_id).await?;
+
+        let staff = self.courses.find_staff(&[course.id]).await?;
+        ensure_score_entry(actor, &staff)?;
+
+        if course.is_closed() && input.student_scores.is_some() {
+            let message = "El curso está cerrado, sus notas ya no pueden modificarse".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        if input.student_scores.is_some()
+            && self.find_period(&course).await?.is_some_and(|period| !period.is_grading_open())
+        {
+            let message = "El plazo de notas del periodo ya terminó".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
         if let Some(scores) = input.student_scores {
             enrollment.student_scores = scores.into_iter().map(StudentScore::from).collect();
         }
@@ -178,14 +369,47 @@ impl EnrollmentService for EnrollmentServiceImpl {
             enrollment.practice_id = Some(Uuid::parse_str(&practice_id).unwrap());
         }
 
-        self.enrollments.save(enrollment).await
+        let enrollment = self.enrollments.save(enrollment).await?;
+
+        let entry = AuditEntry::new(audit, "enrollment.update", "enrollment", Some(enrollment.id))
+            .diff(Some(&before), Some(&enrollment));
+        self.audit_log.record(entry).await?;
+
+        Ok(enrollment)
+    }
+
+    async fn set_practice(
+        &self,
+        id: &Uuid,
+        practice_id: &Uuid,
+        audit: &AuditContext,
+    ) -> Result<Enrollment, AppError> {
+        let Some(mut enrollment) = self.enrollments.find_by_id(id).await? else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        let before = enrollment.clone();
+
+        enrollment.practice_id = Some(*practice_id);
+
+        let enrollment = self.enrollments.save(enrollment).await?;
+
+        let entry = AuditEntry::new(audit, "enrollment.update", "enrollment", Some(enrollment.id))
+            .diff(Some(&before), Some(&enrollment));
+        self.audit_log.record(entry).await?;
+
+        Ok(enrollment)
     }
 
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError> {
-        if self.enrollments.find_by_id(id).await?.is_none() {
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError> {
+        let Some(enrollment) = self.enrollments.find_by_id(id).await? else {
             return Err(AppError::ResourceNotFound(*id));
         };
 
-        self.enrollments.delete(id).await
+        self.enrollments.delete(id).await?;
+
+        let entry = AuditEntry::new(audit, "enrollment.delete", "enrollment", Some(*id))
+            .before(&enrollment);
+        self.audit_log.record(entry).await
     }
 }
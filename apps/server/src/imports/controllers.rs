@@ -1,8 +1,10 @@
 use sword::prelude::*;
 
 use crate::{
+    auth::{RoleMiddleware, SessionContext},
     container::AppModule,
     imports::{ImportCourseDto, ImportService},
+    users::Role,
 };
 
 #[controller("/imports")]
@@ -11,11 +13,12 @@ pub struct ImportsController {}
 #[routes]
 impl ImportsController {
     #[post("/course")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn import_couse(ctx: Context) -> HttpResult<HttpResponse> {
         let data = ctx.validated_body::<ImportCourseDto>()?;
         let service = ctx.get_dependency::<AppModule, dyn ImportService>()?;
 
-        service.import_course_students(data).await?;
+        service.import_course_students(data, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Created())
     }
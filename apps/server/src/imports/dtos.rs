@@ -2,12 +2,11 @@ use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::shared::validators::validate_uuid;
-use crate::users::{validate_rut_id, User};
+use crate::users::{Rut, User};
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct ImportUserDto {
-    #[validate(custom(function = validate_rut_id))]
-    pub rut: String,
+    pub rut: Rut,
 
     #[validate(length(
         min = 5,
@@ -1,9 +1,10 @@
 use async_trait::async_trait;
 use shaku::{Component, Interface};
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use uuid::Uuid;
 
 use crate::{
+    audit::{AuditContext, AuditEntry, AuditService},
     enrollments::EnrollmentService,
     imports::{ImportCourseDto, ImportUserDto, ImportedUser},
     shared::{
@@ -31,11 +32,18 @@ pub struct ImportServiceImpl {
 
     #[shaku(inject)]
     event_queue: Arc<dyn EventQueue>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
 }
 
 #[async_trait]
 pub trait ImportService: Interface {
-    async fn import_course_students(&self, data: ImportCourseDto) -> Result<(), AppError>;
+    async fn import_course_students(
+        &self,
+        data: ImportCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
     async fn classify_imported_students(
         &self,
         students: Vec<ImportUserDto>,
@@ -48,24 +56,29 @@ impl ImportService for ImportServiceImpl {
         &self,
         students: Vec<ImportUserDto>,
     ) -> Result<(Vec<ImportedUser>, Vec<User>), AppError> {
-        let ruts = students.iter().map(|s| s.rut.clone()).collect::<Vec<_>>();
+        let ruts = students.iter().map(|s| s.rut).collect::<Vec<_>>();
         let existing_students = self.users.find_many(user_filter! { ruts }).await?;
 
-        // Filter those not in existing students (by rut)
-        // and map them to ImportedUser (preserving plain password for events)
+        // Filter those not in existing students (by canonical rut), keeping
+        // a single row per rut, and map them to ImportedUser
+        // (preserving plain password for events)
+
+        let mut seen = HashSet::new();
 
         let imported_students = students
             .iter()
-            .filter(|s| !existing_students.iter().any(|existing| existing.rut == s.rut))
+            .filter(|s| !existing_students.iter().any(|existing| existing.rut == s.rut.to_string()))
+            .filter(|s| seen.insert(s.rut))
             .map(|data| -> Result<ImportedUser, AppError> {
                 let (plain, hash) = self.hasher.random_password()?;
 
                 let entity = User {
-                    rut: data.rut.clone(),
+                    rut: data.rut.to_string(),
                     email: data.email.clone(),
                     name: data.name.clone(),
                     password: hash,
                     roles: vec![Role::Student],
+                    must_change_password: true,
                     ..Default::default()
                 };
 
@@ -79,7 +92,11 @@ impl ImportService for ImportServiceImpl {
         Ok((imported_students, existing_students))
     }
 
-    async fn import_course_students(&self, course: ImportCourseDto) -> Result<(), AppError> {
+    async fn import_course_students(
+        &self,
+        course: ImportCourseDto,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
         let (imported_students, existing_students) =
             self.classify_imported_students(course.students).await?;
 
@@ -90,7 +107,13 @@ impl ImportService for ImportServiceImpl {
 
         let new_students = imported_students.into_iter().map(|s| s.entity).collect::<Vec<User>>();
 
-        self.users.create_many(new_students.clone()).await?;
+        let created = self.users.create_many(new_students.clone()).await?;
+
+        for user in &created {
+            let entry = AuditEntry::new(audit, "user.create", "user", Some(user.id)).after(user);
+            self.audit_log.record(entry).await?;
+        }
+
         self.event_queue.publish(Event::ManyUsersCreated(event_data)).await;
 
         let all_students = existing_students
@@ -101,7 +124,7 @@ impl ImportService for ImportServiceImpl {
 
         let course_id = Uuid::parse_str(&course.id).unwrap();
 
-        self.enrollments.create_many(&course_id, all_students).await?;
+        self.enrollments.create_many(&course_id, all_students, audit).await?;
 
         Ok(())
     }
@@ -5,15 +5,22 @@ pub mod users {
     mod dtos;
     mod entity;
     mod repository;
+    mod rut;
     mod service;
 
-    pub use controllers::UsersController;
+    pub use controllers::{MeController, UsersController};
     pub use dtos::{
-        role_validator, validate_rut_id, CreateUserDto, GetUsersQueryDto, UpdateUserDto,
-        UserResponse,
+        password_schema, role_validator, ConfirmEmailChangeDto, CreateUserDto, GetUsersQueryDto,
+        MergeUsersDto, UpdateProfileDto, UpdateUserDto, UserResponse,
     };
-    pub use entity::{Role, User};
-    pub use repository::{PostgresUserRepository, UserFilter, UserRepository};
+    pub use entity::{
+        CombinedEnrollment, EmailChange, MergeReport, Role, ScoreChoice, User, UserMerge,
+    };
+    pub use repository::{
+        EmailChangeRepository, PostgresEmailChangeRepository, PostgresUserRepository, UserFilter,
+        UserRepository, UserSortField,
+    };
+    pub use rut::{Rut, RutError};
 
     pub use service::{UserService, UserServiceImpl};
 }
@@ -27,15 +34,87 @@ pub mod courses {
 
     pub use controllers::CoursesController;
     pub use dtos::{
-        CourseEvaluationDto, CourseResponse, CourseWithStaff, CreateCourseDto, UpdateCourseDto,
+        staff_user, AddStaffMemberDto, CloneCourseDto, ClonedCourseResponse, CourseEvaluationDto,
+        CourseResponse, CourseWithStaff, CreateCourseDto, FinalGrade, FinalGradeResponse,
+        GetCoursesQueryDto, StaffMember, StaffMemberResponse, UpdateCourseDto, UpdateEvaluationDto,
+        UpdatedCourseResponse,
     };
 
-    pub use entity::{Course, CourseEvaluation, CourseStatus};
+    pub use entity::{Course, CourseEvaluation, CourseStaff, CourseStatus, StaffRole};
 
     pub use repository::{CourseFilter, CourseRepository, PostgresCourseRepository};
     pub use service::{CourseService, CourseServiceImpl};
 }
 
+pub mod periods {
+    mod controllers;
+    mod dtos;
+    mod entity;
+    mod repository;
+    mod service;
+
+    pub use controllers::PeriodsController;
+    pub use dtos::{CreatePeriodDto, GetPeriodsQueryDto, UpdatePeriodDto};
+    pub use entity::AcademicPeriod;
+    pub use repository::{PeriodFilter, PeriodRepository, PostgresPeriodRepository};
+    pub use service::{PeriodService, PeriodServiceImpl};
+}
+
+pub mod audit {
+    mod controllers;
+    mod dtos;
+    mod entity;
+    mod repository;
+    mod service;
+
+    pub use controllers::AuditController;
+    pub use dtos::{AuditEntryResponse, GetAuditLogQueryDto};
+    pub use entity::{AuditContext, AuditEntry, AuditLog, PERSONAL_FIELDS};
+    pub use repository::{AuditFilter, AuditRepository, PostgresAuditRepository};
+    pub use service::{AuditService, AuditServiceImpl};
+}
+
+pub mod auth {
+    mod controllers;
+    mod dtos;
+    mod entity;
+    mod middleware;
+    mod policies;
+    mod repository;
+    mod service;
+    mod sessions;
+
+    pub use controllers::AuthController;
+    pub use dtos::{
+        AuthResponse, ChangePasswordDto, ForgotPasswordDto, LoginDto, LoginResponse,
+        RefreshSessionDto, ResetPasswordDto, SessionResponse, TwoFactorActivationResponse,
+        TwoFactorCodeDto, TwoFactorLoginDto, TwoFactorSetupResponse,
+    };
+    pub use entity::{
+        LoginAttempt, PasswordResetToken, RecoveryCode, RefreshToken, Session, TwoFactor,
+    };
+    pub use middleware::{
+        PasswordChangeMiddleware, RoleMiddleware, SessionContext, SessionMiddleware,
+        TwoFactorSetupMiddleware, IMPERSONATED_BY_HEADER,
+    };
+    pub use policies::{
+        can_manage_course, ensure_course_access, ensure_course_closing, ensure_enrollment_access,
+        ensure_not_impersonating, ensure_practice_review, ensure_score_entry,
+        ensure_staff_management,
+    };
+    pub use repository::{
+        InMemoryLoginAttemptRepository, LoginAttemptRepository, PasswordResetTokenRepository,
+        PostgresLoginAttemptRepository, PostgresPasswordResetTokenRepository,
+        PostgresRefreshTokenRepository, PostgresTwoFactorRepository, RefreshTokenRepository,
+        TwoFactorRepository,
+    };
+    pub use service::{
+        AuthService, AuthServiceImpl, AuthSession, ClientInfo, LoginOutcome, TwoFactorActivation,
+        TwoFactorSetup,
+    };
+    pub use sessions::{InMemorySessionStore, RedisSessionStore, SessionStore};
+}
+
 pub mod imports {
     mod controllers;
     mod dtos;
@@ -55,11 +134,12 @@ pub mod enrollments {
 
     pub use controllers::EnrollmentsController;
     pub use dtos::{
-        CreateEnrollmentDto, EnrollmentResponse, EnrollmentWithStudentAndPractice,
-        GetEnrollmentsDto, StudentScoreDto, UpdateEnrollmentDto,
+        CreateEnrollmentDto, EnrollmentResponse, EnrollmentWithCourseAndPractice,
+        EnrollmentWithStudentAndPractice, GetEnrollmentsDto, StudentEnrollmentResponse,
+        StudentEnrollmentsQueryDto, StudentScoreDto, UpdateEnrollmentDto,
     };
 
-    pub use entity::{Enrollment, StudentScore};
+    pub use entity::{Enrollment, Enrollments, StudentScore, PASSING_GRADE};
 
     pub use repository::{EnrollmentFilter, EnrollmentRepository, PostgresEnrollmentRepository};
 
@@ -72,9 +152,14 @@ pub mod practices {
     mod repository;
     mod service;
 
-    pub use dtos::{CreatePracticeDto, UpdatePracticeDto};
-    pub use entity::{Practice, PracticeStatus, Practices};
-    pub use repository::{PostgresPracticeRepository, PracticeFilter, PracticeRepository};
+    pub use dtos::{
+        CreatePracticeDto, ReviewPracticeDto, SupervisorTokenQueryDto, UpdatePracticeDto,
+    };
+    pub use entity::{Practice, PracticeStatus, Practices, SupervisorAction, SupervisorToken};
+    pub use repository::{
+        PostgresPracticeRepository, PostgresSupervisorTokenRepository, PracticeFilter,
+        PracticeRepository, SupervisorTokenRepository,
+    };
     pub use service::{PracticeService, PracticeServiceImpl};
 }
 
@@ -107,8 +192,10 @@ pub mod shared {
     pub mod services {
         pub mod errors;
         pub mod hasher;
+        pub mod jwt;
         pub mod mailer;
         pub mod printer;
+        pub mod totp;
         pub mod templates {
             mod context;
             mod files;
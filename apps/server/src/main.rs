@@ -3,15 +3,18 @@ use sword::prelude::Application;
 use tokio::sync::mpsc;
 
 use server::{
+    audit::AuditController,
+    auth::{AuthController, RedisSessionStore},
     config::ApplicationConfig,
     courses::CoursesController,
     enrollments::EnrollmentsController,
+    periods::PeriodsController,
     shared::{
         database::PostgresDatabase,
         layers::{setup_cors, HttpLogger},
         services::event_queue::SubscriberOptions,
     },
-    users::UsersController,
+    users::{MeController, UsersController},
 };
 
 use server::shared::services::{
@@ -21,7 +24,7 @@ use server::shared::services::{
     templates::TemplateConfig,
 };
 
-use server::config::{CorsConfig, PostgresDbConfig};
+use server::config::{AuthConfig, CorsConfig, PostgresDbConfig, RedisConfig};
 use server::container::DependencyContainer;
 
 #[tokio::main]
@@ -33,26 +36,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let pg_db_config = app.config.get::<PostgresDbConfig>()?;
     let mailer_config = app.config.get::<MailerConfig>()?;
     let template_config = app.config.get::<TemplateConfig>()?;
+    let auth_config = app.config.get::<AuthConfig>()?;
+    let redis_config = app.config.get::<RedisConfig>()?;
 
-    let (db, mailer, printer) = {
+    let (db, session_store, mailer, printer) = {
         let db = PostgresDatabase::new(&pg_db_config)
             .await
             .expect("Failed to create database connection");
 
         db.migrate().await.expect("Failed to create database connection");
 
+        let session_store = RedisSessionStore::new(&redis_config)
+            .await
+            .expect("Failed to create redis connection");
+
         let mailer =
             Mailer::new(&mailer_config, &template_config).expect("Failed to create mailer");
 
         let printer = Printer::new(&template_config).expect("Failed to create printer");
 
-        (db, mailer, printer)
+        (db, session_store, mailer, printer)
     };
 
     let (tx, rx) = mpsc::channel(app_config.event_queue_buffer_size);
 
     let publisher = TokioEventSender::new(tx);
-    let dependency_container = DependencyContainer::new(db, publisher);
+    let dependency_container = DependencyContainer::new(db, session_store, publisher, auth_config);
 
     EventSubscriber::new(SubscriberOptions {
         rx,
@@ -75,9 +84,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     app.di_module(dependency_container.module)?
+        .controller::<AuthController>()
         .controller::<UsersController>()
+        .controller::<MeController>()
         .controller::<CoursesController>()
         .controller::<EnrollmentsController>()
+        .controller::<PeriodsController>()
+        .controller::<AuditController>()
         .layer(http_logger.layer)
         .layer(cors_layer)
         .layer(helmet_layer)
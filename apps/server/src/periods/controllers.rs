@@ -0,0 +1,80 @@
+use sword::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    auth::{RoleMiddleware, SessionMiddleware},
+    container::AppModule,
+    periods::{CreatePeriodDto, GetPeriodsQueryDto, PeriodService, UpdatePeriodDto},
+    users::Role,
+};
+
+#[controller("/periods")]
+pub struct PeriodsController {}
+
+#[routes]
+impl PeriodsController {
+    /// Obtener los periodos académicos, del más reciente al más antiguo.
+    /// Filtrable por año y semestre.
+
+    #[get("/")]
+    #[middleware(SessionMiddleware)]
+    async fn get_periods(ctx: Context) -> HttpResult<HttpResponse> {
+        let query = ctx.validated_query::<GetPeriodsQueryDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn PeriodService>()?;
+
+        let periods = service.get_all(query.into()).await?;
+
+        Ok(HttpResponse::Ok().data(periods))
+    }
+
+    #[get("/{id}")]
+    #[middleware(SessionMiddleware)]
+    async fn get_period(ctx: Context) -> HttpResult<HttpResponse> {
+        let period_id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn PeriodService>()?;
+
+        let period = service.get_by_id(&period_id).await?;
+
+        Ok(HttpResponse::Ok().data(period))
+    }
+
+    /// Registrar un nuevo periodo, solo uno por año y semestre
+
+    #[post("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
+    async fn create_period(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<CreatePeriodDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn PeriodService>()?;
+
+        let period = service.create(input, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Created().data(period))
+    }
+
+    /// Modificar las fechas y plazos de un periodo
+
+    #[patch("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
+    async fn update_period(ctx: Context) -> HttpResult<HttpResponse> {
+        let period_id = ctx.param::<Uuid>("id")?;
+        let input = ctx.validated_body::<UpdatePeriodDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn PeriodService>()?;
+
+        let period = service.update(&period_id, input, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok().data(period))
+    }
+
+    /// Eliminar un periodo sin cursos asociados
+
+    #[delete("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
+    async fn remove(ctx: Context) -> HttpResult<HttpResponse> {
+        let period_id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn PeriodService>()?;
+
+        service.remove(&period_id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::periods::{AcademicPeriod, PeriodFilter};
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>> CREATE PERIOD DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePeriodDto {
+    #[validate(range(min = 2000, max = 2100, message = "El año debe tener 4 dígitos."))]
+    pub year: i32,
+
+    #[validate(range(min = 1, max = 2, message = "El semestre debe ser 1 o 2."))]
+    pub term: i32,
+
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub enrollment_deadline: DateTime<Utc>,
+    pub grades_deadline: DateTime<Utc>,
+}
+
+impl From<CreatePeriodDto> for AcademicPeriod {
+    fn from(dto: CreatePeriodDto) -> Self {
+        AcademicPeriod {
+            id: Uuid::new_v4(),
+            year: dto.year,
+            term: dto.term,
+            start_date: dto.start_date,
+            end_date: dto.end_date,
+            enrollment_deadline: dto.enrollment_deadline,
+            grades_deadline: dto.grades_deadline,
+        }
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>> UPDATE PERIOD DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// El año y el semestre no cambian, solo las fechas y plazos.
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePeriodDto {
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub enrollment_deadline: Option<DateTime<Utc>>,
+    pub grades_deadline: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>> GET PERIODS QUERY <<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPeriodsQueryDto {
+    #[validate(range(min = 2000, max = 2100, message = "El año debe tener 4 dígitos."))]
+    pub year: Option<i32>,
+
+    #[validate(range(min = 1, max = 2, message = "El semestre debe ser 1 o 2."))]
+    pub term: Option<i32>,
+}
+
+impl From<GetPeriodsQueryDto> for PeriodFilter {
+    fn from(dto: GetPeriodsQueryDto) -> Self {
+        PeriodFilter {
+            year: dto.year,
+            term: dto.term,
+        }
+    }
+}
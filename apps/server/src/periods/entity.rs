@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use sea_query::Iden;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Semestre académico. Los cursos asociados comparten sus plazos de
+/// inscripción y de notas, y las prácticas deben realizarse dentro de él.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AcademicPeriod {
+    pub id: Uuid,
+    pub year: i32,
+    pub term: i32,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub enrollment_deadline: DateTime<Utc>,
+    pub grades_deadline: DateTime<Utc>,
+}
+
+impl AcademicPeriod {
+    pub fn is_enrollment_open(&self) -> bool {
+        Utc::now() <= self.enrollment_deadline
+    }
+
+    pub fn is_grading_open(&self) -> bool {
+        Utc::now() <= self.grades_deadline
+    }
+
+    /// Una práctica debe comenzar durante el periodo y terminar antes del
+    /// plazo de notas, para que alcance a ser evaluada.
+    pub fn allows_practice(&self, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> bool {
+        start_date >= self.start_date
+            && start_date <= self.end_date
+            && end_date <= self.grades_deadline
+    }
+}
+
+pub enum AcademicPeriods {
+    Table,
+    Id,
+    Year,
+    Term,
+    StartDate,
+}
+
+impl Iden for AcademicPeriods {
+    fn unquoted(&self) -> &str {
+        match self {
+            AcademicPeriods::Table => "academic_periods",
+            AcademicPeriods::Id => "id",
+            AcademicPeriods::Year => "year",
+            AcademicPeriods::Term => "term",
+            AcademicPeriods::StartDate => "start_date",
+        }
+    }
+}
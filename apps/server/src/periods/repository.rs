@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use sea_query::{Expr, ExprTrait, Order, PostgresQueryBuilder, Query};
+use sea_query_sqlx::SqlxBinder;
+use shaku::{Component, Interface};
+use sqlx::{query_as_with as sqlx_query, Postgres};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    periods::entity::{AcademicPeriod, AcademicPeriods},
+    shared::{database::DatabaseConnection, errors::AppError},
+};
+
+#[derive(Component)]
+#[shaku(interface = PeriodRepository)]
+pub struct PostgresPeriodRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PeriodFilter {
+    pub year: Option<i32>,
+    pub term: Option<i32>,
+}
+
+#[async_trait]
+pub trait PeriodRepository: Interface {
+    async fn find_many(&self, filter: PeriodFilter) -> Result<Vec<AcademicPeriod>, AppError>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<AcademicPeriod>, AppError>;
+    async fn save(&self, period: AcademicPeriod) -> Result<AcademicPeriod, AppError>;
+    async fn delete(&self, id: &Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl PeriodRepository for PostgresPeriodRepository {
+    async fn find_many(&self, filter: PeriodFilter) -> Result<Vec<AcademicPeriod>, AppError> {
+        let mut query =
+            Query::select().expr(Expr::cust("*")).from(AcademicPeriods::Table).to_owned();
+
+        if let Some(year) = filter.year {
+            query.and_where(Expr::col(AcademicPeriods::Year).eq(year));
+        }
+
+        if let Some(term) = filter.term {
+            query.and_where(Expr::col(AcademicPeriods::Term).eq(term));
+        }
+
+        query.order_by(AcademicPeriods::StartDate, Order::Desc);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        let result = sqlx_query::<Postgres, AcademicPeriod, _>(&sql, values)
+            .fetch_all(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<AcademicPeriod>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(AcademicPeriods::Table)
+            .and_where(Expr::col(AcademicPeriods::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let model = sqlx_query::<Postgres, AcademicPeriod, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(model)
+    }
+
+    async fn save(&self, period: AcademicPeriod) -> Result<AcademicPeriod, AppError> {
+        let query = r#"
+            INSERT INTO academic_periods (id, year, term, start_date, end_date, enrollment_deadline, grades_deadline)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO UPDATE SET
+                start_date = EXCLUDED.start_date,
+                end_date = EXCLUDED.end_date,
+                enrollment_deadline = EXCLUDED.enrollment_deadline,
+                grades_deadline = EXCLUDED.grades_deadline
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, AcademicPeriod>(query)
+            .bind(period.id)
+            .bind(period.year)
+            .bind(period.term)
+            .bind(period.start_date)
+            .bind(period.end_date)
+            .bind(period.enrollment_deadline)
+            .bind(period.grades_deadline)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::delete()
+            .from_table(AcademicPeriods::Table)
+            .and_where(Expr::col(AcademicPeriods::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(self.db_connection.get_pool()).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use shaku::{Component, Interface};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    audit::{AuditContext, AuditEntry, AuditService},
+    course_filter,
+    courses::{CourseFilter, CourseRepository},
+    period_filter,
+    periods::{AcademicPeriod, CreatePeriodDto, PeriodFilter, PeriodRepository, UpdatePeriodDto},
+    shared::errors::{AppError, Input},
+};
+
+#[derive(Component)]
+#[shaku(interface = PeriodService)]
+pub struct PeriodServiceImpl {
+    #[shaku(inject)]
+    periods: Arc<dyn PeriodRepository>,
+
+    #[shaku(inject)]
+    courses: Arc<dyn CourseRepository>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
+}
+
+#[async_trait]
+pub trait PeriodService: Interface {
+    async fn get_all(&self, filter: PeriodFilter) -> Result<Vec<AcademicPeriod>, AppError>;
+    async fn get_by_id(&self, id: &Uuid) -> Result<AcademicPeriod, AppError>;
+
+    async fn create(
+        &self,
+        input: CreatePeriodDto,
+        audit: &AuditContext,
+    ) -> Result<AcademicPeriod, AppError>;
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        input: UpdatePeriodDto,
+        audit: &AuditContext,
+    ) -> Result<AcademicPeriod, AppError>;
+
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError>;
+}
+
+/// El plazo de inscripción cae dentro del periodo y el de notas no antes de
+/// su término, de lo contrario no habría tiempo para evaluar.
+fn ensure_valid_dates(period: &AcademicPeriod) -> Result<(), AppError> {
+    let invalid = |field: &str, message: &str, value: String| {
+        Err(AppError::InvalidInput(Input {
+            field: field.to_string(),
+            message: message.to_string(),
+            value,
+        }))
+    };
+
+    if period.start_date >= period.end_date {
+        let message = "La fecha de término debe ser posterior a la fecha de inicio";
+        return invalid("endDate", message, period.end_date.to_rfc3339());
+    }
+
+    if period.enrollment_deadline < period.start_date
+        || period.enrollment_deadline > period.end_date
+    {
+        let message = "El plazo de inscripción debe estar dentro del periodo";
+        return invalid("enrollmentDeadline", message, period.enrollment_deadline.to_rfc3339());
+    }
+
+    if period.grades_deadline < period.end_date {
+        let message = "El plazo de notas no puede ser anterior al término del periodo";
+        return invalid("gradesDeadline", message, period.grades_deadline.to_rfc3339());
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl PeriodService for PeriodServiceImpl {
+    async fn get_all(&self, filter: PeriodFilter) -> Result<Vec<AcademicPeriod>, AppError> {
+        self.periods.find_many(filter).await
+    }
+
+    async fn get_by_id(&self, id: &Uuid) -> Result<AcademicPeriod, AppError> {
+        self.periods.find_by_id(id).await?.ok_or(AppError::ResourceNotFound(*id))
+    }
+
+    async fn create(
+        &self,
+        input: CreatePeriodDto,
+        audit: &AuditContext,
+    ) -> Result<AcademicPeriod, AppError> {
+        let period = AcademicPeriod::from(input);
+
+        ensure_valid_dates(&period)?;
+
+        let filter = period_filter! {
+            year: period.year,
+            term: period.term,
+        };
+
+        if !self.periods.find_many(filter).await?.is_empty() {
+            return Err(AppError::Conflict(Input {
+                message: "Ya existe un periodo para el mismo año y semestre".to_string(),
+                ..Default::default()
+            }));
+        }
+
+        let period = self.periods.save(period).await?;
+
+        let entry =
+            AuditEntry::new(audit, "period.create", "period", Some(period.id)).after(&period);
+        self.audit_log.record(entry).await?;
+
+        Ok(period)
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        input: UpdatePeriodDto,
+        audit: &AuditContext,
+    ) -> Result<AcademicPeriod, AppError> {
+        let mut period = self.get_by_id(id).await?;
+        let before = period.clone();
+
+        if let Some(start_date) = input.start_date {
+            period.start_date = start_date;
+        }
+
+        if let Some(end_date) = input.end_date {
+            period.end_date = end_date;
+        }
+
+        if let Some(enrollment_deadline) = input.enrollment_deadline {
+            period.enrollment_deadline = enrollment_deadline;
+        }
+
+        if let Some(grades_deadline) = input.grades_deadline {
+            period.grades_deadline = grades_deadline;
+        }
+
+        ensure_valid_dates(&period)?;
+
+        let period = self.periods.save(period).await?;
+
+        let entry = AuditEntry::new(audit, "period.update", "period", Some(period.id))
+            .diff(Some(&before), Some(&period));
+        self.audit_log.record(entry).await?;
+
+        Ok(period)
+    }
+
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError> {
+        let period = self.get_by_id(id).await?;
+
+        if !self
+            .courses
+            .find_many(course_filter! { period_id: period.id })
+            .await?
+            .is_empty()
+        {
+            return Err(AppError::InvalidInput(Input {
+                field: "periodId".to_string(),
+                message: "No se puede eliminar un periodo con cursos asociados".to_string(),
+                value: period.id.to_string(),
+            }));
+        }
+
+        self.periods.delete(&period.id).await?;
+
+        let entry =
+            AuditEntry::new(audit, "period.delete", "period", Some(period.id)).before(&period);
+        self.audit_log.record(entry).await
+    }
+}
@@ -109,6 +109,19 @@ pub struct UpdatePracticeDto {
     pub end_date: Option<DateTime<Utc>>,
 }
 
+#[derive(Serialize, Deserialize, Validate)]
+pub struct ReviewPracticeDto {
+    #[validate(custom(function = "validate_review_status"))]
+    pub status: PracticeStatus,
+}
+
+/// Token de un solo uso recibido por el supervisor en los enlaces del correo.
+#[derive(Serialize, Deserialize, Validate)]
+pub struct SupervisorTokenQueryDto {
+    #[validate(length(min = 1, message = "El token del enlace es obligatorio."))]
+    pub token: String,
+}
+
 static PHONE_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^(?:\+56)?\s?(?:9\d{8}|\d{1}\d{8})$").unwrap());
 
@@ -120,6 +133,16 @@ fn validate_update_practice_dates(schema: &UpdatePracticeDto) -> Result<(), Vali
     validate_dates(schema.start_date, schema.end_date)
 }
 
+fn validate_review_status(status: &PracticeStatus) -> Result<(), ValidationError> {
+    if *status == PracticeStatus::Pending {
+        return Err(ValidationError::new(
+            "El estado de la práctica debe ser 'approved' o 'declined'.",
+        ));
+    }
+
+    Ok(())
+}
+
 fn validate_dates(
     start_date: Option<DateTime<Utc>>,
     end_date: Option<DateTime<Utc>>,
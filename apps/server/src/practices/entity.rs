@@ -63,3 +63,66 @@ impl Iden for Practices {
         }
     }
 }
+
+/// Acción que un supervisor externo puede realizar mediante un enlace de un solo uso.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "supervisor_action", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SupervisorAction {
+    Approve,
+    Decline,
+    Authorize,
+    Evaluate,
+}
+
+/// Token enviado al supervisor de una práctica. Al igual que los tokens de
+/// recuperación de contraseña, solo se almacena el hash del secreto y el
+/// enlace lleva `<id>.<secreto>`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisorToken {
+    pub id: Uuid,
+    pub practice_id: Uuid,
+    pub action: SupervisorAction,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SupervisorToken {
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[allow(dead_code)]
+pub enum SupervisorTokens {
+    Table,
+    Id,
+    PracticeId,
+    Action,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
+
+impl Iden for SupervisorTokens {
+    fn unquoted(&self) -> &str {
+        match self {
+            SupervisorTokens::Table => "supervisor_tokens",
+            SupervisorTokens::Id => "id",
+            SupervisorTokens::PracticeId => "practice_id",
+            SupervisorTokens::Action => "action",
+            SupervisorTokens::TokenHash => "token_hash",
+            SupervisorTokens::ExpiresAt => "expires_at",
+            SupervisorTokens::UsedAt => "used_at",
+            SupervisorTokens::CreatedAt => "created_at",
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use sea_query::{Expr, ExprTrait, PostgresQueryBuilder, Query};
 use sea_query_sqlx::SqlxBinder;
 use shaku::{Component, Interface};
@@ -8,7 +9,7 @@ use sqlx::{query_as_with as sqlx_query, Postgres};
 use uuid::Uuid;
 
 use crate::{
-    practices::entity::{Practice, Practices},
+    practices::entity::{Practice, Practices, SupervisorAction, SupervisorToken, SupervisorTokens},
     shared::{database::DatabaseConnection, errors::AppError},
 };
 
@@ -111,3 +112,97 @@ impl PracticeRepository for PostgresPracticeRepository {
         Ok(())
     }
 }
+
+#[derive(Component)]
+#[shaku(interface = SupervisorTokenRepository)]
+pub struct PostgresSupervisorTokenRepository {
+    #[shaku(inject)]
+    db_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[async_trait]
+pub trait SupervisorTokenRepository: Interface {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<SupervisorToken>, AppError>;
+    async fn save(&self, token: SupervisorToken) -> Result<SupervisorToken, AppError>;
+
+    /// Marca el token como usado solo si sigue pendiente y vigente. Retorna
+    /// `false` si otra solicitud lo consumió antes o si ya expiró.
+    async fn consume(&self, id: &Uuid) -> Result<bool, AppError>;
+
+    async fn invalidate(
+        &self,
+        practice_id: &Uuid,
+        actions: &[SupervisorAction],
+    ) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl SupervisorTokenRepository for PostgresSupervisorTokenRepository {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<SupervisorToken>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(SupervisorTokens::Table)
+            .and_where(Expr::col(SupervisorTokens::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let token = sqlx_query::<Postgres, SupervisorToken, _>(&sql, values)
+            .fetch_optional(self.db_connection.get_pool())
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn save(&self, token: SupervisorToken) -> Result<SupervisorToken, AppError> {
+        let query = r#"
+            INSERT INTO supervisor_tokens (id, practice_id, action, token_hash, expires_at, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO UPDATE SET
+                used_at = EXCLUDED.used_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, SupervisorToken>(query)
+            .bind(token.id)
+            .bind(token.practice_id)
+            .bind(token.action)
+            .bind(&token.token_hash)
+            .bind(token.expires_at)
+            .bind(token.used_at)
+            .bind(token.created_at)
+            .fetch_one(self.db_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn consume(&self, id: &Uuid) -> Result<bool, AppError> {
+        let query = r#"
+            UPDATE supervisor_tokens SET used_at = now()
+            WHERE id = $1 AND used_at IS NULL AND expires_at > now()
+        "#;
+
+        let result = sqlx::query(query).bind(id).execute(self.db_connection.get_pool()).await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn invalidate(
+        &self,
+        practice_id: &Uuid,
+        actions: &[SupervisorAction],
+    ) -> Result<(), AppError> {
+        let query = r#"
+            UPDATE supervisor_tokens SET used_at = $1
+            WHERE practice_id = $2 AND action = ANY($3) AND used_at IS NULL
+        "#;
+
+        sqlx::query(query)
+            .bind(Utc::now())
+            .bind(practice_id)
+            .bind(actions.to_vec())
+            .execute(self.db_connection.get_pool())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,19 +1,35 @@
 use crate::{
     practices::entity::PracticeStatus,
-    shared::services::event_queue::{Event, EventQueue},
+    shared::services::{
+        event_queue::{Event, EventQueue},
+        hasher::PasswordHasher,
+    },
 };
 
 use async_trait::async_trait;
 
+use chrono::{DateTime, Duration, Utc};
 use shaku::{Component, Interface};
 use std::{io::Bytes, sync::Arc};
 use uuid::Uuid;
 
 use crate::{
-    courses::CourseService,
-    enrollments::{EnrollmentService, UpdateEnrollmentDto},
-    practices::{CreatePracticeDto, Practice, PracticeRepository, UpdatePracticeDto},
-    shared::{errors::AppError, AppResult},
+    audit::{AuditContext, AuditEntry, AuditService},
+    auth::ensure_practice_review,
+    config::AuthConfig,
+    courses::{staff_user, Course, CourseService, StaffRole},
+    enrollment_filter,
+    enrollments::{EnrollmentFilter, EnrollmentService},
+    periods::PeriodRepository,
+    practices::{
+        CreatePracticeDto, Practice, PracticeRepository, SupervisorAction, SupervisorToken,
+        SupervisorTokenRepository, UpdatePracticeDto,
+    },
+    shared::{
+        errors::{AppError, Input},
+        AppResult,
+    },
+    users::User,
 };
 
 #[derive(Component)]
@@ -28,32 +44,248 @@ pub struct PracticeServiceImpl {
     #[shaku(inject)]
     courses: Arc<dyn CourseService>,
 
+    #[shaku(inject)]
+    periods: Arc<dyn PeriodRepository>,
+
     #[shaku(inject)]
     event_queue: Arc<dyn EventQueue>,
+
+    #[shaku(inject)]
+    supervisor_tokens: Arc<dyn SupervisorTokenRepository>,
+
+    #[shaku(inject)]
+    hasher: Arc<dyn PasswordHasher>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
+
+    config: AuthConfig,
+}
+
+impl From<AuthConfig> for PracticeServiceImplParameters {
+    fn from(config: AuthConfig) -> Self {
+        PracticeServiceImplParameters { config }
+    }
 }
 
 #[async_trait]
 pub trait PracticeService: Interface {
     async fn get_by_id(&self, id: &Uuid) -> Result<Option<Practice>, AppError>;
+    async fn get_authorized(&self, actor: &User, id: &Uuid) -> Result<Practice, AppError>;
 
-    async fn update(&self, id: &Uuid, input: UpdatePracticeDto) -> Result<Practice, AppError>;
+    async fn update(
+        &self,
+        actor: &User,
+        enrollment_id: &Uuid,
+        input: UpdatePracticeDto,
+        audit: &AuditContext,
+    ) -> Result<Practice, AppError>;
 
     async fn create(
         &self,
+        actor: &User,
         enrollment_id: &Uuid,
         input: CreatePracticeDto,
+        audit: &AuditContext,
     ) -> Result<Practice, AppError>;
 
-    async fn authorize(&self, practice_id: &Uuid, document: Bytes<&[u8]>) -> Result<(), AppError>;
+    async fn supervisor_review(
+        &self,
+        enrollment_id: &Uuid,
+        practice_id: &Uuid,
+        token: &str,
+        status: PracticeStatus,
+        audit: &AuditContext,
+    ) -> AppResult<Practice>;
+
+    async fn authorize(
+        &self,
+        practice_id: &Uuid,
+        token: &str,
+        document: Bytes<&[u8]>,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
+
+    async fn evaluate(
+        &self,
+        practice_id: &Uuid,
+        token: &str,
+        audit: &AuditContext,
+    ) -> Result<(), AppError>;
 
     async fn update_status(
         &self,
         enrollment_id: &Uuid,
         practice_id: &Uuid,
         status: PracticeStatus,
+        audit: &AuditContext,
+    ) -> AppResult<Practice>;
+
+    async fn review(
+        &self,
+        actor: &User,
+        enrollment_id: &Uuid,
+        status: PracticeStatus,
+        audit: &AuditContext,
     ) -> AppResult<Practice>;
 
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError>;
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError>;
+}
+
+fn invalid_supervisor_link() -> AppError {
+    AppError::Unauthorized("El enlace es inválido, expiró o ya fue utilizado".to_string())
+}
+
+impl PracticeServiceImpl {
+    /// Emite un token de un solo uso para la acción indicada y retorna
+    /// su forma `<id>.<secreto>`, que es la que viaja en el enlace.
+    async fn issue_supervisor_token(
+        &self,
+        practice_id: &Uuid,
+        action: SupervisorAction,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, AppError> {
+        let secret = Uuid::new_v4().simple().to_string();
+
+        let token = self
+            .supervisor_tokens
+            .save(SupervisorToken {
+                id: Uuid::new_v4(),
+                practice_id: *practice_id,
+                action,
+                token_hash: self.hasher.hash(&secret)?,
+                expires_at,
+                used_at: None,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(format!("{}.{secret}", token.id))
+    }
+
+    /// Valida que el token pertenezca a la práctica y acción indicadas,
+    /// que no haya sido usado y que siga vigente.
+    async fn find_supervisor_token(
+        &self,
+        practice_id: &Uuid,
+        action: SupervisorAction,
+        token: &str,
+    ) -> Result<SupervisorToken, AppError> {
+        let Some((id, secret)) = token.split_once('.') else {
+            return Err(invalid_supervisor_link());
+        };
+
+        let id = Uuid::parse_str(id).map_err(|_| invalid_supervisor_link())?;
+
+        let Some(stored) = self.supervisor_tokens.find_by_id(&id).await? else {
+            return Err(invalid_supervisor_link());
+        };
+
+        if stored.practice_id != *practice_id || stored.action != action {
+            return Err(invalid_supervisor_link());
+        }
+
+        if stored.is_used() || stored.is_expired() {
+            return Err(invalid_supervisor_link());
+        }
+
+        if !self.hasher.verify(secret, &stored.token_hash)? {
+            return Err(invalid_supervisor_link());
+        }
+
+        Ok(stored)
+    }
+
+    /// Consume el token de forma atómica antes de ejecutar la acción, de modo
+    /// que dos solicitudes simultáneas con el mismo enlace no prosperen ambas.
+    async fn claim_supervisor_token(&self, token: &SupervisorToken) -> Result<(), AppError> {
+        if !self.supervisor_tokens.consume(&token.id).await? {
+            return Err(invalid_supervisor_link());
+        }
+
+        Ok(())
+    }
+
+    /// Enlaces de aprobación y rechazo que recibe el supervisor al inscribirse la práctica.
+    async fn issue_review_links(&self, practice: &Practice) -> AppResult<(String, String)> {
+        let expires_at = Utc::now() + self.supervisor_link_exp();
+
+        Ok((
+            self.issue_supervisor_token(&practice.id, SupervisorAction::Approve, expires_at)
+                .await?,
+            self.issue_supervisor_token(&practice.id, SupervisorAction::Decline, expires_at)
+                .await?,
+        ))
+    }
+
+    /// Enlaces de autorización y evaluación que recibe el supervisor al aprobarse la práctica.
+    async fn issue_approval_links(&self, practice: &Practice) -> AppResult<(String, String)> {
+        // La evaluación se completa al finalizar la práctica, por lo que
+        // su enlace vence contando desde la fecha de término.
+
+        let authorize_expires_at = Utc::now() + self.supervisor_link_exp();
+        let evaluate_expires_at = practice.end_date + self.supervisor_link_exp();
+
+        Ok((
+            self.issue_supervisor_token(
+                &practice.id,
+                SupervisorAction::Authorize,
+                authorize_expires_at,
+            )
+            .await?,
+            self.issue_supervisor_token(
+                &practice.id,
+                SupervisorAction::Evaluate,
+                evaluate_expires_at,
+            )
+            .await?,
+        ))
+    }
+
+    fn supervisor_link_exp(&self) -> Duration {
+        Duration::milliseconds(self.config.supervisor_link_exp_ms as i64)
+    }
+
+    /// Si el curso pertenece a un periodo, la práctica debe comenzar dentro
+    /// de él y terminar antes de su plazo de notas.
+    async fn ensure_within_period(&self, course: &Course, practice: &Practice) -> AppResult<()> {
+        let Some(period_id) = course.period_id else {
+            return Ok(());
+        };
+
+        let Some(period) = self.periods.find_by_id(&period_id).await? else {
+            return Ok(());
+        };
+
+        if !period.allows_practice(practice.start_date, practice.end_date) {
+            return Err(AppError::InvalidInput(Input {
+                field: "startDate".to_string(),
+                message: "Las fechas de la práctica no corresponden al periodo del curso"
+                    .to_string(),
+                value: format!("{} - {}", practice.start_date, practice.end_date),
+            }));
+        }
+
+        Ok(())
+    }
+
+    async fn review_pending(
+        &self,
+        enrollment_id: &Uuid,
+        practice: Option<Practice>,
+        status: PracticeStatus,
+        audit: &AuditContext,
+    ) -> AppResult<Practice> {
+        let Some(practice) = practice else {
+            return Err(AppError::ResourceNotFound(*enrollment_id));
+        };
+
+        if practice.practice_status != PracticeStatus::Pending {
+            return Err(AppError::InvalidOperation("La práctica ya fue revisada".to_string()));
+        }
+
+        self.update_status(enrollment_id, &practice.id, status, audit).await
+    }
 }
 
 #[async_trait]
@@ -62,29 +294,45 @@ impl PracticeService for PracticeServiceImpl {
         self.practices.find_by_id(id).await
     }
 
+    async fn get_authorized(&self, actor: &User, id: &Uuid) -> Result<Practice, AppError> {
+        let enrollments = self.enrollments.get_all(enrollment_filter! { practice_id: *id }).await?;
+
+        let Some((enrollment, _, _)) = enrollments.into_iter().next() else {
+            return Err(AppError::ResourceNotFound(*id));
+        };
+
+        let (_, _, practice) = self.enrollments.get_authorized(actor, &enrollment.id).await?;
+
+        practice.ok_or(AppError::ResourceNotFound(*id))
+    }
+
     async fn create(
         &self,
+        actor: &User,
         enrollment_id: &Uuid,
         input: CreatePracticeDto,
+        audit: &AuditContext,
     ) -> Result<Practice, AppError> {
         let practice = Practice::from(input);
 
-        let (enrollment, student, _) = self.enrollments.get_by_id(enrollment_id).await?;
+        let (enrollment, student, _) =
+            self.enrollments.get_authorized(actor, enrollment_id).await?;
 
         let (course, _) = self.courses.get_by_id(&enrollment.course_id).await?;
 
+        self.ensure_within_period(&course, &practice).await?;
+
         let practice = self.practices.save(practice).await?;
 
-        let enrollment = {
-            let data = UpdateEnrollmentDto {
-                practice_id: Some(practice.id.to_string()),
-                student_scores: None,
-            };
+        let entry = AuditEntry::new(audit, "practice.create", "practice", Some(practice.id))
+            .after(&practice);
+        self.audit_log.record(entry).await?;
 
-            self.enrollments.update(enrollment_id, data).await?
-        };
+        let enrollment = self.enrollments.set_practice(enrollment_id, &practice.id, audit).await?;
+
+        let links = self.issue_review_links(&practice).await?;
 
-        let event_data = (student, practice.clone(), course, enrollment);
+        let event_data = (student, practice.clone(), course, enrollment, links);
 
         self.event_queue.publish(Event::PracticeCreated(event_data)).await;
 
@@ -96,6 +344,7 @@ impl PracticeService for PracticeServiceImpl {
         enrollment_id: &Uuid,
         practice_id: &Uuid,
         status: PracticeStatus,
+        audit: &AuditContext,
     ) -> AppResult<Practice> {
         let (enrollment, student, practice) = self.enrollments.get_by_id(enrollment_id).await?;
 
@@ -105,26 +354,106 @@ impl PracticeService for PracticeServiceImpl {
             return Err(AppError::ResourceNotFound(*practice_id));
         }
 
-        let (course, teacher) = self.courses.get_by_id(&enrollment.course_id).await?;
+        let before = practice.clone();
 
-        let event_data = (student, enrollment, practice.clone(), course, teacher);
+        let (course, staff) = self.courses.get_by_id(&enrollment.course_id).await?;
+
+        let teacher = staff_user(&staff, StaffRole::Lead)
+            .ok_or(AppError::ResourceNotFound(course.teacher_id))?;
+        let coordinator = staff_user(&staff, StaffRole::Coordinator);
+
+        let action = match status {
+            PracticeStatus::Approved => "practice.approve",
+            _ => "practice.decline",
+        };
 
         match status {
             PracticeStatus::Approved => {
+                let links = self.issue_approval_links(&practice).await?;
+
+                let event_data =
+                    (student, enrollment, practice.clone(), course, teacher, coordinator, links);
+
                 practice.practice_status = PracticeStatus::Approved;
                 self.event_queue.publish(Event::PracticeApproved(event_data)).await;
             }
             PracticeStatus::Declined => {
+                let event_data =
+                    (student, enrollment, practice.clone(), course, teacher, coordinator);
+
                 practice.practice_status = PracticeStatus::Declined;
                 self.event_queue.publish(Event::PracticeDeclined(event_data)).await;
             }
             _ => unreachable!("Only Approved or Declined statuses are allowed in this method"),
         }
 
-        self.practices.save(practice).await
+        let practice = self.practices.save(practice).await?;
+
+        // Una vez revisada, el enlace de la alternativa que no se usó deja de servir.
+        self.supervisor_tokens
+            .invalidate(&practice.id, &[SupervisorAction::Approve, SupervisorAction::Decline])
+            .await?;
+
+        let entry = AuditEntry::new(audit, action, "practice", Some(practice.id))
+            .diff(Some(&before), Some(&practice));
+        self.audit_log.record(entry).await?;
+
+        Ok(practice)
+    }
+
+    async fn review(
+        &self,
+        actor: &User,
+        enrollment_id: &Uuid,
+        status: PracticeStatus,
+        audit: &AuditContext,
+    ) -> AppResult<Practice> {
+        let (enrollment, _, practice) = self.enrollments.get_by_id(enrollment_id).await?;
+        let (_, staff) = self.courses.get_by_id(&enrollment.course_id).await?;
+        let staff = staff.into_iter().map(|(member, _)| member).collect::<Vec<_>>();
+
+        ensure_practice_review(actor, &staff)?;
+
+        self.review_pending(enrollment_id, practice, status, audit).await
+    }
+
+    async fn supervisor_review(
+        &self,
+        enrollment_id: &Uuid,
+        practice_id: &Uuid,
+        token: &str,
+        status: PracticeStatus,
+        audit: &AuditContext,
+    ) -> AppResult<Practice> {
+        let action = match status {
+            PracticeStatus::Approved => SupervisorAction::Approve,
+            _ => SupervisorAction::Decline,
+        };
+
+        let token = self.find_supervisor_token(practice_id, action, token).await?;
+
+        let (_, _, practice) = self.enrollments.get_by_id(enrollment_id).await?;
+
+        if practice.as_ref().is_some_and(|practice| practice.id != *practice_id) {
+            return Err(AppError::ResourceNotFound(*practice_id));
+        }
+
+        self.claim_supervisor_token(&token).await?;
+
+        self.review_pending(enrollment_id, practice, status, audit).await
     }
 
-    async fn authorize(&self, practice_id: &Uuid, doc: Bytes<&[u8]>) -> Result<(), AppError> {
+    async fn authorize(
+        &self,
+        practice_id: &Uuid,
+        token: &str,
+        doc: Bytes<&[u8]>,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        let token = self
+            .find_supervisor_token(practice_id, SupervisorAction::Authorize, token)
+            .await?;
+
         let practice = self
             .practices
             .find_by_id(practice_id)
@@ -136,6 +465,11 @@ impl PracticeService for PracticeServiceImpl {
             .collect::<Result<Vec<u8>, std::io::Error>>()
             .map_err(|e| AppError::InternalServerError(e.into()))?;
 
+        self.claim_supervisor_token(&token).await?;
+
+        let entry = AuditEntry::new(audit, "practice.authorize", "practice", Some(practice.id));
+        self.audit_log.record(entry).await?;
+
         let event_data = (practice, bytes);
 
         self.event_queue.publish(Event::PracticeAuthorized(event_data)).await;
@@ -143,9 +477,37 @@ impl PracticeService for PracticeServiceImpl {
         Ok(())
     }
 
-    async fn update(&self, id: &Uuid, input: UpdatePracticeDto) -> Result<Practice, AppError> {
-        let mut practice =
-            self.practices.find_by_id(id).await?.ok_or(AppError::ResourceNotFound(*id))?;
+    async fn evaluate(
+        &self,
+        practice_id: &Uuid,
+        token: &str,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        let token = self
+            .find_supervisor_token(practice_id, SupervisorAction::Evaluate, token)
+            .await?;
+
+        self.claim_supervisor_token(&token).await?;
+
+        let entry = AuditEntry::new(audit, "practice.evaluate", "practice", Some(*practice_id));
+        self.audit_log.record(entry).await
+    }
+
+    async fn update(
+        &self,
+        actor: &User,
+        enrollment_id: &Uuid,
+        input: UpdatePracticeDto,
+        audit: &AuditContext,
+    ) -> Result<Practice, AppError> {
+        let (enrollment, student, practice) =
+            self.enrollments.get_authorized(actor, enrollment_id).await?;
+
+        let Some(mut practice) = practice else {
+            return Err(AppError::ResourceNotFound(*enrollment_id));
+        };
+
+        let before = practice.clone();
 
         if let Some(enterprise_name) = input.enterprise_name {
             practice.enterprise_name = enterprise_name;
@@ -175,13 +537,74 @@ impl PracticeService for PracticeServiceImpl {
             practice.end_date = end_date;
         }
 
-        self.practices.save(practice).await
+        let (course, staff) = self.courses.get_by_id(&enrollment.course_id).await?;
+
+        self.ensure_within_period(&course, &practice).await?;
+
+        let practice = self.practices.save(practice).await?;
+
+        let entry = AuditEntry::new(audit, "practice.update", "practice", Some(practice.id))
+            .diff(Some(&before), Some(&practice));
+        self.audit_log.record(entry).await?;
+
+        let supervisor_changed = practice.supervisor_email != before.supervisor_email
+            || practice.start_date != before.start_date
+            || practice.end_date != before.end_date;
+
+        if !supervisor_changed {
+            return Ok(practice);
+        }
+
+        // Los enlaces ya enviados corresponden al supervisor o a las fechas
+        // anteriores: se revocan y se envían nuevos con los datos actuales.
+        match practice.practice_status {
+            PracticeStatus::Pending => {
+                self.supervisor_tokens
+                    .invalidate(
+                        &practice.id,
+                        &[SupervisorAction::Approve, SupervisorAction::Decline],
+                    )
+                    .await?;
+
+                let links = self.issue_review_links(&practice).await?;
+                let event_data = (student, practice.clone(), course, enrollment, links);
+
+                self.event_queue.publish(Event::PracticeCreated(event_data)).await;
+            }
+            PracticeStatus::Approved => {
+                self.supervisor_tokens
+                    .invalidate(
+                        &practice.id,
+                        &[SupervisorAction::Authorize, SupervisorAction::Evaluate],
+                    )
+                    .await?;
+
+                let teacher = staff_user(&staff, StaffRole::Lead)
+                    .ok_or(AppError::ResourceNotFound(course.teacher_id))?;
+                let coordinator = staff_user(&staff, StaffRole::Coordinator);
+
+                let links = self.issue_approval_links(&practice).await?;
+                let event_data =
+                    (student, enrollment, practice.clone(), course, teacher, coordinator, links);
+
+                self.event_queue.publish(Event::PracticeApproved(event_data)).await;
+            }
+            PracticeStatus::Declined => {}
+        }
+
+        Ok(practice)
     }
 
-    async fn remove(&self, id: &Uuid) -> Result<(), AppError> {
+    async fn remove(&self, id: &Uuid, audit: &AuditContext) -> Result<(), AppError> {
         let practice =
             self.practices.find_by_id(id).await?.ok_or(AppError::ResourceNotFound(*id))?;
 
-        self.practices.delete(&practice.id).await
+        // Los enlaces pendientes de la práctica se eliminan junto con ella
+        // (`ON DELETE CASCADE`), por lo que ninguno sigue siendo válido.
+        self.practices.delete(&practice.id).await?;
+
+        let entry = AuditEntry::new(audit, "practice.delete", "practice", Some(practice.id))
+            .before(&practice);
+        self.audit_log.record(entry).await
     }
 }
@@ -1,9 +1,49 @@
+use sea_query::Order;
+use serde::{Deserialize, Serialize};
+
 pub struct Pagination<T> {
     pub items: Vec<T>,
+    pub total: u64,
     pub current_page: u64,
     pub total_pages: u64,
     pub has_next: bool,
     pub has_previous: bool,
 }
 
+impl<T> Pagination<T> {
+    pub fn new(items: Vec<T>, page: u64, page_size: u64, total: i64) -> Self {
+        let total = total.max(0) as u64;
+        let total_pages = total.div_ceil(page_size.max(1));
+
+        Pagination {
+            items,
+            total,
+            total_pages,
+            current_page: page,
+            has_previous: page > 1,
+            has_next: page < total_pages,
+        }
+    }
+}
+
 pub const DEFAULT_PAGE_SIZE: u64 = 10;
+
+/// Tamaño de página máximo que un cliente puede solicitar.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl From<SortDirection> for Order {
+    fn from(direction: SortDirection) -> Self {
+        match direction {
+            SortDirection::Asc => Order::Asc,
+            SortDirection::Desc => Order::Desc,
+        }
+    }
+}
@@ -20,6 +20,12 @@ pub enum AppError {
         source: sqlx::Error,
     },
 
+    #[error("Cache error: {source}")]
+    Cache {
+        #[from]
+        source: redis::RedisError,
+    },
+
     #[error("Not found: {0}")]
     ResourceNotFound(Uuid),
 
@@ -34,6 +40,15 @@ pub enum AppError {
 
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
 }
 
 impl From<AppError> for HttpResponse {
@@ -57,6 +72,12 @@ impl From<AppError> for HttpResponse {
 
             AppError::InvalidOperation(message) => HttpResponse::BadRequest().message(message),
 
+            AppError::Unauthorized(message) => HttpResponse::Unauthorized().message(message),
+
+            AppError::Forbidden(message) => HttpResponse::Forbidden().message(message),
+
+            AppError::TooManyRequests(message) => HttpResponse::TooManyRequests().message(message),
+
             _ => {
                 tracing::error!("Internal AppError: {error:?}");
 
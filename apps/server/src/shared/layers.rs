@@ -9,6 +9,7 @@ use tower_http::trace::{MakeSpan, OnRequest, OnResponse, TraceLayer as TowerTrac
 
 use tracing::Span;
 
+use crate::auth::IMPERSONATED_BY_HEADER;
 use crate::config::CorsConfig;
 
 type TraceLayer = TowerTraceLayer<
@@ -99,4 +100,5 @@ pub fn setup_cors(config: &CorsConfig) -> CorsLayer {
         .allow_credentials(config.allow_credentials)
         .allow_methods(methods)
         .allow_headers(headers)
+        .expose_headers([http::HeaderName::from_static(IMPERSONATED_BY_HEADER)])
 }
@@ -55,6 +55,13 @@ macro_rules! course_filter {
     };
 }
 
+#[macro_export]
+macro_rules! period_filter {
+    ($($field:ident $(: $value:expr)?),* $(,)?) => {
+        $crate::filter!(PeriodFilter, { $($field $(: $value)?),* })
+    };
+}
+
 #[macro_export]
 macro_rules! template_ctx {
     ($($key:expr => $value:expr),* $(,)?) => {
@@ -1,4 +1,5 @@
 use bcrypt::BcryptError;
+use jsonwebtoken::errors::Error as JwtError;
 
 use lettre::{
     address::AddressError, error::Error as LettreError, transport::smtp::Error as SmtpError,
@@ -32,6 +33,23 @@ pub enum ServiceError {
         #[from]
         source: TeraError,
     },
+
+    #[error("Token error: {source}")]
+    Token {
+        #[from]
+        source: JwtError,
+    },
+
+    #[error("TOTP error: {message}")]
+    Totp { message: String },
+}
+
+impl ServiceError {
+    pub fn totp(error: impl ToString) -> Self {
+        Self::Totp {
+            message: error.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -42,6 +60,9 @@ pub enum HasherError {
     #[error("Verify error: {source}")]
     Verify { source: BcryptError },
 
+    #[error("Argon2 error: {message}")]
+    Argon2 { message: String },
+
     #[error("Password generation error: {message}")]
     PasswordGeneration { message: String },
 }
@@ -55,6 +76,12 @@ impl HasherError {
         Self::Verify { source }
     }
 
+    pub fn argon2(error: argon2::password_hash::Error) -> Self {
+        Self::Argon2 {
+            message: error.to_string(),
+        }
+    }
+
     pub fn password_generation(message: String) -> Self {
         Self::PasswordGeneration { message }
     }
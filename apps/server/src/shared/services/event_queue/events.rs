@@ -1,17 +1,26 @@
 use chrono::{DateTime, Utc};
 use chrono_tz::America::Santiago;
 
-use crate::{courses::Course, enrollments::Enrollment, practices::Practice, users::User};
+use crate::{
+    courses::{Course, StaffMember},
+    enrollments::Enrollment,
+    practices::Practice,
+    users::{Rut, User},
+};
 
 #[derive(Debug, Clone)]
 pub enum Event {
-    PracticeCreated((User, Practice, Course, Enrollment)),
-    PracticeDeclined((User, Enrollment, Practice, Course, User)),
-    PracticeApproved((User, Enrollment, Practice, Course, User)),
+    PracticeCreated((User, Practice, Course, Enrollment, (String, String))),
+    PracticeDeclined((User, Enrollment, Practice, Course, User, Option<User>)),
+    PracticeApproved((User, Enrollment, Practice, Course, User, Option<User>, (String, String))),
     PracticeAuthorized((Practice, Vec<u8>)),
     UserCreated((String, String, String)),
     ManyUsersCreated(Vec<(String, String, String)>),
-    CourseCreated((Course, User)),
+    CourseCreated((Course, Vec<StaffMember>)),
+    StaffAssigned((Course, StaffMember)),
+    CourseClosed((Course, User, Vec<(Enrollment, User)>)),
+    PasswordResetRequested((String, String, String)),
+    EmailChangeRequested((String, String, String, String)),
 }
 
 pub fn format_date(date: String) -> String {
@@ -20,3 +29,23 @@ pub fn format_date(date: String) -> String {
     date.map(|date| date.with_timezone(&Santiago).format("%d/%m/%y").to_string())
         .unwrap_or_default()
 }
+
+/// RUT con separador de miles para las plantillas, tal como se almacenó si no es válido.
+pub fn display_rut(rut: String) -> String {
+    rut.parse::<Rut>().map(|rut| rut.formatted()).unwrap_or(rut)
+}
+
+/// Filas de notas finales para las plantillas, que solo reciben texto: columnas
+/// `rut|nombre|nota` y filas separadas por `||`, que la plantilla recorre con `split`.
+pub fn final_grade_rows(results: Vec<(Enrollment, User)>) -> String {
+    results
+        .into_iter()
+        .map(|(enrollment, student)| {
+            let grade = enrollment.final_grade.map(|g| format!("{g:.1}")).unwrap_or_default();
+            let name = student.name.replace('|', "/");
+
+            format!("{}|{name}|{grade}", display_rut(student.rut))
+        })
+        .collect::<Vec<_>>()
+        .join("||")
+}
@@ -1,10 +1,10 @@
 use std::{env, path::Path, sync::Arc};
 use tokio::sync::{mpsc::Receiver, Mutex};
-use uuid::Uuid;
 
 use crate::{
+    courses::{Course, StaffMember},
     shared::services::{
-        event_queue::{format_date, Event},
+        event_queue::{display_rut, final_grade_rows, format_date, Event},
         mailer::{MailTo, Mailer},
         printer::{PrintOptions, Printer},
         templates::RawContext,
@@ -62,9 +62,17 @@ impl EventSubscriber {
         printer: Arc<Printer>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         match event {
-            Event::PracticeApproved((student, enrollment, practice, course, teacher)) => {
+            Event::PracticeApproved((
+                student,
+                enrollment,
+                practice,
+                course,
+                teacher,
+                coordinator,
+                (authorize_token, evaluate_token),
+            )) => {
                 let mut template_ctx = template_ctx! {
-                    "student_rut" => student.rut,
+                    "student_rut" => display_rut(student.rut),
                     "student_name" => student.name,
                     "course_name" => course.name,
                     "course_code" => course.code,
@@ -91,12 +99,18 @@ impl EventSubscriber {
                 template_ctx.push(("practice_auth_doc_url", practice_auth_doc));
                 template_ctx.push((
                     "practice_auth_form_url",
-                    format!("/enrollments/{}/practice/{}/authorize", enrollment.id, practice.id),
+                    format!(
+                        "/enrollments/{}/practice/{}/authorize?token={authorize_token}",
+                        enrollment.id, practice.id
+                    ),
                 ));
 
                 template_ctx.push((
                     "practice_evaluation_form_url",
-                    format!("/enrollments/{}/practice/{}/evaluate", enrollment.id, practice.id),
+                    format!(
+                        "/enrollments/{}/practice/{}/evaluate?token={evaluate_token}",
+                        enrollment.id, practice.id
+                    ),
                 ));
 
                 let (_, _, _, _) = tokio::join!(
@@ -121,14 +135,34 @@ impl EventSubscriber {
                     mailer.send(MailTo {
                         subject: "Práctica Aprobada",
                         template: "practice:approval:secretary",
-                        context: template_ctx,
+                        context: template_ctx.clone(),
                         email: mailer.context().config().secretary_email.clone(),
                     })
                 );
+
+                if let Some(coordinator) = coordinator {
+                    template_ctx.push(("coordinator_name", coordinator.name));
+
+                    mailer
+                        .send(MailTo {
+                            subject: "Práctica Aprobada",
+                            template: "practice:approval:coordinator",
+                            email: coordinator.email,
+                            context: template_ctx,
+                        })
+                        .await?;
+                }
             }
 
-            Event::PracticeDeclined((student, _enrollment, practice, course, teacher)) => {
-                let email_context: RawContext = vec![
+            Event::PracticeDeclined((
+                student,
+                _enrollment,
+                practice,
+                course,
+                teacher,
+                coordinator,
+            )) => {
+                let mut email_context: RawContext = vec![
                     ("student_name", student.name),
                     ("course_name", course.name),
                     ("course_code", course.code),
@@ -158,12 +192,31 @@ impl EventSubscriber {
                         subject: "Inscripción a Práctica Rechazada",
                         template: "practice:decline:teacher",
                         email: teacher.email,
-                        context: email_context,
+                        context: email_context.clone(),
                     }),
                 )?;
+
+                if let Some(coordinator) = coordinator {
+                    email_context.push(("coordinator_name", coordinator.name));
+
+                    mailer
+                        .send(MailTo {
+                            subject: "Inscripción a Práctica Rechazada",
+                            template: "practice:decline:coordinator",
+                            email: coordinator.email,
+                            context: email_context,
+                        })
+                        .await?;
+                }
             }
 
-            Event::PracticeCreated((student, practice, course, enrollment)) => {
+            Event::PracticeCreated((
+                student,
+                practice,
+                course,
+                enrollment,
+                (approve_token, decline_token),
+            )) => {
                 let start_date = format_date(practice.start_date.to_string());
                 let end_date = format_date(practice.end_date.to_string());
 
@@ -181,17 +234,15 @@ impl EventSubscriber {
                     (
                         "approval_link",
                         format!(
-                            "/enrollments/{}/practice/{}/approve",
-                            enrollment.id,
-                            enrollment.practice_id.unwrap_or(Uuid::new_v4())
+                            "/enrollments/{}/practice/{}/approve?token={approve_token}",
+                            enrollment.id, practice.id
                         ),
                     ),
                     (
                         "rejection_link",
                         format!(
-                            "/enrollments/{}/practice/{}/reject",
-                            enrollment.id,
-                            enrollment.practice_id.unwrap_or(Uuid::new_v4())
+                            "/enrollments/{}/practice/{}/decline?token={decline_token}",
+                            enrollment.id, practice.id
                         ),
                     ),
                 ];
@@ -248,23 +299,83 @@ impl EventSubscriber {
                 }
             }
 
-            Event::CourseCreated((course, teacher)) => {
+            Event::PasswordResetRequested((name, email, token)) => {
+                let context: RawContext = vec![("name", name), ("token", token)];
+
+                let mail_opts = MailTo {
+                    subject: "Recuperación de contraseña",
+                    email,
+                    template: "system:password-reset",
+                    context,
+                };
+
+                mailer.send(mail_opts).await?;
+            }
+
+            Event::EmailChangeRequested((name, old_email, new_email, token)) => {
                 let context: RawContext = vec![
-                    ("course_name", course.name),
-                    ("course_code", course.code),
-                    ("teacher_name", teacher.name.clone()),
+                    ("name", name.clone()),
+                    ("email", new_email.clone()),
+                    ("token", token),
                 ];
 
                 let mail_opts = MailTo {
-                    subject: "Asignación de Curso",
-                    email: teacher.email.clone(),
-                    template: "course:creation:teacher",
+                    subject: "Confirmación de cambio de email",
+                    email: new_email.clone(),
+                    template: "system:email-change",
+                    context,
+                };
+
+                mailer.send(mail_opts).await?;
+
+                let context: RawContext = vec![("name", name), ("email", new_email)];
+
+                let mail_opts = MailTo {
+                    subject: "Solicitud de cambio de email",
+                    email: old_email,
+                    template: "system:email-change-notice",
                     context,
                 };
 
                 mailer.send(mail_opts).await?;
             }
 
+            Event::CourseCreated((course, staff)) => {
+                for member in staff {
+                    mailer.send(course_assignment_mail(&course, member)).await?;
+                }
+            }
+
+            Event::StaffAssigned((course, member)) => {
+                mailer.send(course_assignment_mail(&course, member)).await?;
+            }
+
+            Event::CourseClosed((course, teacher, results)) => {
+                let context: RawContext = vec![
+                    ("course_name", course.name),
+                    ("course_code", course.code),
+                    ("course_year", course.year.to_string()),
+                    ("teacher_name", teacher.name.clone()),
+                    ("student_count", results.len().to_string()),
+                    ("final_grades", final_grade_rows(results)),
+                ];
+
+                tokio::try_join!(
+                    mailer.send(MailTo {
+                        subject: "Cierre de Curso y Notas Finales",
+                        template: "course:closing:teacher",
+                        email: teacher.email,
+                        context: context.clone(),
+                    }),
+                    mailer.send(MailTo {
+                        subject: "Cierre de Curso y Notas Finales",
+                        template: "course:closing:secretary",
+                        email: mailer.context().config().secretary_email.clone(),
+                        context,
+                    })
+                )?;
+            }
+
             Event::PracticeAuthorized((practice, pdf)) => {
                 let practice_static_dir = format!("practices/{}/authorization.pdf", practice.id);
                 let documents_dir = env::var("DOCUMENTS_DIR").unwrap_or(".".to_string());
@@ -284,3 +395,20 @@ impl EventSubscriber {
         Ok(())
     }
 }
+
+/// Aviso de asignación a un curso, con el rol que cumple en su equipo docente.
+fn course_assignment_mail(course: &Course, (member, user): StaffMember) -> MailTo {
+    let context: RawContext = vec![
+        ("course_name", course.name.clone()),
+        ("course_code", course.code.clone()),
+        ("teacher_name", user.name),
+        ("staff_role", member.role.label().to_string()),
+    ];
+
+    MailTo {
+        subject: "Asignación de Curso",
+        email: user.email,
+        template: "course:creation:teacher",
+        context,
+    }
+}
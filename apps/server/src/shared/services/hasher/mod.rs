@@ -1,35 +1,34 @@
-use crate::shared::services::errors::{HasherError, ServiceError};
+use argon2::{
+    password_hash::{
+        rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString,
+    },
+    Argon2,
+};
 use passwords::PasswordGenerator;
+use serde::Deserialize;
 use shaku::{Component, Interface};
 
+use crate::{
+    config::AuthConfig,
+    shared::services::errors::{HasherError, ServiceError},
+};
+
 const PASSWORD_HASH_COST: u32 = 10;
 const DEFAULT_PASSWORD_LEN: usize = 12;
 
+const ARGON2ID_PREFIX: &str = "$argon2id$";
+const BCRYPT_PREFIXES: [&str; 4] = ["$2a$", "$2b$", "$2x$", "$2y$"];
+
 pub trait PasswordHasher: Interface {
     fn hash(&self, password: &str) -> Result<String, ServiceError>;
     fn verify(&self, password: &str, hash: &str) -> Result<bool, ServiceError>;
-    fn random_password(&self) -> Result<(String, String), ServiceError>;
-    fn generate_password(&self, len: usize) -> Result<String, ServiceError>;
-    fn generate_and_hash(&self, len: usize) -> Result<(String, String), ServiceError>;
-}
-
-#[derive(Component)]
-#[shaku(interface = PasswordHasher)]
-pub struct BcryptPasswordHasher;
 
-impl PasswordHasher for BcryptPasswordHasher {
-    fn hash(&self, password: &str) -> Result<String, ServiceError> {
-        Ok(bcrypt::hash(password, PASSWORD_HASH_COST).map_err(HasherError::hash)?)
-    }
-
-    fn verify(&self, password: &str, hash: &str) -> Result<bool, ServiceError> {
-        Ok(bcrypt::verify(password, hash).map_err(HasherError::verify)?)
-    }
+    /// Indica si el hash fue generado con un algoritmo o costo distinto al
+    /// configurado y debe recalcularse en el próximo inicio de sesión.
+    fn needs_rehash(&self, hash: &str) -> bool;
 
     fn random_password(&self) -> Result<(String, String), ServiceError> {
-        let plain = self.generate_password(DEFAULT_PASSWORD_LEN)?;
-        let hashed = self.hash(&plain)?;
-        Ok((plain, hashed))
+        self.generate_and_hash(DEFAULT_PASSWORD_LEN)
     }
 
     fn generate_password(&self, len: usize) -> Result<String, ServiceError> {
@@ -58,3 +57,142 @@ impl PasswordHasher for BcryptPasswordHasher {
         Ok((plain, hashed))
     }
 }
+
+/// Algoritmo usado para los nuevos hashes, seleccionado en `auth.password_hasher`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Bcrypt,
+    #[default]
+    Argon2id,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> BCRYPT <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+pub struct BcryptPasswordHasher {
+    pub cost: u32,
+}
+
+impl Default for BcryptPasswordHasher {
+    fn default() -> Self {
+        BcryptPasswordHasher {
+            cost: PASSWORD_HASH_COST,
+        }
+    }
+}
+
+impl PasswordHasher for BcryptPasswordHasher {
+    fn hash(&self, password: &str) -> Result<String, ServiceError> {
+        Ok(bcrypt::hash(password, self.cost).map_err(HasherError::hash)?)
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, ServiceError> {
+        verify_any(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        bcrypt_cost(hash) != Some(self.cost)
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> ARGON2ID <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Argon2id con los parámetros por defecto de la librería (19 MiB, 2 iteraciones,
+/// 1 hilo), los recomendados por OWASP.
+#[derive(Default)]
+pub struct Argon2PasswordHasher;
+
+impl PasswordHasher for Argon2PasswordHasher {
+    fn hash(&self, password: &str) -> Result<String, ServiceError> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        let hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(HasherError::argon2)?;
+
+        Ok(hash.to_string())
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, ServiceError> {
+        verify_any(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        !hash.starts_with(ARGON2ID_PREFIX)
+    }
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> CONFIGURABLE <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Hasher registrado en el contenedor: genera los hashes con el algoritmo
+/// configurado y verifica tanto hashes bcrypt como Argon2id.
+#[derive(Component)]
+#[shaku(interface = PasswordHasher)]
+pub struct ConfigurablePasswordHasher {
+    config: AuthConfig,
+}
+
+impl From<AuthConfig> for ConfigurablePasswordHasherParameters {
+    fn from(config: AuthConfig) -> Self {
+        ConfigurablePasswordHasherParameters { config }
+    }
+}
+
+impl ConfigurablePasswordHasher {
+    fn bcrypt(&self) -> BcryptPasswordHasher {
+        BcryptPasswordHasher {
+            cost: self.config.bcrypt_cost,
+        }
+    }
+}
+
+impl PasswordHasher for ConfigurablePasswordHasher {
+    fn hash(&self, password: &str) -> Result<String, ServiceError> {
+        match self.config.password_hasher {
+            HashAlgorithm::Bcrypt => self.bcrypt().hash(password),
+            HashAlgorithm::Argon2id => Argon2PasswordHasher.hash(password),
+        }
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, ServiceError> {
+        verify_any(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        match self.config.password_hasher {
+            HashAlgorithm::Bcrypt => self.bcrypt().needs_rehash(hash),
+            HashAlgorithm::Argon2id => Argon2PasswordHasher.needs_rehash(hash),
+        }
+    }
+}
+
+/// Verifica la contraseña según el formato del hash almacenado. Los formatos
+/// desconocidos se consideran no coincidentes.
+fn verify_any(password: &str, hash: &str) -> Result<bool, ServiceError> {
+    if hash.starts_with(ARGON2ID_PREFIX) {
+        let parsed = PasswordHash::new(hash).map_err(HasherError::argon2)?;
+
+        return Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok());
+    }
+
+    if BCRYPT_PREFIXES.iter().any(|prefix| hash.starts_with(prefix)) {
+        return Ok(bcrypt::verify(password, hash).map_err(HasherError::verify)?);
+    }
+
+    Ok(false)
+}
+
+/// Costo de un hash bcrypt (`$2b$<costo>$...`), `None` si no es bcrypt.
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    if !BCRYPT_PREFIXES.iter().any(|prefix| hash.starts_with(prefix)) {
+        return None;
+    }
+
+    hash.split('$').nth(2).and_then(|cost| cost.parse().ok())
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use shaku::{Component, Interface};
+use uuid::Uuid;
+
+use crate::{config::AuthConfig, shared::services::errors::ServiceError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Session,
+    Refresh,
+}
+
+/// Alcance de un token de sesión. Las cuentas con contraseña generada por
+/// el sistema reciben un token `PasswordChange`, válido solo para cambiarla,
+/// y las que deben configurar la verificación en dos pasos uno `TwoFactorSetup`.
+/// `TwoFactorChallenge` no corresponde a una sesión: solo permite completar
+/// el segundo paso del login.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    #[default]
+    Full,
+    PasswordChange,
+    TwoFactorSetup,
+    TwoFactorChallenge,
+}
+
+/// Claims compartidos por los tokens de sesión y de refresco.
+/// `sid` identifica la sesión (familia de refresh tokens) y se mantiene
+/// constante entre rotaciones, `jti` es único por token emitido.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub sid: Uuid,
+    pub jti: Uuid,
+    pub iat: i64,
+    pub exp: i64,
+
+    #[serde(default)]
+    pub scope: TokenScope,
+}
+
+impl Claims {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_else(Utc::now)
+    }
+}
+
+pub trait JwtService: Interface {
+    fn claims(&self, kind: TokenKind, user_id: &Uuid, session_id: &Uuid) -> Claims;
+    fn sign(&self, kind: TokenKind, claims: &Claims) -> Result<String, ServiceError>;
+    fn verify(&self, kind: TokenKind, token: &str) -> Result<Claims, ServiceError>;
+}
+
+#[derive(Component)]
+#[shaku(interface = JwtService)]
+pub struct JsonWebTokenService {
+    config: AuthConfig,
+}
+
+impl JsonWebTokenService {
+    fn secret(&self, kind: TokenKind) -> &[u8] {
+        match kind {
+            TokenKind::Session => self.config.session_jwt_secret.as_bytes(),
+            TokenKind::Refresh => self.config.refresh_jwt_secret.as_bytes(),
+        }
+    }
+
+    fn expiration(&self, kind: TokenKind) -> Duration {
+        let ms = match kind {
+            TokenKind::Session => self.config.session_jwt_exp_ms,
+            TokenKind::Refresh => self.config.refresh_jwt_exp_ms,
+        };
+
+        Duration::milliseconds(ms as i64)
+    }
+}
+
+impl JwtService for JsonWebTokenService {
+    fn claims(&self, kind: TokenKind, user_id: &Uuid, session_id: &Uuid) -> Claims {
+        let now = Utc::now();
+
+        Claims {
+            sub: *user_id,
+            sid: *session_id,
+            jti: Uuid::new_v4(),
+            iat: now.timestamp(),
+            exp: (now + self.expiration(kind)).timestamp(),
+            scope: TokenScope::Full,
+        }
+    }
+
+    fn sign(&self, kind: TokenKind, claims: &Claims) -> Result<String, ServiceError> {
+        let key = EncodingKey::from_secret(self.secret(kind));
+
+        Ok(encode(&Header::default(), claims, &key)?)
+    }
+
+    fn verify(&self, kind: TokenKind, token: &str) -> Result<Claims, ServiceError> {
+        let key = DecodingKey::from_secret(self.secret(kind));
+        let data = decode::<Claims>(token, &key, &Validation::default())?;
+
+        Ok(data.claims)
+    }
+}
+
+impl From<AuthConfig> for JsonWebTokenServiceParameters {
+    fn from(config: AuthConfig) -> Self {
+        JsonWebTokenServiceParameters { config }
+    }
+}
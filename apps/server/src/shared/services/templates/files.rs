@@ -12,6 +12,18 @@ pub static PRINTER_TEMPLATES: LazyLock<Templates> = LazyLock::new(|| {
 pub static MAILER_TEMPLATES: LazyLock<Templates> = LazyLock::new(|| {
     vec![
         ("system:welcome.html", include_str!("../mailer/templates/system/welcome.html")),
+        (
+            "system:password-reset.html",
+            include_str!("../mailer/templates/system/password-reset.html"),
+        ),
+        (
+            "system:email-change.html",
+            include_str!("../mailer/templates/system/email-change.html"),
+        ),
+        (
+            "system:email-change-notice.html",
+            include_str!("../mailer/templates/system/email-change-notice.html"),
+        ),
         (
             "practice:creation:supervisor.html",
             include_str!("../mailer/templates/practice/creation/supervisor.html"),
@@ -48,9 +60,25 @@ pub static MAILER_TEMPLATES: LazyLock<Templates> = LazyLock::new(|| {
             "practice:decline:teacher.html",
             include_str!("../mailer/templates/practice/decline/teacher.html"),
         ),
+        (
+            "practice:approval:coordinator.html",
+            include_str!("../mailer/templates/practice/approval/coordinator.html"),
+        ),
+        (
+            "practice:decline:coordinator.html",
+            include_str!("../mailer/templates/practice/decline/coordinator.html"),
+        ),
         (
             "course:creation:teacher.html",
             include_str!("../mailer/templates/course/creation/teacher.html"),
         ),
+        (
+            "course:closing:teacher.html",
+            include_str!("../mailer/templates/course/closing/teacher.html"),
+        ),
+        (
+            "course:closing:secretary.html",
+            include_str!("../mailer/templates/course/closing/secretary.html"),
+        ),
     ]
 });
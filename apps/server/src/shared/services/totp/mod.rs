@@ -0,0 +1,91 @@
+use chrono::Utc;
+use shaku::{Component, Interface};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::{config::AuthConfig, shared::services::errors::ServiceError};
+
+/// Parámetros RFC 6238 compatibles con las aplicaciones autenticadoras habituales.
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECS: u64 = 30;
+
+/// Pasos aceptados antes y después del actual, por desfase de reloj del dispositivo.
+const TOTP_SKEW: u64 = 1;
+
+pub trait TotpService: Interface {
+    /// Secreto aleatorio codificado en base32.
+    fn generate_secret(&self) -> String;
+
+    /// URI `otpauth://` que las aplicaciones autenticadoras leen desde un código QR.
+    fn provisioning_uri(&self, secret: &str, account: &str) -> Result<String, ServiceError>;
+
+    /// Retorna el paso de tiempo al que corresponde el código, solo si es
+    /// posterior a `last_step`, de modo que un código no pueda reutilizarse.
+    fn verify(
+        &self,
+        secret: &str,
+        code: &str,
+        last_step: Option<i64>,
+    ) -> Result<Option<i64>, ServiceError>;
+}
+
+#[derive(Component)]
+#[shaku(interface = TotpService)]
+pub struct RfcTotpService {
+    config: AuthConfig,
+}
+
+impl From<AuthConfig> for RfcTotpServiceParameters {
+    fn from(config: AuthConfig) -> Self {
+        RfcTotpServiceParameters { config }
+    }
+}
+
+impl TotpService for RfcTotpService {
+    fn generate_secret(&self) -> String {
+        Secret::generate_secret().to_encoded().to_string()
+    }
+
+    fn provisioning_uri(&self, secret: &str, account: &str) -> Result<String, ServiceError> {
+        let totp = totp(secret, Some(self.config.totp_issuer.clone()), account.to_string())?;
+
+        Ok(totp.get_url())
+    }
+
+    fn verify(
+        &self,
+        secret: &str,
+        code: &str,
+        last_step: Option<i64>,
+    ) -> Result<Option<i64>, ServiceError> {
+        let totp = totp(secret, None, String::new())?;
+        let code = code.trim();
+
+        let current = Utc::now().timestamp() as u64 / TOTP_STEP_SECS;
+
+        let step = (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW)
+            .filter(|step| last_step.is_none_or(|last| *step as i64 > last))
+            .find(|step| totp.generate(step * TOTP_STEP_SECS) == code);
+
+        Ok(step.map(|step| step as i64))
+    }
+}
+
+/// Código válido para el secreto en el instante indicado (segundos UNIX).
+pub fn code_at(secret: &str, timestamp: u64) -> Result<String, ServiceError> {
+    Ok(totp(secret, None, String::new())?.generate(timestamp))
+}
+
+fn totp(secret: &str, issuer: Option<String>, account: String) -> Result<TOTP, ServiceError> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().map_err(ServiceError::totp)?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        TOTP_SKEW as u8,
+        TOTP_STEP_SECS,
+        secret,
+        issuer,
+        account,
+    )
+    .map_err(ServiceError::totp)
+}
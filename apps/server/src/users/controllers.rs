@@ -1,5 +1,13 @@
+use crate::auth::{ensure_not_impersonating, RoleMiddleware, SessionContext, SessionMiddleware};
 use crate::container::AppModule;
-use crate::users::{CreateUserDto, GetUsersQueryDto, UpdateUserDto, UserResponse, UserService};
+use crate::enrollments::{
+    EnrollmentService, StudentEnrollmentResponse, StudentEnrollmentsQueryDto,
+};
+use crate::shared::errors::AppError;
+use crate::users::{
+    ConfirmEmailChangeDto, CreateUserDto, GetUsersQueryDto, MergeUsersDto, Role, UpdateProfileDto,
+    UpdateUserDto, UserResponse, UserService,
+};
 
 use serde_json::json;
 use sword::{prelude::*, web::HttpResult};
@@ -11,8 +19,15 @@ pub struct UsersController;
 #[routes]
 impl UsersController {
     #[get("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn find_all(ctx: Context) -> HttpResult<HttpResponse> {
         let query = ctx.validated_query::<GetUsersQueryDto>()?;
+
+        if query.requests_deleted() && !ctx.session_user()?.is_administrator() {
+            let message = "Solo un administrador puede listar usuarios eliminados".to_string();
+            return Err(AppError::Forbidden(message).into());
+        }
+
         let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
 
         let data = service.get_all(query.into()).await?;
@@ -20,6 +35,7 @@ impl UsersController {
 
         let json = json!({
             "users": users,
+            "total": data.total,
             "currentPage": data.current_page,
             "totalPages": data.total_pages,
             "hasNext": data.has_next,
@@ -30,33 +46,165 @@ impl UsersController {
     }
 
     #[post("/")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator, Role::Secretary])]
     async fn create(ctx: Context) -> HttpResult<HttpResponse> {
         let user_data = ctx.validated_body::<CreateUserDto>()?;
         let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
 
-        let user = service.create(user_data).await?;
+        let user = service.create(user_data, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Created().data(UserResponse::from(user)))
     }
 
     #[put("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
     pub async fn update(ctx: Context) -> HttpResult<HttpResponse> {
         let id = ctx.param::<Uuid>("id")?;
         let user_data = ctx.validated_body::<UpdateUserDto>()?;
 
         let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
-        let user = service.update(id, user_data).await?;
+        let user = service.update(id, user_data, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok().data(UserResponse::from(user)))
+    }
+
+    /// Confirmar un cambio de email con el token enviado a la nueva dirección.
+    /// Ruta pública, el token acredita el acceso a ese correo.
+
+    #[post("/email/confirm")]
+    async fn confirm_email_change(ctx: Context) -> HttpResult<HttpResponse> {
+        let input = ctx.validated_body::<ConfirmEmailChangeDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        let user = service.confirm_email_change(&input.token, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok().data(UserResponse::from(user)))
     }
 
     #[delete("/{id}")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
     async fn remove(ctx: Context) -> HttpResult<HttpResponse> {
         let id = ctx.param::<Uuid>("id")?;
         let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
 
-        service.remove(id).await?;
+        service.remove(id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    #[post("/{id}/unlock")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn unlock(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        service.unlock(id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+
+    #[delete("/{id}/sessions")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn revoke_sessions(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        service.revoke_sessions(id, &ctx.audit_context()).await?;
 
         Ok(HttpResponse::Ok())
     }
+
+    /// Fusionar una cuenta duplicada en la indicada. Devuelve el detalle de
+    /// las inscripciones traspasadas y de las notas elegidas en los cursos
+    /// compartidos.
+
+    #[post("/{id}/merge")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn merge(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let input = ctx.validated_body::<MergeUsersDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        let report = service.merge(id, input.source_id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok().data(report))
+    }
+
+    /// Restaurar un usuario eliminado cuyos datos no hayan sido purgados.
+
+    #[post("/{id}/restore")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn restore(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        let user = service.restore(id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok().data(UserResponse::from(user)))
+    }
+
+    /// Anonimizar de forma irreversible los datos personales de un usuario
+    /// eliminado, conservando su historial de inscripciones.
+
+    #[delete("/{id}/purge")]
+    #[middleware(RoleMiddleware, config = &[Role::Administrator])]
+    async fn purge(ctx: Context) -> HttpResult<HttpResponse> {
+        let id = ctx.param::<Uuid>("id")?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        service.purge(id, &ctx.audit_context()).await?;
+
+        Ok(HttpResponse::Ok())
+    }
+}
+
+/// Cuenta del usuario autenticado.
+#[controller("/me")]
+pub struct MeController;
+
+#[routes]
+impl MeController {
+    #[get("/")]
+    #[middleware(SessionMiddleware)]
+    async fn profile(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+
+        Ok(HttpResponse::Ok().data(UserResponse::from(user)))
+    }
+
+    /// Actualizar el nombre, email o contraseña propios. No disponible
+    /// durante una suplantación.
+
+    #[patch("/")]
+    #[middleware(SessionMiddleware)]
+    async fn update_profile(ctx: Context) -> HttpResult<HttpResponse> {
+        let session = ctx.current_session()?;
+        ensure_not_impersonating(&session)?;
+
+        let input = ctx.validated_body::<UpdateProfileDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn UserService>()?;
+
+        let user = service
+            .update_profile(ctx.session_user()?, &session.id, input, &ctx.audit_context())
+            .await?;
+
+        Ok(HttpResponse::Ok().data(UserResponse::from(user)))
+    }
+
+    /// Cursos en que el usuario está inscrito, con sus notas y práctica.
+    /// Filtrable por periodo académico.
+
+    #[get("/enrollments")]
+    #[middleware(SessionMiddleware)]
+    async fn enrollments(ctx: Context) -> HttpResult<HttpResponse> {
+        let user = ctx.session_user()?;
+        let query = ctx.validated_query::<StudentEnrollmentsQueryDto>()?;
+        let service = ctx.get_dependency::<AppModule, dyn EnrollmentService>()?;
+
+        let enrollments = service.get_by_student(&user.id, query.period_id).await?;
+        let response =
+            enrollments.into_iter().map(StudentEnrollmentResponse::from).collect::<Vec<_>>();
+
+        Ok(HttpResponse::Ok().data(response))
+    }
 }
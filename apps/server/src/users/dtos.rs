@@ -1,4 +1,4 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{str::FromStr, sync::OnceLock};
@@ -6,8 +6,11 @@ use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
 use crate::{
-    shared::errors::{AppError, Input},
-    users::{Role, User, UserFilter},
+    shared::{
+        entities::{SortDirection, MAX_PAGE_SIZE},
+        errors::{AppError, Input},
+    },
+    users::{Role, Rut, User, UserFilter, UserSortField},
 };
 
 // ============================================================================
@@ -17,8 +20,7 @@ use crate::{
 #[derive(Serialize, Deserialize, Validate, Debug, Clone)]
 #[validate(schema(function = "validate_password_pairs"))]
 pub struct CreateUserDto {
-    #[validate(custom(function = "validate_rut_id"))]
-    pub rut: String,
+    pub rut: Rut,
 
     #[validate(length(
         min = 5,
@@ -49,12 +51,15 @@ impl TryFrom<CreateUserDto> for User {
 
         Ok(User {
             id: Uuid::new_v4(),
-            rut: dto.rut,
+            rut: dto.rut.to_string(),
             name: dto.name,
             email: dto.email,
             password: dto.password,
             roles,
+            must_change_password: false,
+            password_changed_at: None,
             deleted_at: None,
+            purged_at: None,
             created_at: Utc::now(),
         })
     }
@@ -68,6 +73,7 @@ impl FromStr for Role {
             "administrator" => Ok(Role::Administrator),
             "teacher" => Ok(Role::Teacher),
             "student" => Ok(Role::Student),
+            "coordinator" => Ok(Role::Coordinator),
             "secretary" => Ok(Role::Secretary),
             _ => Err(AppError::InvalidInput(Input {
                 field: "role".to_string(),
@@ -106,11 +112,88 @@ pub struct UpdateUserDto {
     pub roles: Option<Vec<String>>,
 }
 
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>> UPDATE PROFILE DTO <<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Cambios que un usuario puede hacer sobre su propia cuenta. Modificar el
+/// email o la contraseña exige confirmar la contraseña actual.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_profile_update"))]
+pub struct UpdateProfileDto {
+    #[validate(length(
+        min = 5,
+        max = 100,
+        message = "El nombre debe tener entre 5 y 100 caracteres."
+    ))]
+    pub name: Option<String>,
+
+    #[validate(email(message = "El email debe ser válido."))]
+    pub email: Option<String>,
+
+    #[validate(custom(function = "password_schema"))]
+    pub password: Option<String>,
+
+    #[validate(custom(function = "password_schema"))]
+    pub confirm_password: Option<String>,
+
+    #[validate(length(min = 1, max = 100, message = "La contraseña actual es obligatoria."))]
+    pub current_password: Option<String>,
+}
+
+impl UpdateProfileDto {
+    pub fn changes_credentials(&self) -> bool {
+        self.email.is_some() || self.password.is_some()
+    }
+}
+
+fn validate_profile_update(dto: &UpdateProfileDto) -> Result<(), ValidationError> {
+    if dto.name.is_none() && !dto.changes_credentials() {
+        return Err(ValidationError::new("Debe indicar al menos un cambio"));
+    }
+
+    if dto.password != dto.confirm_password {
+        return Err(ValidationError::new("Passwords must match"));
+    }
+
+    if dto.changes_credentials() && dto.current_password.is_none() {
+        return Err(ValidationError::new(
+            "Debe confirmar su contraseña actual para cambiar el email o la contraseña",
+        ));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>> CONFIRM EMAIL CHANGE DTO <<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ConfirmEmailChangeDto {
+    #[validate(length(min = 1, message = "El token de confirmación es obligatorio."))]
+    pub token: String,
+}
+
+// ============================================================================
+// >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>> MERGE USERS DTO <<<<<<<<<<<<<<<<<<<<<<<<<<<
+// ============================================================================
+
+/// Cuenta duplicada que se absorbe en la indicada en la ruta.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeUsersDto {
+    pub source_id: Uuid,
+}
+
 // ============================================================================
 // >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> GET USERS QUERY <<<<<<<<<<<<<<<<<<<<<<<<<<<
 // ============================================================================
 
 #[derive(Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_users_query"))]
 pub struct GetUsersQueryDto {
     #[validate(length(
         min = 1,
@@ -121,13 +204,77 @@ pub struct GetUsersQueryDto {
 
     #[validate(range(min = 1, message = "La página debe ser mayor o igual a 1."))]
     pub page: Option<usize>,
+
+    #[validate(range(
+        min = 1,
+        max = MAX_PAGE_SIZE,
+        message = "El tamaño de página debe estar entre 1 y 100."
+    ))]
+    pub per_page: Option<u64>,
+
+    /// Roles separados por coma, p. ej. `teacher,coordinator`.
+    #[validate(custom(function = "roles_query_validator"))]
+    pub roles: Option<String>,
+
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+
+    pub sort_by: Option<UserSortField>,
+    pub order: Option<SortDirection>,
+
+    /// Incluir usuarios eliminados, solo disponible para administradores.
+    pub include_deleted: Option<bool>,
+
+    /// Listar solo usuarios eliminados, solo disponible para administradores.
+    pub only_deleted: Option<bool>,
+}
+
+impl GetUsersQueryDto {
+    pub fn requests_deleted(&self) -> bool {
+        self.include_deleted == Some(true) || self.only_deleted == Some(true)
+    }
+}
+
+fn validate_users_query(dto: &GetUsersQueryDto) -> Result<(), ValidationError> {
+    if let (Some(from), Some(to)) = (dto.created_from, dto.created_to) {
+        if from > to {
+            return Err(ValidationError::new(
+                "La fecha de inicio debe ser anterior a la de término",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn roles_query_validator(roles: &str) -> Result<(), ValidationError> {
+    role_validator(&split_roles(roles))
+}
+
+fn split_roles(roles: &str) -> Vec<String> {
+    roles.split(',').map(|role| role.trim().to_string()).collect()
 }
 
 impl From<GetUsersQueryDto> for UserFilter {
     fn from(dto: GetUsersQueryDto) -> Self {
+        let roles = dto.roles.map(|roles| {
+            split_roles(&roles)
+                .iter()
+                .filter_map(|role| Role::from_str(role).ok())
+                .collect()
+        });
+
         UserFilter {
             search: dto.search,
             page: dto.page.unwrap_or(1) as u64,
+            per_page: dto.per_page,
+            roles,
+            created_from: dto.created_from,
+            created_to: dto.created_to,
+            include_deleted: dto.include_deleted.unwrap_or(false),
+            only_deleted: dto.only_deleted.unwrap_or(false),
+            sort_by: dto.sort_by.unwrap_or_default(),
+            direction: dto.order.unwrap_or_default(),
             ..UserFilter::default()
         }
     }
@@ -137,14 +284,16 @@ impl From<GetUsersQueryDto> for UserFilter {
 // >>>>>>>>>>>>>>>>>>>>>>>>>>>>>> USER RESPONSE <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
 // ============================================================================
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug)]
 pub struct UserResponse {
     pub id: String,
     pub rut: String,
     pub name: String,
     pub email: String,
     pub roles: Vec<Role>,
+    pub must_change_password: bool,
     pub created_at: String,
+    pub deleted_at: Option<String>,
 }
 
 impl From<User> for UserResponse {
@@ -155,7 +304,9 @@ impl From<User> for UserResponse {
             name: user_model.name,
             email: user_model.email,
             roles: user_model.roles.clone(),
+            must_change_password: user_model.must_change_password,
             created_at: user_model.created_at.to_rfc3339(),
+            deleted_at: user_model.deleted_at.map(|date| date.to_rfc3339()),
         }
     }
 }
@@ -188,7 +339,7 @@ fn validate_optional_password_pairs(dto: &UpdateUserDto) -> Result<(), Validatio
     }
 }
 
-fn password_schema(password: &str) -> Result<(), ValidationError> {
+pub fn password_schema(password: &str) -> Result<(), ValidationError> {
     if password.len() < 8 || password.len() > 100 {
         return Err(ValidationError::new("Password must be 8-100 characters long"));
     }
@@ -221,44 +372,6 @@ fn password_schema(password: &str) -> Result<(), ValidationError> {
 
 /// Valida si el RUT chileno es válido
 /// Formato esperado: "12345678-5" (con guion y dígito verificador)
-pub fn validate_rut_id(rut: &str) -> Result<(), ValidationError> {
-    let Some((number_part, dv_part)) = rut.split_once('-') else {
-        return Err(ValidationError::new("invalid_rut_format"));
-    };
-
-    let number: u32 = match number_part.parse() {
-        Ok(n) => n,
-        Err(_) => return Err(ValidationError::new("invalid_rut_number")),
-    };
-
-    let expected_dv = compute_rut_dv(number);
-    if expected_dv != dv_part.to_uppercase() {
-        return Err(ValidationError::new("invalid_rut_dv"));
-    }
-
-    Ok(())
-}
-
-/// Calcula el dígito verificador (DV) de un RUT chileno
-fn compute_rut_dv(mut rut: u32) -> String {
-    let mut sum = 0;
-    let mut multiplier = 2;
-
-    while rut > 0 {
-        let digit = rut % 10;
-        sum += digit * multiplier;
-        rut /= 10;
-        multiplier = if multiplier == 7 { 2 } else { multiplier + 1 };
-    }
-
-    let remainder = 11 - (sum % 11);
-    match remainder {
-        11 => "0".to_string(),
-        10 => "K".to_string(),
-        n => n.to_string(),
-    }
-}
-
 /// Valida que la lista de roles sea válida
 pub fn role_validator(roles: &Vec<String>) -> Result<(), ValidationError> {
     if roles.is_empty() {
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use uuid::Uuid;
 
+use crate::enrollments::{Enrollment, StudentScore};
+
 #[derive(Serialize, Deserialize, FromRow, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
@@ -13,7 +15,10 @@ pub struct User {
     pub email: String,
     pub password: String,
     pub roles: Vec<Role>,
+    pub must_change_password: bool,
+    pub password_changed_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub purged_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -33,6 +38,22 @@ impl User {
     pub fn is_secretary(&self) -> bool {
         self.roles.contains(&Role::Secretary)
     }
+
+    pub fn is_coordinator(&self) -> bool {
+        self.roles.contains(&Role::Coordinator)
+    }
+
+    pub fn has_any_role(&self, roles: &[Role]) -> bool {
+        roles.iter().any(|role| self.roles.contains(role))
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    pub fn is_purged(&self) -> bool {
+        self.purged_at.is_some()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Type, PartialEq)]
@@ -42,9 +63,23 @@ pub enum Role {
     Administrator,
     Student,
     Teacher,
+    Coordinator,
     Secretary,
 }
 
+impl Role {
+    /// Nombre del rol en el tipo `user_role` de la base de datos.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Administrator => "administrator",
+            Role::Student => "student",
+            Role::Teacher => "teacher",
+            Role::Coordinator => "coordinator",
+            Role::Secretary => "secretary",
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub enum Users {
     Table,
@@ -54,8 +89,11 @@ pub enum Users {
     Email,
     Password,
     Roles,
+    MustChangePassword,
+    PasswordChangedAt,
     CreatedAt,
     DeletedAt,
+    PurgedAt,
 }
 
 impl Iden for Users {
@@ -68,8 +106,11 @@ impl Iden for Users {
             Users::Email => "email",
             Users::Password => "password",
             Users::Roles => "roles",
+            Users::MustChangePassword => "must_change_password",
+            Users::PasswordChangedAt => "password_changed_at",
             Users::CreatedAt => "created_at",
             Users::DeletedAt => "deleted_at",
+            Users::PurgedAt => "purged_at",
         }
     }
 }
@@ -83,8 +124,211 @@ impl Default for User {
             email: String::default(),
             password: String::default(),
             roles: vec![Role::Student],
+            must_change_password: false,
+            password_changed_at: None,
             deleted_at: None,
+            purged_at: None,
             created_at: Utc::now(),
         }
     }
 }
+
+/// Cambio de email pendiente. Se aplica solo cuando el usuario confirma desde
+/// la nueva dirección el enlace recibido, que lleva `<id>.<secreto>`; solo se
+/// almacena el hash del secreto.
+#[derive(Debug, Clone, FromRow)]
+pub struct EmailChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub new_email: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EmailChange {
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[allow(dead_code)]
+pub enum EmailChanges {
+    Table,
+    Id,
+    UserId,
+    NewEmail,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
+
+impl Iden for EmailChanges {
+    fn unquoted(&self) -> &str {
+        match self {
+            EmailChanges::Table => "email_change_requests",
+            EmailChanges::Id => "id",
+            EmailChanges::UserId => "user_id",
+            EmailChanges::NewEmail => "new_email",
+            EmailChanges::TokenHash => "token_hash",
+            EmailChanges::ExpiresAt => "expires_at",
+            EmailChanges::UsedAt => "used_at",
+            EmailChanges::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Fusión de una cuenta duplicada (`source`) en la cuenta que se conserva
+/// (`target`). `combined` contiene las inscripciones del destino ya
+/// actualizadas con las notas y práctica elegidas, y `target_rut` el RUT
+/// canónico del destino cuando el almacenado no lo está.
+#[derive(Debug, Clone)]
+pub struct UserMerge {
+    pub report: MergeReport,
+    pub combined: Vec<Enrollment>,
+    pub target_rut: Option<String>,
+}
+
+impl UserMerge {
+    /// Cruza las inscripciones de ambas cuentas. Las de cursos que el destino
+    /// no tiene se traspasan; en los cursos compartidos se conserva, por
+    /// evaluación, la mayor de las dos notas y la práctica del destino si la tiene.
+    pub fn plan(
+        source_id: Uuid,
+        target_id: Uuid,
+        source_enrollments: Vec<Enrollment>,
+        target_enrollments: Vec<Enrollment>,
+    ) -> Self {
+        let mut report = MergeReport {
+            source_id,
+            target_id,
+            moved_enrollments: vec![],
+            combined_enrollments: vec![],
+        };
+
+        let mut combined = Vec::new();
+
+        for discarded in source_enrollments {
+            let Some(kept) = target_enrollments.iter().find(|e| e.course_id == discarded.course_id)
+            else {
+                report.moved_enrollments.push(discarded.id);
+                continue;
+            };
+
+            let mut kept = kept.clone();
+            let scores = Self::combine_scores(&discarded.student_scores, &kept.student_scores);
+
+            kept.student_scores = scores
+                .iter()
+                .map(|choice| StudentScore {
+                    evaluation_id: choice.evaluation_id,
+                    score: choice.chosen,
+                })
+                .collect();
+
+            let discarded_practice_id = match (kept.practice_id, discarded.practice_id) {
+                (None, source_practice) => {
+                    kept.practice_id = source_practice;
+                    None
+                }
+                (Some(target_practice), Some(source_practice))
+                    if target_practice != source_practice =>
+                {
+                    Some(source_practice)
+                }
+                _ => None,
+            };
+
+            report.combined_enrollments.push(CombinedEnrollment {
+                course_id: kept.course_id,
+                enrollment_id: kept.id,
+                discarded_enrollment_id: discarded.id,
+                practice_id: kept.practice_id,
+                discarded_practice_id,
+                scores,
+            });
+
+            combined.push(kept);
+        }
+
+        Self {
+            report,
+            combined,
+            target_rut: None,
+        }
+    }
+
+    fn combine_scores(source: &[StudentScore], target: &[StudentScore]) -> Vec<ScoreChoice> {
+        let find = |scores: &[StudentScore], evaluation_id: Uuid| {
+            scores.iter().find(|s| s.evaluation_id == evaluation_id).map(|s| s.score)
+        };
+
+        let mut evaluation_ids = Vec::new();
+
+        for score in target.iter().chain(source) {
+            if !evaluation_ids.contains(&score.evaluation_id) {
+                evaluation_ids.push(score.evaluation_id);
+            }
+        }
+
+        evaluation_ids
+            .into_iter()
+            .filter_map(|evaluation_id| {
+                let source_score = find(source, evaluation_id);
+                let target_score = find(target, evaluation_id);
+
+                let chosen = match (source_score, target_score) {
+                    (Some(source), Some(target)) => source.max(target),
+                    (Some(score), None) | (None, Some(score)) => score,
+                    (None, None) => return None,
+                };
+
+                Some(ScoreChoice {
+                    evaluation_id,
+                    source_score,
+                    target_score,
+                    chosen,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Detalle de lo realizado en una fusión de cuentas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub moved_enrollments: Vec<Uuid>,
+    pub combined_enrollments: Vec<CombinedEnrollment>,
+}
+
+/// Curso en que ambas cuentas estaban inscritas. Se conserva la inscripción
+/// del destino y se elimina la de la cuenta absorbida.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CombinedEnrollment {
+    pub course_id: Uuid,
+    pub enrollment_id: Uuid,
+    pub discarded_enrollment_id: Uuid,
+    pub practice_id: Option<Uuid>,
+    pub discarded_practice_id: Option<Uuid>,
+    pub scores: Vec<ScoreChoice>,
+}
+
+/// Nota elegida para una evaluación a partir de las de ambas inscripciones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreChoice {
+    pub evaluation_id: Uuid,
+    pub source_score: Option<f64>,
+    pub target_score: Option<f64>,
+    pub chosen: f64,
+}
@@ -1,16 +1,27 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_query_sqlx::SqlxBinder;
+use serde::{Deserialize, Serialize};
 use shaku::{Component, Interface};
-use sqlx::{query_as_with as sqlx_query, Postgres};
+use sqlx::{query_as_with as sqlx_query, types::Json, Postgres};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use sea_query::{extension::postgres::PgExpr, Expr, ExprTrait, Order, PostgresQueryBuilder, Query};
+use sea_query::{
+    extension::postgres::PgExpr, Cond, Expr, ExprTrait, Order, PostgresQueryBuilder, Query,
+    SelectStatement,
+};
 
 use crate::{
-    shared::{database::DatabaseConnection, entities::DEFAULT_PAGE_SIZE, errors::AppError},
-    users::entity::{User, Users},
+    shared::{
+        database::DatabaseConnection,
+        entities::{SortDirection, DEFAULT_PAGE_SIZE},
+        errors::AppError,
+    },
+    users::{
+        entity::{EmailChange, EmailChanges, Role, User, UserMerge, Users},
+        Rut,
+    },
 };
 
 #[derive(Component)]
@@ -20,15 +31,116 @@ pub struct PostgresUserRepository {
     database_connection: Arc<dyn DatabaseConnection>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    Name,
+    Email,
+    Rut,
+    #[default]
+    CreatedAt,
+}
+
+impl From<UserSortField> for Users {
+    fn from(field: UserSortField) -> Self {
+        match field {
+            UserSortField::Name => Users::Name,
+            UserSortField::Email => Users::Email,
+            UserSortField::Rut => Users::Rut,
+            UserSortField::CreatedAt => Users::CreatedAt,
+        }
+    }
+}
+
+/// Los usuarios eliminados quedan fuera de toda consulta salvo que se
+/// indique `include_deleted`, p. ej. para mostrar registros históricos.
+/// Solo se pagina cuando `page` es mayor a cero, las búsquedas internas
+/// por `ids` o `ruts` obtienen todos los resultados.
 #[derive(Default, Clone)]
 pub struct UserFilter {
     pub search: Option<String>,
     pub page: u64,
+    pub per_page: Option<u64>,
     pub id: Option<Uuid>,
-    pub rut: Option<String>,
+    pub rut: Option<Rut>,
     pub email: Option<String>,
     pub ids: Option<Vec<Uuid>>,
-    pub ruts: Option<Vec<String>>,
+    pub ruts: Option<Vec<Rut>>,
+    pub roles: Option<Vec<Role>>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub include_deleted: bool,
+    pub only_deleted: bool,
+    pub sort_by: UserSortField,
+    pub direction: SortDirection,
+}
+
+impl UserFilter {
+    pub fn with_deleted(mut self) -> Self {
+        self.include_deleted = true;
+        self
+    }
+
+    pub fn page_size(&self) -> u64 {
+        self.per_page.unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+
+    fn apply(&self, query: &mut SelectStatement) {
+        query
+            .apply_if(self.id, |q, value| {
+                q.and_where(Expr::col(Users::Id).eq(value));
+            })
+            .apply_if(self.rut, |q, value| {
+                q.and_where(Expr::col(Users::Rut).eq(value.to_string()));
+            })
+            .apply_if(self.email.clone(), |q, value| {
+                q.and_where(Expr::col(Users::Email).eq(value));
+            })
+            .apply_if(self.ids.clone(), |q, value| {
+                q.and_where(Expr::col(Users::Id).is_in(value));
+            })
+            .apply_if(self.ruts.clone(), |q, value| {
+                q.and_where(Expr::col(Users::Rut).is_in(value.iter().map(Rut::to_string)));
+            })
+            .apply_if(self.search.clone(), |q, value| {
+                let pattern = format!("%{value}%");
+
+                let mut condition = Expr::col(Users::Name)
+                    .ilike(pattern.clone())
+                    .or(Expr::col(Users::Email).ilike(pattern.clone()))
+                    .or(Expr::col(Users::Rut).ilike(pattern));
+
+                // Un RUT completo se busca también en su forma canónica
+                if let Ok(rut) = Rut::parse(&value) {
+                    condition = condition.or(Expr::col(Users::Rut).eq(rut.to_string()));
+                }
+
+                q.and_where(condition);
+            })
+            .apply_if(self.roles.clone(), |q, roles| {
+                // Usuarios con al menos uno de los roles indicados
+                let condition = roles.iter().fold(Cond::any(), |condition, role| {
+                    condition.add(Expr::cust_with_values(
+                        "CAST(? AS user_role) = ANY(roles)",
+                        [role.as_str()],
+                    ))
+                });
+
+                q.cond_where(condition);
+            })
+            .apply_if(self.created_from, |q, value| {
+                q.and_where(Expr::col(Users::CreatedAt).gte(value));
+            })
+            .apply_if(self.created_to, |q, value| {
+                q.and_where(Expr::col(Users::CreatedAt).lte(value));
+            });
+
+        if self.only_deleted {
+            query.and_where(Expr::col(Users::DeletedAt).is_not_null());
+        } else if !self.include_deleted {
+            query.and_where(Expr::col(Users::DeletedAt).is_null());
+        }
+    }
 }
 
 #[async_trait]
@@ -40,7 +152,10 @@ pub trait UserRepository: Interface {
     async fn save(&self, user: User) -> Result<User, AppError>;
     async fn create_many(&self, users: Vec<User>) -> Result<Vec<User>, AppError>;
     async fn delete(&self, user_id: &Uuid) -> Result<(), AppError>;
+    async fn restore(&self, user_id: &Uuid) -> Result<(), AppError>;
+    async fn purge(&self, user_id: &Uuid) -> Result<(), AppError>;
     async fn count(&self, filter: UserFilter) -> Result<i64, AppError>;
+    async fn merge(&self, merge: &UserMerge) -> Result<(), AppError>;
 }
 
 #[async_trait]
@@ -48,30 +163,19 @@ impl UserRepository for PostgresUserRepository {
     async fn find_many(&self, filter: UserFilter) -> Result<Vec<User>, AppError> {
         let mut query = Query::select().expr(Expr::cust("*")).from(Users::Table).to_owned();
 
-        if let Some(ids) = &filter.ids {
-            query.and_where(Expr::col(Users::Id).is_in(ids.clone()));
-        }
+        filter.apply(&mut query);
 
-        if let Some(ruts) = &filter.ruts {
-            query.and_where(Expr::col(Users::Rut).is_in(ruts.clone()));
-        }
+        query
+            .order_by(Users::from(filter.sort_by), Order::from(filter.direction))
+            .order_by(Users::Id, Order::Asc);
 
-        if let Some(search) = &filter.search {
-            let pattern = format!("%{search}%");
+        if filter.page > 0 {
+            let page_size = filter.page_size();
 
-            query.and_where(
-                Expr::col(Users::Name)
-                    .ilike(pattern.clone())
-                    .or(Expr::col(Users::Email).ilike(pattern.clone()))
-                    .or(Expr::col(Users::Rut).ilike(pattern.clone())),
-            );
+            query.limit(page_size);
+            query.offset((filter.page - 1) * page_size);
         }
 
-        query.order_by(Users::CreatedAt, Order::Desc);
-        query.limit(DEFAULT_PAGE_SIZE);
-
-        query.offset(filter.page.saturating_sub(1) * DEFAULT_PAGE_SIZE);
-
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 
         let results = sqlx_query::<Postgres, User, _>(&sql, values)
@@ -82,19 +186,11 @@ impl UserRepository for PostgresUserRepository {
     }
 
     async fn find_one(&self, filter: UserFilter) -> Result<Option<User>, AppError> {
-        let (sql, values) = Query::select()
-            .expr(Expr::cust("*"))
-            .from(Users::Table)
-            .apply_if(filter.id, |q, value| {
-                q.and_where(Expr::col(Users::Id).eq(value));
-            })
-            .apply_if(filter.rut, |q, value| {
-                q.and_where(Expr::col(Users::Rut).eq(value));
-            })
-            .apply_if(filter.email, |q, value| {
-                q.and_where(Expr::col(Users::Email).eq(value));
-            })
-            .build_sqlx(PostgresQueryBuilder);
+        let mut query = Query::select().expr(Expr::cust("*")).from(Users::Table).to_owned();
+
+        filter.apply(&mut query);
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 
         let user = sqlx_query::<Postgres, User, _>(&sql, values)
             .fetch_optional(self.database_connection.get_pool())
@@ -108,6 +204,7 @@ impl UserRepository for PostgresUserRepository {
             .expr(Expr::cust("*"))
             .from(Users::Table)
             .and_where(Expr::col(Users::Id).eq(*user_id))
+            .and_where(Expr::col(Users::DeletedAt).is_null())
             .build_sqlx(PostgresQueryBuilder);
 
         let user = sqlx_query::<Postgres, User, _>(&sql, values)
@@ -119,15 +216,17 @@ impl UserRepository for PostgresUserRepository {
 
     async fn save(&self, user: User) -> Result<User, AppError> {
         let upsert_query = r#"
-            INSERT INTO users (id, rut, name, email, password, roles, created_at, deleted_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO users (id, rut, name, email, password, roles, created_at, deleted_at, must_change_password, password_changed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             ON CONFLICT (id) 
             DO UPDATE SET 
                 rut = EXCLUDED.rut,
                 name = EXCLUDED.name,
                 email = EXCLUDED.email,
                 password = EXCLUDED.password,
-                roles = EXCLUDED.roles
+                roles = EXCLUDED.roles,
+                must_change_password = EXCLUDED.must_change_password,
+                password_changed_at = EXCLUDED.password_changed_at
             WHERE users.deleted_at IS NULL
             RETURNING *
         "#;
@@ -141,6 +240,8 @@ impl UserRepository for PostgresUserRepository {
             .bind(user.roles)
             .bind(user.created_at)
             .bind(user.deleted_at)
+            .bind(user.must_change_password)
+            .bind(user.password_changed_at)
             .fetch_one(self.database_connection.get_pool())
             .await?;
 
@@ -157,7 +258,7 @@ impl UserRepository for PostgresUserRepository {
 
         for _ in &users {
             query_values.push(format!(
-                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
                 arg_index,     // id
                 arg_index + 1, // rut
                 arg_index + 2, // name
@@ -166,14 +267,16 @@ impl UserRepository for PostgresUserRepository {
                 arg_index + 5, // roles
                 arg_index + 6, // created_at
                 arg_index + 7, // deleted_at
+                arg_index + 8, // must_change_password
+                arg_index + 9, // password_changed_at
             ));
 
-            arg_index += 8;
+            arg_index += 10;
         }
 
         let query = format!(
             r#" 
-                INSERT INTO users (id, rut, name, email, password, roles, created_at, deleted_at)
+                INSERT INTO users (id, rut, name, email, password, roles, created_at, deleted_at, must_change_password, password_changed_at)
                 VALUES {}
                 ON CONFLICT (id) DO NOTHING
                 RETURNING *
@@ -192,7 +295,9 @@ impl UserRepository for PostgresUserRepository {
                 .bind(&user.password)
                 .bind(&user.roles)
                 .bind(user.created_at)
-                .bind(user.deleted_at);
+                .bind(user.deleted_at)
+                .bind(user.must_change_password)
+                .bind(user.password_changed_at);
         }
 
         let results = sqlx_query.fetch_all(self.database_connection.get_pool()).await?;
@@ -205,6 +310,46 @@ impl UserRepository for PostgresUserRepository {
             .table(Users::Table)
             .value(Users::DeletedAt, Utc::now())
             .and_where(Expr::col(Users::Id).eq(*user_id))
+            .and_where(Expr::col(Users::DeletedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(self.database_connection.get_pool())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(Users::Table)
+            .value(Users::DeletedAt, Expr::cust("NULL"))
+            .and_where(Expr::col(Users::Id).eq(*user_id))
+            .and_where(Expr::col(Users::PurgedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(self.database_connection.get_pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Reemplaza los datos personales por valores derivados del id, de modo que
+    /// las inscripciones y prácticas asociadas se conservan sin identificar a la persona.
+    async fn purge(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let anonymous_id = user_id.simple();
+
+        let (sql, values) = Query::update()
+            .table(Users::Table)
+            .value(Users::Rut, format!("purged-{anonymous_id}"))
+            .value(Users::Name, "Usuario eliminado")
+            .value(Users::Email, format!("{anonymous_id}@purged.invalid"))
+            .value(Users::Password, "")
+            .value(Users::MustChangePassword, false)
+            .value(Users::PurgedAt, Utc::now())
+            .and_where(Expr::col(Users::Id).eq(*user_id))
+            .and_where(Expr::col(Users::DeletedAt).is_not_null())
             .build_sqlx(PostgresQueryBuilder);
 
         sqlx::query_with(&sql, values)
@@ -220,17 +365,7 @@ impl UserRepository for PostgresUserRepository {
             .from(Users::Table)
             .to_owned();
 
-        if let Some(search) = filter.search {
-            let search_pattern = format!("%{search}%");
-            query = query
-                .and_where(
-                    Expr::col(Users::Name)
-                        .ilike(&search_pattern)
-                        .or(Expr::col(Users::Email).ilike(&search_pattern))
-                        .or(Expr::col(Users::Rut).ilike(&search_pattern)),
-                )
-                .to_owned();
-        }
+        filter.apply(&mut query);
 
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
 
@@ -240,4 +375,178 @@ impl UserRepository for PostgresUserRepository {
 
         Ok(count.0)
     }
+
+    /// Aplica la fusión en una sola transacción: descarta las inscripciones
+    /// duplicadas (y sus prácticas reemplazadas), traspasa el resto junto con
+    /// los cursos a cargo, registra la fusión, elimina la cuenta absorbida y
+    /// normaliza el RUT del destino.
+    async fn merge(&self, merge: &UserMerge) -> Result<(), AppError> {
+        let report = &merge.report;
+        let discarded = report
+            .combined_enrollments
+            .iter()
+            .map(|combined| combined.discarded_enrollment_id)
+            .collect::<Vec<_>>();
+        let discarded_practices = report
+            .combined_enrollments
+            .iter()
+            .filter_map(|combined| combined.discarded_practice_id)
+            .collect::<Vec<_>>();
+
+        let mut tx = self.database_connection.get_pool().begin().await?;
+
+        sqlx::query("DELETE FROM enrollments WHERE id = ANY($1)")
+            .bind(&discarded)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM practices WHERE id = ANY($1)")
+            .bind(&discarded_practices)
+            .execute(&mut *tx)
+            .await?;
+
+        for enrollment in &merge.combined {
+            sqlx::query(
+                "UPDATE enrollments SET practice_id = $2, student_scores = $3 WHERE id = $1",
+            )
+            .bind(enrollment.id)
+            .bind(enrollment.practice_id)
+            .bind(&enrollment.student_scores)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let reassignments = [
+            "UPDATE enrollments SET student_id = $2 WHERE student_id = $1",
+            "UPDATE courses SET teacher_id = $2 WHERE teacher_id = $1",
+            "UPDATE courses SET coordinator_id = $2 WHERE coordinator_id = $1",
+            // En cursos donde ambas cuentas son del equipo prevalece el rol que
+            // se guarda en el curso (profesor a cargo o coordinador)
+            r#"DELETE FROM course_staff t USING course_staff s
+               WHERE t.user_id = $2 AND s.user_id = $1 AND t.course_id = s.course_id
+               AND s.role IN ('lead', 'coordinator')"#,
+            r#"DELETE FROM course_staff s USING course_staff t
+               WHERE s.user_id = $1 AND t.user_id = $2 AND s.course_id = t.course_id"#,
+            "UPDATE course_staff SET user_id = $2 WHERE user_id = $1",
+            // Fusiones previas hacia la cuenta absorbida pasan a apuntar al destino
+            "UPDATE user_merges SET target_id = $2 WHERE target_id = $1",
+        ];
+
+        for query in reassignments {
+            sqlx::query(query)
+                .bind(report.source_id)
+                .bind(report.target_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let insert_query = r#"
+            INSERT INTO user_merges (id, source_id, target_id, report, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+        "#;
+
+        sqlx::query(insert_query)
+            .bind(Uuid::new_v4())
+            .bind(report.source_id)
+            .bind(report.target_id)
+            .bind(Json(report))
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await?;
+
+        let (sql, values) = Query::update()
+            .table(Users::Table)
+            .value(Users::DeletedAt, Utc::now())
+            .and_where(Expr::col(Users::Id).eq(report.source_id))
+            .and_where(Expr::col(Users::DeletedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+
+        // Con la cuenta absorbida ya eliminada, el RUT canónico no choca con
+        // el suyo en el índice único de usuarios activos
+        if let Some(rut) = &merge.target_rut {
+            let (sql, values) = Query::update()
+                .table(Users::Table)
+                .value(Users::Rut, rut)
+                .and_where(Expr::col(Users::Id).eq(report.target_id))
+                .build_sqlx(PostgresQueryBuilder);
+
+            sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = EmailChangeRepository)]
+pub struct PostgresEmailChangeRepository {
+    #[shaku(inject)]
+    database_connection: Arc<dyn DatabaseConnection>,
+}
+
+#[async_trait]
+pub trait EmailChangeRepository: Interface {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<EmailChange>, AppError>;
+    async fn save(&self, change: EmailChange) -> Result<EmailChange, AppError>;
+
+    async fn invalidate_all(&self, user_id: &Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl EmailChangeRepository for PostgresEmailChangeRepository {
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<EmailChange>, AppError> {
+        let (sql, values) = Query::select()
+            .expr(Expr::cust("*"))
+            .from(EmailChanges::Table)
+            .and_where(Expr::col(EmailChanges::Id).eq(*id))
+            .build_sqlx(PostgresQueryBuilder);
+
+        let change = sqlx_query::<Postgres, EmailChange, _>(&sql, values)
+            .fetch_optional(self.database_connection.get_pool())
+            .await?;
+
+        Ok(change)
+    }
+
+    async fn save(&self, change: EmailChange) -> Result<EmailChange, AppError> {
+        let query = r#"
+            INSERT INTO email_change_requests (id, user_id, new_email, token_hash, expires_at, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO UPDATE SET
+                used_at = EXCLUDED.used_at
+            RETURNING *
+        "#;
+
+        let result = sqlx::query_as::<_, EmailChange>(query)
+            .bind(change.id)
+            .bind(change.user_id)
+            .bind(&change.new_email)
+            .bind(&change.token_hash)
+            .bind(change.expires_at)
+            .bind(change.used_at)
+            .bind(change.created_at)
+            .fetch_one(self.database_connection.get_pool())
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn invalidate_all(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let (sql, values) = Query::update()
+            .table(EmailChanges::Table)
+            .value(EmailChanges::UsedAt, Utc::now())
+            .and_where(Expr::col(EmailChanges::UserId).eq(*user_id))
+            .and_where(Expr::col(EmailChanges::UsedAt).is_null())
+            .build_sqlx(PostgresQueryBuilder);
+
+        sqlx::query_with(&sql, values)
+            .execute(self.database_connection.get_pool())
+            .await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+use crate::shared::errors::{AppError, Input};
+
+/// RUT chileno. Acepta las formas habituales (`12.345.678-5`, `12345678-5`,
+/// `123456785`, con espacios o `k` minúscula) y se almacena en su forma
+/// canónica: número sin puntos ni ceros a la izquierda, guion y dígito
+/// verificador en mayúscula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rut {
+    number: u32,
+    dv: char,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RutError {
+    #[error("El RUT no tiene un formato válido")]
+    Format,
+
+    #[error("El dígito verificador del RUT es incorrecto")]
+    VerificationDigit,
+}
+
+/// Mayor número de RUT representable con 8 dígitos.
+const MAX_RUT_NUMBER: u32 = 99_999_999;
+
+impl Rut {
+    pub fn parse(value: &str) -> Result<Self, RutError> {
+        let cleaned = value
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '.')
+            .map(|c| c.to_ascii_uppercase())
+            .collect::<String>();
+
+        let (number_part, dv_part) = match cleaned.split_once('-') {
+            Some(parts) => parts,
+            None if cleaned.len() > 1 => cleaned.split_at(cleaned.len() - 1),
+            None => return Err(RutError::Format),
+        };
+
+        if number_part.is_empty() || !number_part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(RutError::Format);
+        }
+
+        let number = number_part
+            .trim_start_matches('0')
+            .parse::<u32>()
+            .ok()
+            .filter(|number| (1..=MAX_RUT_NUMBER).contains(number))
+            .ok_or(RutError::Format)?;
+
+        let mut dv_chars = dv_part.chars();
+        let (Some(dv), None) = (dv_chars.next(), dv_chars.next()) else {
+            return Err(RutError::Format);
+        };
+
+        if dv != Self::compute_dv(number) {
+            return Err(RutError::VerificationDigit);
+        }
+
+        Ok(Rut { number, dv })
+    }
+
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn dv(&self) -> char {
+        self.dv
+    }
+
+    /// Forma para mostrar, con separador de miles: `12.345.678-5`.
+    pub fn formatted(&self) -> String {
+        let digits = self.number.to_string();
+        let mut grouped = String::with_capacity(digits.len() + 2);
+
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push('.');
+            }
+
+            grouped.push(digit);
+        }
+
+        format!("{grouped}-{}", self.dv)
+    }
+
+    /// Dígito verificador según el algoritmo módulo 11.
+    fn compute_dv(mut number: u32) -> char {
+        let mut sum = 0;
+        let mut multiplier = 2;
+
+        while number > 0 {
+            sum += (number % 10) * multiplier;
+            number /= 10;
+            multiplier = if multiplier == 7 { 2 } else { multiplier + 1 };
+        }
+
+        match 11 - (sum % 11) {
+            11 => '0',
+            10 => 'K',
+            n => char::from_digit(n, 10).unwrap_or('0'),
+        }
+    }
+}
+
+impl fmt::Display for Rut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.number, self.dv)
+    }
+}
+
+impl FromStr for Rut {
+    type Err = RutError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Rut::parse(value)
+    }
+}
+
+impl TryFrom<String> for Rut {
+    type Error = RutError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Rut::parse(&value)
+    }
+}
+
+impl From<Rut> for String {
+    fn from(rut: Rut) -> Self {
+        rut.to_string()
+    }
+}
+
+impl From<RutError> for AppError {
+    fn from(error: RutError) -> Self {
+        AppError::InvalidInput(Input {
+            field: "rut".to_string(),
+            message: error.to_string(),
+            ..Input::default()
+        })
+    }
+}
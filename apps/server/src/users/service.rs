@@ -4,18 +4,29 @@ use crate::shared::services::{
 };
 
 use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::json;
 use shaku::{Component, Interface};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
+    audit::{AuditContext, AuditEntry, AuditService},
+    auth::{AuthService, LoginAttempt, LoginAttemptRepository, TwoFactorRepository},
+    config::AuthConfig,
+    course_filter,
+    courses::{CourseFilter, CourseRepository},
+    enrollment_filter,
+    enrollments::{EnrollmentFilter, EnrollmentRepository},
     shared::{
-        entities::{Pagination, DEFAULT_PAGE_SIZE},
+        entities::Pagination,
         errors::{AppError, Input},
     },
     user_filter,
     users::{
-        dtos::from_string_vec_roles, CreateUserDto, UpdateUserDto, User, UserFilter, UserRepository,
+        dtos::from_string_vec_roles, CreateUserDto, EmailChange, EmailChangeRepository,
+        MergeReport, Rut, UpdateProfileDto, UpdateUserDto, User, UserFilter, UserMerge,
+        UserRepository,
     },
 };
 
@@ -30,49 +41,87 @@ pub struct UserServiceImpl {
 
     #[shaku(inject)]
     event_queue: Arc<dyn EventQueue>,
-}
 
-#[async_trait]
-pub trait UserService: Interface {
-    async fn get_all(&self, filter: UserFilter) -> Result<Pagination<User>, AppError>;
+    #[shaku(inject)]
+    login_attempts: Arc<dyn LoginAttemptRepository>,
+
+    #[shaku(inject)]
+    auth: Arc<dyn AuthService>,
+
+    #[shaku(inject)]
+    audit_log: Arc<dyn AuditService>,
+
+    #[shaku(inject)]
+    two_factor: Arc<dyn TwoFactorRepository>,
 
-    async fn create(&self, user: CreateUserDto) -> Result<User, AppError>;
-    async fn update(&self, id: Uuid, user: UpdateUserDto) -> Result<User, AppError>;
-    async fn remove(&self, id: Uuid) -> Result<(), AppError>;
+    #[shaku(inject)]
+    email_changes: Arc<dyn EmailChangeRepository>,
+
+    #[shaku(inject)]
+    enrollments: Arc<dyn EnrollmentRepository>,
+
+    #[shaku(inject)]
+    courses: Arc<dyn CourseRepository>,
+
+    config: AuthConfig,
+}
+
+impl From<AuthConfig> for UserServiceImplParameters {
+    fn from(config: AuthConfig) -> Self {
+        UserServiceImplParameters { config }
+    }
 }
 
 #[async_trait]
-impl UserService for UserServiceImpl {
-    async fn get_all(&self, filter: UserFilter) -> Result<Pagination<User>, AppError> {
-        let results = self.users.find_many(filter.clone()).await?;
-        let total = self.users.count(filter.clone()).await?;
+pub trait UserService: Interface {
+    async fn get_all(&self, filter: UserFilter) -> Result<Pagination<User>, AppError>;
 
-        let total_pages = (total as f64 / DEFAULT_PAGE_SIZE as f64).ceil() as u64;
+    async fn create(&self, user: CreateUserDto, audit: &AuditContext) -> Result<User, AppError>;
+    async fn update(
+        &self,
+        id: Uuid,
+        user: UpdateUserDto,
+        audit: &AuditContext,
+    ) -> Result<User, AppError>;
+    async fn update_profile(
+        &self,
+        user: User,
+        session_id: &Uuid,
+        input: UpdateProfileDto,
+        audit: &AuditContext,
+    ) -> Result<User, AppError>;
+    async fn confirm_email_change(
+        &self,
+        token: &str,
+        audit: &AuditContext,
+    ) -> Result<User, AppError>;
+    async fn remove(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError>;
+    async fn restore(&self, id: Uuid, audit: &AuditContext) -> Result<User, AppError>;
+    async fn purge(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError>;
 
-        Ok(Pagination {
-            total_pages,
-            items: results,
-            current_page: filter.page,
-            has_previous: filter.page > 1,
-            has_next: filter.page < total_pages,
-        })
-    }
+    async fn unlock(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError>;
+    async fn revoke_sessions(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError>;
+    async fn merge(
+        &self,
+        target_id: Uuid,
+        source_id: Uuid,
+        audit: &AuditContext,
+    ) -> Result<MergeReport, AppError>;
+}
 
-    async fn create(&self, mut input: CreateUserDto) -> Result<User, AppError> {
+impl UserServiceImpl {
+    /// El RUT y el email solo deben ser únicos entre los usuarios no eliminados.
+    async fn ensure_available(&self, rut: &Rut, email: &str) -> Result<(), AppError> {
         let (user_by_rut, user_by_email) = tokio::try_join!(
-            self.users.find_one(user_filter! {
-                rut: input.rut.clone(),
-            }),
-            self.users.find_one(user_filter! {
-                email: input.email.clone()
-            })
+            self.users.find_one(user_filter! { rut: *rut }),
+            self.users.find_one(user_filter! { email: email.to_string() })
         )?;
 
         if user_by_rut.is_some() {
             return Err(AppError::Conflict(Input {
                 field: "rut".to_string(),
                 message: "Ya existe un usuario con este RUT".to_string(),
-                value: input.rut.clone(),
+                value: rut.to_string(),
             }));
         }
 
@@ -80,15 +129,153 @@ impl UserService for UserServiceImpl {
             return Err(AppError::Conflict(Input {
                 field: "email".to_string(),
                 message: "Ya existe un usuario con este email".to_string(),
-                value: input.email.clone(),
+                value: email.to_string(),
             }));
         }
 
+        Ok(())
+    }
+
+    /// La cuenta que se conserva asume los roles de la absorbida en los equipos
+    /// docentes, por lo que debe tener los roles de usuario que estos exigen.
+    async fn ensure_staff_roles(&self, target: &User, source_id: &Uuid) -> Result<(), AppError> {
+        let courses = self.courses.find_many(course_filter! { staff_id: *source_id }).await?;
+        let course_ids = courses.iter().map(|course| course.id).collect::<Vec<_>>();
+
+        let missing_role = self
+            .courses
+            .find_staff(&course_ids)
+            .await?
+            .into_iter()
+            .filter(|member| member.user_id == *source_id)
+            .map(|member| member.role.required_role())
+            .find(|role| !target.roles.contains(role));
+
+        if let Some(role) = missing_role {
+            let message = format!(
+                "La cuenta de destino debe tener el rol {} para asumir los cursos de la cuenta absorbida",
+                role.as_str()
+            );
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_email_available(&self, email: &str, user_id: &Uuid) -> Result<(), AppError> {
+        match self.users.find_one(user_filter! { email: email.to_string() }).await? {
+            Some(user) if user.id != *user_id => Err(AppError::Conflict(Input {
+                field: "email".to_string(),
+                message: "Ya existe un usuario con este email".to_string(),
+                value: email.to_string(),
+            })),
+            _ => Ok(()),
+        }
+    }
+
+    /// Registra un cambio de email pendiente, que se aplica al confirmarse desde
+    /// la nueva dirección. Se avisa también a la dirección actual.
+    async fn request_email_change(
+        &self,
+        user: &User,
+        email: String,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        if email == user.email {
+            return Ok(());
+        }
+
+        self.ensure_email_available(&email, &user.id).await?;
+        self.email_changes.invalidate_all(&user.id).await?;
+
+        let secret = Uuid::new_v4().simple().to_string();
+        let now = Utc::now();
+
+        let change = self
+            .email_changes
+            .save(EmailChange {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                new_email: email,
+                token_hash: self.hasher.hash(&secret)?,
+                expires_at: now + Duration::milliseconds(self.config.email_change_exp_ms as i64),
+                used_at: None,
+                created_at: now,
+            })
+            .await?;
+
+        let entry = AuditEntry::new(audit, "user.email_change_request", "user", Some(user.id))
+            .after(&json!({ "email": change.new_email }));
+        self.audit_log.record(entry).await?;
+
+        let event_data = (
+            user.name.clone(),
+            user.email.clone(),
+            change.new_email,
+            format!("{}.{secret}", change.id),
+        );
+
+        self.event_queue.publish(Event::EmailChangeRequested(event_data)).await;
+
+        Ok(())
+    }
+
+    async fn find_email_change(&self, token: &str) -> Result<EmailChange, AppError> {
+        let invalid_token = || {
+            AppError::InvalidOperation("El enlace de confirmación es inválido o expiró".to_string())
+        };
+
+        let Some((id, secret)) = token.split_once('.') else {
+            return Err(invalid_token());
+        };
+
+        let id = Uuid::parse_str(id).map_err(|_| invalid_token())?;
+
+        match self.email_changes.find_by_id(&id).await? {
+            Some(change) if !change.is_used() && !change.is_expired() => {
+                if !self.hasher.verify(secret, &change.token_hash)? {
+                    return Err(invalid_token());
+                }
+
+                Ok(change)
+            }
+            _ => Err(invalid_token()),
+        }
+    }
+
+    async fn find_deleted(&self, id: Uuid) -> Result<User, AppError> {
+        match self.users.find_one(user_filter! { id }.with_deleted()).await? {
+            Some(user) if user.is_deleted() => Ok(user),
+            Some(_) => Err(AppError::InvalidOperation("El usuario no está eliminado".to_string())),
+            None => Err(AppError::ResourceNotFound(id)),
+        }
+    }
+}
+
+#[async_trait]
+impl UserService for UserServiceImpl {
+    async fn get_all(&self, filter: UserFilter) -> Result<Pagination<User>, AppError> {
+        let results = self.users.find_many(filter.clone()).await?;
+        let total = self.users.count(filter.clone()).await?;
+
+        Ok(Pagination::new(results, filter.page, filter.page_size(), total))
+    }
+
+    async fn create(
+        &self,
+        mut input: CreateUserDto,
+        audit: &AuditContext,
+    ) -> Result<User, AppError> {
+        self.ensure_available(&input.rut, &input.email).await?;
+
         let unhashed_password = input.password.clone();
 
         input.password = self.hasher.hash(&input.password)?;
         let user = self.users.save(User::try_from(input.clone())?).await?;
 
+        let entry = AuditEntry::new(audit, "user.create", "user", Some(user.id)).after(&user);
+        self.audit_log.record(entry).await?;
+
         let event_data = (user.name.clone(), user.email.clone(), unhashed_password.clone());
 
         self.event_queue.publish(Event::UserCreated(event_data)).await;
@@ -96,42 +283,281 @@ impl UserService for UserServiceImpl {
         Ok(user)
     }
 
-    async fn update(&self, id: Uuid, input: UpdateUserDto) -> Result<User, AppError> {
+    async fn update(
+        &self,
+        id: Uuid,
+        input: UpdateUserDto,
+        audit: &AuditContext,
+    ) -> Result<User, AppError> {
         let Some(mut user) = self.users.find_by_id(&id).await? else {
             return Err(AppError::ResourceNotFound(id));
         };
 
-        if let Some(e) = input.email {
-            let email_exists =
-                self.users.find_one(user_filter! { email: e.clone() }).await?.is_some();
+        let before = user.clone();
+
+        if let Some(e) = &input.email {
+            self.ensure_email_available(e, &user.id).await?;
+        }
+
+        // Un cambio de contraseña o de roles cierra las sesiones abiertas del usuario
+        let mut revoke_sessions = false;
+
+        if let Some(p) = input.password {
+            user.password = self.hasher.hash(&p)?;
+            user.password_changed_at = Some(Utc::now());
+            revoke_sessions = true;
+        }
+
+        if let Some(roles) = input.roles {
+            let roles = from_string_vec_roles(roles)?;
+
+            revoke_sessions |= roles.len() != user.roles.len()
+                || roles.iter().any(|role| !user.roles.contains(role));
+
+            user.roles = roles;
+        }
+
+        let user = self.users.save(user).await?;
+
+        let entry = AuditEntry::new(audit, "user.update", "user", Some(user.id))
+            .diff(Some(&before), Some(&user));
+        self.audit_log.record(entry).await?;
+
+        if let Some(email) = input.email {
+            self.request_email_change(&user, email, audit).await?;
+        }
+
+        if revoke_sessions {
+            self.auth.revoke_sessions(&user.id).await?;
+        }
+
+        Ok(user)
+    }
+
+    /// Actualiza la cuenta del propio usuario. Un cambio de contraseña cierra
+    /// sus demás sesiones, manteniendo abierta la sesión desde la que se hizo.
+    async fn update_profile(
+        &self,
+        mut user: User,
+        session_id: &Uuid,
+        input: UpdateProfileDto,
+        audit: &AuditContext,
+    ) -> Result<User, AppError> {
+        if input.changes_credentials() {
+            let current_password = input.current_password.as_deref().unwrap_or_default();
 
-            if email_exists && user.email != e {
-                return Err(AppError::Conflict(Input {
-                    field: "email".to_string(),
-                    message: "Ya existe un usuario con este email".to_string(),
-                    value: e.clone(),
+            if !self.hasher.verify(current_password, &user.password)? {
+                return Err(AppError::InvalidInput(Input {
+                    field: "currentPassword".to_string(),
+                    message: "La contraseña actual es incorrecta".to_string(),
+                    ..Input::default()
                 }));
             }
+        }
 
-            user.email = e
+        let before = user.clone();
+
+        if let Some(name) = input.name {
+            user.name = name.trim().to_string();
         }
 
-        if let Some(p) = input.password {
-            user.password = self.hasher.hash(&p)?
+        if let Some(email) = &input.email {
+            self.ensure_email_available(email, &user.id).await?;
         }
 
-        if let Some(roles) = input.roles {
-            user.roles = from_string_vec_roles(roles)?;
+        let password_changed = input.password.is_some();
+
+        if let Some(password) = input.password {
+            user.password = self.hasher.hash(&password)?;
+            user.must_change_password = false;
+            user.password_changed_at = Some(Utc::now());
+        }
+
+        let user = self.users.save(user).await?;
+
+        let entry = AuditEntry::new(audit, "user.profile_update", "user", Some(user.id))
+            .diff(Some(&before), Some(&user));
+        self.audit_log.record(entry).await?;
+
+        if let Some(email) = input.email {
+            self.request_email_change(&user, email, audit).await?;
         }
 
-        self.users.save(user).await
+        if password_changed {
+            for session in self.auth.list_sessions(&user.id).await? {
+                if session.id != *session_id {
+                    self.auth.revoke_session(&user.id, &session.id).await?;
+                }
+            }
+        }
+
+        Ok(user)
+    }
+
+    /// Aplica un cambio de email pendiente. Se vuelve a comprobar que la
+    /// dirección siga disponible, pues pudo ocuparse tras la solicitud.
+    async fn confirm_email_change(
+        &self,
+        token: &str,
+        audit: &AuditContext,
+    ) -> Result<User, AppError> {
+        let mut change = self.find_email_change(token).await?;
+
+        let Some(mut user) = self.users.find_by_id(&change.user_id).await? else {
+            return Err(AppError::ResourceNotFound(change.user_id));
+        };
+
+        self.ensure_email_available(&change.new_email, &user.id).await?;
+
+        change.used_at = Some(Utc::now());
+        let change = self.email_changes.save(change).await?;
+
+        let before = user.clone();
+        user.email = change.new_email;
+
+        let user = self.users.save(user).await?;
+
+        let entry = AuditEntry::new(audit, "user.email_change", "user", Some(user.id))
+            .diff(Some(&before), Some(&user));
+        self.audit_log.record(entry).await?;
+
+        Ok(user)
+    }
+
+    async fn remove(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError> {
+        let Some(user) = self.users.find_by_id(&id).await? else {
+            return Err(AppError::ResourceNotFound(id));
+        };
+
+        self.users.delete(&id).await?;
+        self.auth.revoke_sessions(&id).await?;
+
+        let entry = AuditEntry::new(audit, "user.delete", "user", Some(id)).before(&user);
+        self.audit_log.record(entry).await
     }
 
-    async fn remove(&self, id: Uuid) -> Result<(), AppError> {
+    /// Reactiva un usuario eliminado, siempre que sus datos no hayan sido
+    /// purgados y su RUT y email no pertenezcan ahora a otra cuenta.
+    async fn restore(&self, id: Uuid, audit: &AuditContext) -> Result<User, AppError> {
+        let deleted = self.find_deleted(id).await?;
+
+        if deleted.is_purged() {
+            let message = "Los datos del usuario fueron purgados, no es posible restaurarlo";
+            return Err(AppError::InvalidOperation(message.to_string()));
+        }
+
+        self.ensure_available(&deleted.rut.parse::<Rut>()?, &deleted.email).await?;
+        self.users.restore(&id).await?;
+
+        let Some(user) = self.users.find_by_id(&id).await? else {
+            return Err(AppError::ResourceNotFound(id));
+        };
+
+        let entry = AuditEntry::new(audit, "user.restore", "user", Some(id))
+            .diff(Some(&deleted), Some(&user));
+        self.audit_log.record(entry).await?;
+
+        Ok(user)
+    }
+
+    /// Anonimiza de forma irreversible los datos personales de un usuario
+    /// eliminado. Sus inscripciones y prácticas se conservan.
+    async fn purge(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError> {
+        let user = self.find_deleted(id).await?;
+
+        if user.is_purged() {
+            let message = "Los datos del usuario ya fueron purgados".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        self.users.purge(&id).await?;
+        self.two_factor.delete(&id).await?;
+        self.login_attempts.reset(&LoginAttempt::email_key(&user.email)).await?;
+        self.auth.revoke_sessions(&id).await?;
+
+        // Los registros anteriores pierden los datos personales, y el de la
+        // purga no incluye snapshot para no volver a persistirlos
+        self.audit_log.scrub_user(&id).await?;
+
+        let entry = AuditEntry::new(audit, "user.purge", "user", Some(id));
+        self.audit_log.record(entry).await
+    }
+
+    async fn unlock(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError> {
+        let Some(user) = self.users.find_by_id(&id).await? else {
+            return Err(AppError::ResourceNotFound(id));
+        };
+
+        self.login_attempts.reset(&LoginAttempt::email_key(&user.email)).await?;
+
+        let entry = AuditEntry::new(audit, "user.unlock", "user", Some(id));
+        self.audit_log.record(entry).await
+    }
+
+    async fn revoke_sessions(&self, id: Uuid, audit: &AuditContext) -> Result<(), AppError> {
         if self.users.find_by_id(&id).await?.is_none() {
             return Err(AppError::ResourceNotFound(id));
         }
 
-        self.users.delete(&id).await
+        self.auth.revoke_sessions(&id).await?;
+
+        let entry = AuditEntry::new(audit, "user.revoke_sessions", "user", Some(id));
+        self.audit_log.record(entry).await
+    }
+
+    /// Fusiona una cuenta duplicada en otra: traspasa sus inscripciones,
+    /// combinando las notas de los cursos en que ambas estaban inscritas, y
+    /// elimina la cuenta absorbida.
+    async fn merge(
+        &self,
+        target_id: Uuid,
+        source_id: Uuid,
+        audit: &AuditContext,
+    ) -> Result<MergeReport, AppError> {
+        if target_id == source_id {
+            let message = "No es posible fusionar una cuenta consigo misma".to_string();
+            return Err(AppError::InvalidOperation(message));
+        }
+
+        let (target, source) =
+            tokio::try_join!(self.users.find_by_id(&target_id), self.users.find_by_id(&source_id))?;
+
+        let Some(target) = target else {
+            return Err(AppError::ResourceNotFound(target_id));
+        };
+
+        if source.is_none() {
+            return Err(AppError::ResourceNotFound(source_id));
+        }
+
+        self.ensure_staff_roles(&target, &source_id).await?;
+
+        let (source_enrollments, target_enrollments) = tokio::try_join!(
+            self.enrollments.find_many(enrollment_filter! { student_id: source_id }),
+            self.enrollments.find_many(enrollment_filter! { student_id: target_id })
+        )?;
+
+        // El RUT de la cuenta que se conserva queda en forma canónica, aunque la
+        // migración no haya podido normalizarlo por el duplicado
+        let target_rut = target
+            .rut
+            .parse::<Rut>()
+            .ok()
+            .map(|rut| rut.to_string())
+            .filter(|rut| *rut != target.rut);
+
+        let merge = UserMerge {
+            target_rut,
+            ..UserMerge::plan(source_id, target_id, source_enrollments, target_enrollments)
+        };
+
+        self.users.merge(&merge).await?;
+        self.auth.revoke_sessions(&source_id).await?;
+
+        let entry =
+            AuditEntry::new(audit, "user.merge", "user", Some(target_id)).after(&merge.report);
+        self.audit_log.record(entry).await?;
+
+        Ok(merge.report)
     }
 }
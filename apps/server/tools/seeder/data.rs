@@ -15,6 +15,9 @@ pub fn teachers() -> Vec<User> {
         roles: vec![Role::Teacher],
         created_at: chrono::Utc::now(),
         deleted_at: None,
+        purged_at: None,
+        must_change_password: false,
+        password_changed_at: None,
         password: hash("!abc1234ABC.", 8).unwrap(),
     }]
 }
@@ -28,6 +31,9 @@ pub fn administrators() -> Vec<User> {
         roles: vec![Role::Administrator],
         created_at: chrono::Utc::now(),
         deleted_at: None,
+        purged_at: None,
+        must_change_password: false,
+        password_changed_at: None,
         password: hash("!abc1234ABC.", 8).unwrap(),
     }]
 }
@@ -35,12 +41,15 @@ pub fn administrators() -> Vec<User> {
 pub fn secretaries() -> Vec<User> {
     vec![User {
         id: Uuid::new_v4(),
-        rut: "12345678-9".to_string(),
+        rut: "12345678-5".to_string(),
         name: "Secretaria".to_string(),
         email: "secretary@uct.cl".to_string(),
         roles: vec![Role::Secretary],
         created_at: chrono::Utc::now(),
         deleted_at: None,
+        purged_at: None,
+        must_change_password: false,
+        password_changed_at: None,
         password: hash("!abc1234ABC.", 8).unwrap(),
     }]
 }
@@ -76,6 +85,8 @@ pub fn info_1164(teachers: &[User]) -> Course {
         teacher_id: teachers.first().unwrap().id,
         evaluations: evaluation_schema.clone(),
         course_status: CourseStatus::Active,
+        coordinator_id: None,
+        period_id: None,
     }
 }
 
@@ -90,5 +101,7 @@ pub fn info_1198(teachers: &[User]) -> Course {
         teacher_id: teachers.first().unwrap().id,
         evaluations: evaluation_schema,
         course_status: CourseStatus::Active,
+        coordinator_id: None,
+        period_id: None,
     }
 }
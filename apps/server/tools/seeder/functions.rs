@@ -41,6 +41,16 @@ pub async fn create_course(pool: &Pool<Postgres>, course: Course) {
         .execute(pool)
         .await
         .unwrap();
+
+    for member in course.assigned_staff() {
+        sqlx::query("INSERT INTO course_staff (course_id, user_id, role) VALUES ($1, $2, $3)")
+            .bind(member.course_id)
+            .bind(member.user_id)
+            .bind(member.role)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
 }
 
 pub async fn create_enrollments(pool: &Pool<Postgres>, students: Vec<User>, course: Course) {
@@ -57,6 +57,9 @@ async fn main() -> Result<(), sqlx::Error> {
             roles: vec![Role::Student],
             created_at: chrono::Utc::now(),
             deleted_at: None,
+            purged_at: None,
+            must_change_password: false,
+            password_changed_at: None,
             password: hash("!abc1234ABC.", 8).unwrap(),
         };
 
@@ -80,6 +83,9 @@ async fn main() -> Result<(), sqlx::Error> {
             roles: vec![Role::Student],
             created_at: chrono::Utc::now(),
             deleted_at: None,
+            purged_at: None,
+            must_change_password: false,
+            password_changed_at: None,
             password: hash("!abc1234ABC.", 8).unwrap(),
         };
 
@@ -0,0 +1,133 @@
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use serde_json::json;
+
+use crate::{
+    authorization::utils::TestAccount,
+    extract_resource_id, init_test_app, init_test_app_with, test_database,
+    users::utils::{UserBuilder, create_user},
+};
+
+pub mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn test_user_mutations_are_audited() {
+    let app = init_test_app().await;
+    let admin_id = session_user_id(&app).await;
+
+    let user = create_user(&app, UserBuilder::new().with_roles(vec!["student"]).build()).await;
+    let user_id = extract_resource_id(&user);
+    let email = user["email"].as_str().unwrap().to_string();
+
+    app.put(&format!("/users/{user_id}"))
+        .add_header(
+            HeaderName::from_static("x-forwarded-for"),
+            HeaderValue::from_static("203.0.113.7"),
+        )
+        .json(&json!({ "roles": ["teacher"] }))
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.delete(&format!("/users/{user_id}")).await.assert_status(StatusCode::OK);
+
+    let entries = audit_entries(&app, &format!("entityId={user_id}")).await;
+    let actions = entries.iter().map(|e| e["action"].as_str().unwrap()).collect::<Vec<_>>();
+
+    assert_eq!(actions, vec!["user.delete", "user.update", "user.create"]);
+    assert!(entries.iter().all(|e| e["actorId"].as_str() == Some(admin_id.as_str())));
+    assert!(entries.iter().all(|e| e["entityType"] == "user"));
+
+    // La actualización registra sólo los campos modificados, con la IP del cliente
+    let update = &entries[1];
+
+    assert_eq!(update["before"], json!({ "roles": ["student"] }));
+    assert_eq!(update["after"], json!({ "roles": ["teacher"] }));
+    assert_eq!(update["ip"], "203.0.113.7");
+
+    // Las contraseñas nunca quedan en el registro
+    let create = &entries[2];
+
+    assert!(create["before"].is_null());
+    assert!(create["after"].get("password").is_none());
+    assert_eq!(create["after"]["email"].as_str(), Some(email.as_str()));
+}
+
+#[tokio::test]
+async fn test_forwarded_ip_is_ignored_without_trusted_proxy() {
+    let app = init_test_app_with(|config| config.trusted_proxy = false).await;
+
+    let user = create_user(&app, UserBuilder::new().with_roles(vec!["student"]).build()).await;
+    let user_id = extract_resource_id(&user);
+
+    app.put(&format!("/users/{user_id}"))
+        .add_header(HeaderName::from_static("x-real-ip"), HeaderValue::from_static("203.0.113.8"))
+        .add_header(
+            HeaderName::from_static("x-forwarded-for"),
+            HeaderValue::from_static("203.0.113.9"),
+        )
+        .json(&json!({ "roles": ["teacher"] }))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let entries = audit_entries(&app, &format!("action=user.update&entityId={user_id}")).await;
+
+    assert_eq!(entries.len(), 1);
+    assert_ne!(entries[0]["ip"], "203.0.113.8");
+    assert_ne!(entries[0]["ip"], "203.0.113.9");
+}
+
+#[tokio::test]
+async fn test_audit_log_filters() {
+    let app = init_test_app().await;
+    let admin_id = session_user_id(&app).await;
+
+    let user = create_user(&app, UserBuilder::new().with_roles(vec!["student"]).build()).await;
+    let user_id = extract_resource_id(&user);
+
+    app.post(&format!("/users/{user_id}/unlock"))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let entries =
+        audit_entries(&app, &format!("actorId={admin_id}&action=user.unlock&entityType=user"))
+            .await;
+
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|e| e["action"] == "user.unlock"));
+    assert!(entries.iter().any(|e| e["entityId"].as_str() == Some(user_id.as_str())));
+
+    let entries = audit_entries(&app, "from=2000-01-01T00:00:00Z&to=2000-12-31T00:00:00Z").await;
+    assert!(entries.is_empty());
+
+    app.get("/audit?from=2025-12-31T00:00:00Z&to=2025-01-01T00:00:00Z")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    app.get("/audit?page=0").await.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_audit_log_is_restricted_to_administrators() {
+    let mut app = init_test_app().await;
+    let secretary = TestAccount::create(&app, "secretary").await;
+
+    secretary.act(&mut app).await;
+
+    app.get("/audit").await.assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_audit_log_is_append_only() {
+    let app = init_test_app().await;
+
+    create_user(&app, UserBuilder::new().with_roles(vec!["student"]).build()).await;
+
+    let db = test_database().await;
+
+    let update = sqlx::query("UPDATE audit_log SET action = 'tampered'").execute(&db.pool).await;
+    assert!(update.is_err());
+
+    let delete = sqlx::query("DELETE FROM audit_log").execute(&db.pool).await;
+    assert!(delete.is_err());
+}
@@ -0,0 +1,18 @@
+use axum_test::TestServer;
+use serde_json::Value;
+use sword::web::ResponseBody;
+
+pub async fn audit_entries(server: &TestServer, query: &str) -> Vec<Value> {
+    let response = server.get(&format!("/audit?{query}")).await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to query audit log: {}", body.data);
+
+    body.data["entries"].as_array().cloned().unwrap_or_default()
+}
+
+pub async fn session_user_id(server: &TestServer) -> String {
+    let body = server.get("/auth/session").await.json::<ResponseBody>();
+
+    body.data["id"].as_str().map(String::from).expect("Session user without id")
+}
@@ -0,0 +1,660 @@
+pub mod utils;
+
+use chrono::Duration;
+use serde_json::json;
+use server::users::Role;
+use sword::web::ResponseBody;
+
+use crate::{
+    TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, TEST_LOGIN_BACKOFF_BASE_MS,
+    audit::utils::{audit_entries, session_user_id},
+    authorization::utils::TestAccount,
+    init_test_app, init_test_app_with, login, set_session,
+};
+
+use utils::*;
+
+#[tokio::test]
+async fn test_login_should_work() {
+    let app = init_test_app().await;
+    let session = login(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    assert!(!token(&session, "sessionToken").is_empty());
+    assert!(!token(&session, "refreshToken").is_empty());
+    assert_eq!(session["user"]["email"].as_str(), Some(TEST_ADMIN_EMAIL));
+}
+
+#[tokio::test]
+async fn test_login_wrong_password() {
+    let app = init_test_app().await;
+    let credentials = json!({ "email": TEST_ADMIN_EMAIL, "password": "WrongPassword123!" });
+
+    let response = app.post("/auth/login").json(&credentials).await;
+
+    assert_eq!(response.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_login_unknown_email() {
+    let app = init_test_app().await;
+    let credentials = json!({ "email": "nobody@test.cl", "password": TEST_ADMIN_PASSWORD });
+
+    let response = app.post("/auth/login").json(&credentials).await;
+
+    assert_eq!(response.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_session_returns_authenticated_user() {
+    let app = init_test_app().await;
+    let response = app.get("/auth/session").await;
+
+    assert_eq!(response.status_code(), 200);
+
+    let body = response.json::<ResponseBody>();
+    assert_eq!(body.data["email"].as_str(), Some(TEST_ADMIN_EMAIL));
+}
+
+#[tokio::test]
+async fn test_protected_route_without_session() {
+    let mut app = init_test_app().await;
+    app.clear_headers();
+
+    let response = app.get("/users").await;
+
+    assert_eq!(response.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_protected_route_with_invalid_session() {
+    let mut app = init_test_app().await;
+    crate::set_session(&mut app, "not-a-valid-token");
+
+    let response = app.get("/users").await;
+
+    assert_eq!(response.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_tokens() {
+    let app = init_test_app().await;
+    let session = login(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+    let refresh_token = token(&session, "refreshToken");
+
+    let response = refresh(&app, &refresh_token).await;
+    assert_eq!(response.status_code(), 200);
+
+    let rotated = response.json::<ResponseBody>().data;
+    assert_ne!(token(&rotated, "refreshToken"), refresh_token);
+
+    // Reusing a rotated refresh token must fail and revoke the whole session
+    let reused = refresh(&app, &refresh_token).await;
+    assert_eq!(reused.status_code(), 401);
+
+    let after_reuse = refresh(&app, &token(&rotated, "refreshToken")).await;
+    assert_eq!(after_reuse.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_concurrent_refresh_rotates_only_once() {
+    let app = init_test_app().await;
+    let session = login(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+    let refresh_token = token(&session, "refreshToken");
+
+    let (first, second) =
+        tokio::join!(refresh(&app, &refresh_token), refresh(&app, &refresh_token));
+
+    // At most one request may claim the token; the other counts as reuse and
+    // revokes the session, so no rotated token stays valid afterwards
+    let rotated: Vec<_> = [first, second]
+        .into_iter()
+        .filter(|response| response.status_code() == 200)
+        .map(|response| response.json::<ResponseBody>().data)
+        .collect();
+    assert!(rotated.len() <= 1);
+
+    for session in rotated {
+        let after_race = refresh(&app, &token(&session, "refreshToken")).await;
+        assert_eq!(after_race.status_code(), 401);
+    }
+}
+
+#[tokio::test]
+async fn test_logout_revokes_refresh_token() {
+    let app = init_test_app().await;
+    let session = login(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+    let refresh_token = token(&session, "refreshToken");
+
+    let response = logout(&app, &refresh_token).await;
+    assert_eq!(response.status_code(), 200);
+
+    let response = refresh(&app, &refresh_token).await;
+    assert_eq!(response.status_code(), 401);
+}
+
+// ==================== PASSWORD RESET ====================
+
+#[tokio::test]
+async fn test_forgot_password_does_not_reveal_accounts() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let response = forgot_password(&app, &account.email).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(count_reset_tokens(&account.id).await, 1);
+
+    let response = forgot_password(&app, "nobody@test.cl").await;
+    assert_eq!(response.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_reset_password_should_work_once() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let session = login(&app, &account.email, "TestPassword123!").await;
+
+    let token = insert_reset_token(&account.id, Duration::hours(1)).await;
+
+    let response = reset_password(&app, &token, "NewPassword456!").await;
+    assert_eq!(response.status_code(), 200);
+
+    login(&app, &account.email, "NewPassword456!").await;
+
+    let credentials = json!({ "email": account.email, "password": "TestPassword123!" });
+    assert_eq!(app.post("/auth/login").json(&credentials).await.status_code(), 401);
+
+    // Las sesiones previas al cambio quedan revocadas
+    let response = refresh(&app, &token(&session, "refreshToken")).await;
+    assert_eq!(response.status_code(), 401);
+
+    let response = reset_password(&app, &token, "OtherPassword789!").await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_reset_password_rejects_invalid_tokens() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let expired = insert_reset_token(&account.id, Duration::hours(-1)).await;
+    let response = reset_password(&app, &expired, "NewPassword456!").await;
+    assert_eq!(response.status_code(), 400);
+
+    let valid = insert_reset_token(&account.id, Duration::hours(1)).await;
+    let (id, _) = valid.split_once('.').unwrap();
+
+    let response = reset_password(&app, &format!("{id}.wrong-secret"), "NewPassword456!").await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = reset_password(&app, "not-a-token", "NewPassword456!").await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = reset_password(&app, &valid, "weak").await;
+    assert_eq!(response.status_code(), 400);
+}
+
+// ==================== FORCED PASSWORD CHANGE ====================
+
+#[tokio::test]
+async fn test_generated_password_requires_change_before_access() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    require_password_change(&account.id).await;
+
+    let session = login(&app, &account.email, "TestPassword123!").await;
+
+    assert_eq!(session["mustChangePassword"], true);
+    assert!(session["refreshToken"].is_null());
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+
+    assert_eq!(app.get("/courses").await.status_code(), 403);
+    assert_eq!(app.get("/auth/session").await.status_code(), 403);
+
+    let response = change_password(&app, "WrongPassword123!", "NewPassword456!").await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = change_password(&app, "TestPassword123!", "NewPassword456!").await;
+    assert_eq!(response.status_code(), 200);
+
+    let session = response.json::<ResponseBody>().data;
+
+    assert_eq!(session["mustChangePassword"], false);
+    assert!(!token(&session, "refreshToken").is_empty());
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+    assert_eq!(app.get("/courses").await.status_code(), 200);
+
+    let session = login(&app, &account.email, "NewPassword456!").await;
+    assert_eq!(session["mustChangePassword"], false);
+}
+
+#[tokio::test]
+async fn test_new_passwords_are_hashed_with_argon2id() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    assert!(stored_password_hash(&account.id).await.starts_with("$argon2id$"));
+}
+
+#[tokio::test]
+async fn test_login_rehashes_legacy_bcrypt_passwords() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    set_legacy_password(&account.id, "TestPassword123!").await;
+    assert!(stored_password_hash(&account.id).await.starts_with("$2"));
+
+    login(&app, &account.email, "TestPassword123!").await;
+
+    assert!(stored_password_hash(&account.id).await.starts_with("$argon2id$"));
+
+    // El nuevo hash sigue aceptando la misma contraseña
+    login(&app, &account.email, "TestPassword123!").await;
+}
+
+#[tokio::test]
+async fn test_change_password_requires_session() {
+    let mut app = init_test_app().await;
+    app.clear_headers();
+
+    let response = change_password(&app, "TestPassword123!", "NewPassword456!").await;
+
+    assert_eq!(response.status_code(), 401);
+}
+
+// ==================== LOGIN THROTTLING ====================
+
+fn backoff(multiplier: u64) -> std::time::Duration {
+    std::time::Duration::from_millis(TEST_LOGIN_BACKOFF_BASE_MS * multiplier + 50)
+}
+
+#[tokio::test]
+async fn test_failed_logins_apply_backoff() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    failed_logins(&app, &account.email, 3).await;
+
+    let response = login_from(&app, &account.email, "TestPassword123!", "10.0.0.2").await;
+    assert_eq!(response.status_code(), 429);
+
+    tokio::time::sleep(backoff(1)).await;
+
+    let response = login_from(&app, &account.email, "TestPassword123!", "10.0.0.2").await;
+    assert_eq!(response.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_failed_logins_are_tracked_per_ip() {
+    let app = init_test_app().await;
+
+    for index in 0..3 {
+        let email = format!("nobody{index}@test.cl");
+        let response = login_from(&app, &email, "WrongPassword123!", "10.0.0.3").await;
+
+        assert_eq!(response.status_code(), 401);
+    }
+
+    let response = login_from(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, "10.0.0.3").await;
+    assert_eq!(response.status_code(), 429);
+
+    let response = login_from(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, "10.0.0.4").await;
+    assert_eq!(response.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_forged_forwarded_for_does_not_reset_ip_throttle() {
+    let app = init_test_app().await;
+
+    // El cliente cambia la primera entrada en cada intento; el proxy agrega su IP real
+    for (real_ip, proxy_header) in [("10.0.0.6", Some("10.0.0.6")), ("10.0.0.7", None)] {
+        for index in 0..3 {
+            let email = format!("nobody{index}@test.cl");
+            let forwarded_for = format!("203.0.113.{index}, {real_ip}");
+            let response =
+                login_forwarded(&app, &email, "WrongPassword123!", &forwarded_for, proxy_header)
+                    .await;
+
+            assert_eq!(response.status_code(), 401);
+        }
+
+        let forwarded_for = format!("203.0.113.99, {real_ip}");
+        let response = login_forwarded(
+            &app,
+            TEST_ADMIN_EMAIL,
+            TEST_ADMIN_PASSWORD,
+            &forwarded_for,
+            proxy_header,
+        )
+        .await;
+
+        assert_eq!(response.status_code(), 429);
+    }
+}
+
+#[tokio::test]
+async fn test_account_locks_after_threshold_until_unlocked() {
+    let app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    // 3 intentos libres y luego un fallo por cada espera, hasta alcanzar el umbral
+    failed_logins(&app, &account.email, 3).await;
+
+    tokio::time::sleep(backoff(1)).await;
+    failed_logins(&app, &account.email, 1).await;
+
+    tokio::time::sleep(backoff(2)).await;
+    failed_logins(&app, &account.email, 1).await;
+
+    let response = login_from(&app, &account.email, "TestPassword123!", "10.0.0.5").await;
+    assert_eq!(response.status_code(), 403);
+
+    let response = app.post(&format!("/users/{}/unlock", account.id)).await;
+    assert_eq!(response.status_code(), 200);
+
+    let response = login_from(&app, &account.email, "TestPassword123!", "10.0.0.5").await;
+    assert_eq!(response.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_only_administrators_can_unlock_accounts() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let secretary = TestAccount::create(&app, "secretary").await;
+
+    secretary.act(&mut app).await;
+
+    let response = app.post(&format!("/users/{}/unlock", account.id)).await;
+    assert_eq!(response.status_code(), 403);
+}
+
+// ==================== SESSIONS ====================
+
+#[tokio::test]
+async fn test_list_sessions_marks_current() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let other = login(&app, &account.email, "TestPassword123!").await;
+    account.act(&mut app).await;
+
+    let sessions = list_sessions(&app).await;
+
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|session| session["current"] == true).count(), 1);
+
+    set_session(&mut app, &token(&other, "sessionToken"));
+
+    let current = list_sessions(&app).await.into_iter().find(|s| s["current"] == true);
+    assert!(current.is_some());
+}
+
+#[tokio::test]
+async fn test_revoke_session_invalidates_its_tokens() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let other = login(&app, &account.email, "TestPassword123!").await;
+    account.act(&mut app).await;
+
+    let other_id = list_sessions(&app)
+        .await
+        .into_iter()
+        .find(|session| session["current"] == false)
+        .and_then(|session| session["id"].as_str().map(String::from))
+        .expect("Missing second session");
+
+    let response = app.delete(&format!("/auth/sessions/{other_id}")).await;
+    assert_eq!(response.status_code(), 200);
+
+    assert_eq!(list_sessions(&app).await.len(), 1);
+
+    let response = refresh(&app, &token(&other, "refreshToken")).await;
+    assert_eq!(response.status_code(), 401);
+
+    set_session(&mut app, &token(&other, "sessionToken"));
+    assert_eq!(app.get("/auth/session").await.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_cannot_revoke_sessions_of_other_users() {
+    let mut app = init_test_app().await;
+    let admin_session_id = list_sessions(&app).await[0]["id"].as_str().unwrap().to_string();
+
+    let account = TestAccount::create(&app, "student").await;
+    account.act(&mut app).await;
+
+    let response = app.delete(&format!("/auth/sessions/{admin_session_id}")).await;
+    assert_eq!(response.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_administrator_revokes_all_sessions_of_user() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let session = login(&app, &account.email, "TestPassword123!").await;
+
+    let response = app.delete(&format!("/users/{}/sessions", account.id)).await;
+    assert_eq!(response.status_code(), 200);
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+    assert_eq!(app.get("/auth/session").await.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_role_change_revokes_sessions() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let session = login(&app, &account.email, "TestPassword123!").await;
+
+    let response = app
+        .put(&format!("/users/{}", account.id))
+        .json(&json!({ "roles": ["teacher"] }))
+        .await;
+
+    assert_eq!(response.status_code(), 200);
+
+    let response = refresh(&app, &token(&session, "refreshToken")).await;
+    assert_eq!(response.status_code(), 401);
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+    assert_eq!(app.get("/auth/session").await.status_code(), 401);
+}
+
+// ==================== IMPERSONATION ====================
+
+#[tokio::test]
+async fn test_administrator_impersonates_user() {
+    let mut app = init_test_app().await;
+    let admin_id = session_user_id(&app).await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let session = impersonate(&app, &account.id).await;
+
+    assert!(session["refreshToken"].is_null());
+    assert_eq!(session["impersonatedBy"].as_str(), Some(admin_id.as_str()));
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+
+    let response = app.get("/auth/session").await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(impersonated_by(&response), Some(admin_id));
+
+    let body = response.json::<ResponseBody>();
+    assert_eq!(body.data["email"].as_str(), Some(account.email.as_str()));
+
+    // La sesión suplantada solo posee los permisos del usuario
+    assert_eq!(app.get("/users").await.status_code(), 403);
+
+    account.act(&mut app).await;
+    assert_eq!(impersonated_by(&app.get("/auth/session").await), None);
+}
+
+#[tokio::test]
+async fn test_impersonation_blocks_sensitive_actions() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let session = impersonate(&app, &account.id).await;
+    set_session(&mut app, &token(&session, "sessionToken"));
+
+    let response = change_password(&app, "TestPassword123!", "NewPassword123!").await;
+    assert_eq!(response.status_code(), 403);
+
+    let session_id = list_sessions(&app).await[0]["id"].as_str().unwrap().to_string();
+
+    let response = app.delete(&format!("/auth/sessions/{session_id}")).await;
+    assert_eq!(response.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_only_administrators_can_impersonate() {
+    let mut app = init_test_app().await;
+    let admin_id = session_user_id(&app).await;
+    let account = TestAccount::create(&app, "student").await;
+    let secretary = TestAccount::create(&app, "secretary").await;
+
+    let response = app.post(&format!("/auth/impersonate/{admin_id}")).await;
+    assert_eq!(response.status_code(), 403);
+
+    secretary.act(&mut app).await;
+
+    let response = app.post(&format!("/auth/impersonate/{}", account.id)).await;
+    assert_eq!(response.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_impersonation_is_audited() {
+    let mut app = init_test_app().await;
+    let admin_id = session_user_id(&app).await;
+    let account = TestAccount::create(&app, "student").await;
+
+    let session = impersonate(&app, &account.id).await;
+    set_session(&mut app, &token(&session, "sessionToken"));
+
+    let response = app.delete("/auth/impersonate").await;
+    assert_eq!(response.status_code(), 200);
+
+    assert_eq!(app.get("/auth/session").await.status_code(), 401);
+
+    let admin = login(&app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+    set_session(&mut app, &token(&admin, "sessionToken"));
+
+    for action in ["auth.impersonation_start", "auth.impersonation_stop"] {
+        let query = format!("action={action}&entityId={}", account.id);
+        let entries = audit_entries(&app, &query).await;
+
+        assert_eq!(entries.len(), 1, "Missing audit entry for {action}");
+        assert_eq!(entries[0]["actorId"].as_str(), Some(admin_id.as_str()));
+    }
+
+    let stop = &audit_entries(&app, "action=auth.impersonation_stop").await[0];
+    assert_eq!(stop["impersonatedId"].as_str(), Some(account.id.as_str()));
+}
+
+// ==================== TWO FACTOR ====================
+
+#[tokio::test]
+async fn test_two_factor_enrollment_and_login() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    account.act(&mut app).await;
+
+    let response = app.post("/auth/two-factor/setup").await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200);
+    assert!(body.data["provisioningUri"].as_str().unwrap().starts_with("otpauth://totp/"));
+
+    let response = app.post("/auth/two-factor/confirm").json(&json!({ "code": "000000" })).await;
+    assert_eq!(response.status_code(), 400);
+
+    let (secret, activation) = enable_two_factor(&app).await;
+
+    assert_eq!(activation["recoveryCodes"].as_array().map(Vec::len), Some(10));
+    assert!(!token(&activation, "sessionToken").is_empty());
+
+    let challenge = login(&app, &account.email, "TestPassword123!").await;
+
+    assert_eq!(challenge["twoFactorRequired"], true);
+    assert!(challenge.get("sessionToken").is_none());
+
+    let challenge_token = token(&challenge, "challengeToken");
+
+    // El código utilizado al confirmar no puede reutilizarse
+    let replayed = json!({ "code": totp_code(&secret, 0) });
+    let response = login_two_factor(&app, &challenge_token, replayed).await;
+    assert_eq!(response.status_code(), 401);
+
+    let next = json!({ "code": totp_code(&secret, 1) });
+    let response = login_two_factor(&app, &challenge_token, next).await;
+    assert_eq!(response.status_code(), 200);
+
+    let session = response.json::<ResponseBody>().data;
+    set_session(&mut app, &token(&session, "sessionToken"));
+
+    assert_eq!(app.get("/auth/session").await.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_two_factor_challenge_is_not_a_session() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    account.act(&mut app).await;
+
+    enable_two_factor(&app).await;
+
+    let challenge = login(&app, &account.email, "TestPassword123!").await;
+    set_session(&mut app, &token(&challenge, "challengeToken"));
+
+    assert_eq!(app.get("/auth/session").await.status_code(), 401);
+}
+
+#[tokio::test]
+async fn test_recovery_codes_are_single_use() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    account.act(&mut app).await;
+
+    let (_, activation) = enable_two_factor(&app).await;
+    let recovery_code = activation["recoveryCodes"][0].as_str().unwrap().to_uppercase();
+
+    for expected in [200, 401] {
+        let challenge = login(&app, &account.email, "TestPassword123!").await;
+        let input = json!({ "recoveryCode": recovery_code });
+
+        let response = login_two_factor(&app, &token(&challenge, "challengeToken"), input).await;
+        assert_eq!(response.status_code(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_two_factor_required_for_configured_roles() {
+    let mut app = init_test_app_with(|config| {
+        config.two_factor_required_roles = vec![Role::Secretary];
+    })
+    .await;
+
+    let secretary = TestAccount::create(&app, "secretary").await;
+    let session = login(&app, &secretary.email, "TestPassword123!").await;
+
+    assert_eq!(session["twoFactorSetupRequired"], true);
+    assert!(session["refreshToken"].is_null());
+
+    set_session(&mut app, &token(&session, "sessionToken"));
+    assert_eq!(app.get("/auth/session").await.status_code(), 403);
+
+    let (secret, activation) = enable_two_factor(&app).await;
+
+    assert_eq!(activation["twoFactorSetupRequired"], false);
+    assert!(!token(&activation, "refreshToken").is_empty());
+
+    set_session(&mut app, &token(&activation, "sessionToken"));
+    assert_eq!(app.get("/auth/session").await.status_code(), 200);
+
+    let input = json!({ "code": totp_code(&secret, 1) });
+    let response = app.post("/auth/two-factor/disable").json(&input).await;
+    assert_eq!(response.status_code(), 403);
+}
@@ -0,0 +1,239 @@
+use axum::http::{HeaderName, HeaderValue};
+use axum_test::{TestResponse, TestServer};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use server::shared::services::{
+    hasher::{BcryptPasswordHasher, PasswordHasher},
+    totp::code_at,
+};
+use sword::web::ResponseBody;
+use uuid::Uuid;
+
+use crate::test_database;
+
+pub fn token(session: &Value, key: &str) -> String {
+    session
+        .get(key)
+        .and_then(|token| token.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| panic!("Session does not contain '{key}': {session:?}"))
+}
+
+pub async fn refresh(server: &TestServer, refresh_token: &str) -> TestResponse {
+    server
+        .post("/auth/refresh")
+        .json(&json!({ "refreshToken": refresh_token }))
+        .await
+}
+
+pub async fn logout(server: &TestServer, refresh_token: &str) -> TestResponse {
+    server
+        .post("/auth/logout")
+        .json(&json!({ "refreshToken": refresh_token }))
+        .await
+}
+
+pub async fn forgot_password(server: &TestServer, email: &str) -> TestResponse {
+    server.post("/auth/password/forgot").json(&json!({ "email": email })).await
+}
+
+pub async fn reset_password(server: &TestServer, token: &str, password: &str) -> TestResponse {
+    server
+        .post("/auth/password/reset")
+        .json(&json!({ "token": token, "password": password, "confirmPassword": password }))
+        .await
+}
+
+/// Inserta un token de recuperación conocido para el usuario, equivalente
+/// al que se enviaría por correo, y retorna su forma `<id>.<secreto>`.
+pub async fn insert_reset_token(user_id: &str, expires_in: Duration) -> String {
+    let db = test_database().await;
+
+    let id = Uuid::new_v4();
+    let secret = Uuid::new_v4().simple().to_string();
+    let hash = BcryptPasswordHasher::default()
+        .hash(&secret)
+        .expect("Failed to hash reset token");
+
+    sqlx::query(
+        "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at)
+         VALUES ($1, $2, $3, $4, NOW())",
+    )
+    .bind(id)
+    .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+    .bind(hash)
+    .bind(Utc::now() + expires_in)
+    .execute(&db.pool)
+    .await
+    .expect("Failed to insert reset token");
+
+    format!("{id}.{secret}")
+}
+
+pub async fn count_reset_tokens(user_id: &str) -> i64 {
+    let db = test_database().await;
+
+    sqlx::query_scalar("SELECT COUNT(*) FROM password_reset_tokens WHERE user_id = $1")
+        .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+        .fetch_one(&db.pool)
+        .await
+        .expect("Failed to count reset tokens")
+}
+
+pub async fn require_password_change(user_id: &str) {
+    let db = test_database().await;
+
+    sqlx::query("UPDATE users SET must_change_password = TRUE WHERE id = $1")
+        .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+        .execute(&db.pool)
+        .await
+        .expect("Failed to flag user");
+}
+
+pub async fn change_password(
+    server: &TestServer,
+    current_password: &str,
+    password: &str,
+) -> TestResponse {
+    server
+        .post("/auth/password/change")
+        .json(&json!({
+            "currentPassword": current_password,
+            "password": password,
+            "confirmPassword": password,
+        }))
+        .await
+}
+
+/// Reemplaza la contraseña del usuario por un hash bcrypt, como las cuentas
+/// creadas antes de adoptar Argon2id.
+pub async fn set_legacy_password(user_id: &str, password: &str) {
+    let db = test_database().await;
+    let hash = BcryptPasswordHasher::default().hash(password).expect("Failed to hash password");
+
+    sqlx::query("UPDATE users SET password = $1 WHERE id = $2")
+        .bind(hash)
+        .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+        .execute(&db.pool)
+        .await
+        .expect("Failed to set legacy password");
+}
+
+pub async fn stored_password_hash(user_id: &str) -> String {
+    let db = test_database().await;
+
+    sqlx::query_scalar("SELECT password FROM users WHERE id = $1")
+        .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+        .fetch_one(&db.pool)
+        .await
+        .expect("Failed to fetch password hash")
+}
+
+/// Intento de login enviado como si proviniera de la IP indicada (vía proxy reverso)
+pub async fn login_from(
+    server: &TestServer,
+    email: &str,
+    password: &str,
+    ip: &'static str,
+) -> TestResponse {
+    server
+        .post("/auth/login")
+        .add_header(HeaderName::from_static("x-forwarded-for"), HeaderValue::from_static(ip))
+        .json(&json!({ "email": email, "password": password }))
+        .await
+}
+
+/// Intento de login con un `X-Forwarded-For` elegido por el cliente, al que el
+/// proxy reverso agrega la IP real (y la informa en `X-Real-IP` si se indica).
+pub async fn login_forwarded(
+    server: &TestServer,
+    email: &str,
+    password: &str,
+    forwarded_for: &str,
+    real_ip: Option<&'static str>,
+) -> TestResponse {
+    let mut request = server.post("/auth/login").add_header(
+        HeaderName::from_static("x-forwarded-for"),
+        HeaderValue::from_str(forwarded_for).unwrap(),
+    );
+
+    if let Some(ip) = real_ip {
+        request =
+            request.add_header(HeaderName::from_static("x-real-ip"), HeaderValue::from_static(ip));
+    }
+
+    request.json(&json!({ "email": email, "password": password })).await
+}
+
+pub async fn failed_logins(server: &TestServer, email: &str, attempts: usize) {
+    for _ in 0..attempts {
+        let response = login_from(server, email, "WrongPassword123!", "10.0.0.1").await;
+        assert_eq!(response.status_code(), 401);
+    }
+}
+
+pub async fn list_sessions(server: &TestServer) -> Vec<Value> {
+    let response = server.get("/auth/sessions").await;
+    assert_eq!(response.status_code(), 200);
+
+    response.json::<ResponseBody>().data.as_array().cloned().unwrap_or_default()
+}
+
+/// Inicia una suplantación del usuario y retorna la sesión emitida.
+pub async fn impersonate(server: &TestServer, user_id: &str) -> Value {
+    let response = server.post(&format!("/auth/impersonate/{user_id}")).await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to impersonate {user_id}: {}", body.data);
+
+    body.data
+}
+
+pub fn impersonated_by(response: &TestResponse) -> Option<String> {
+    response
+        .headers()
+        .get("x-impersonated-by")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Código TOTP del secreto desplazado `offset_steps` pasos de 30 segundos
+/// respecto del actual, para simular códigos sucesivos de la aplicación.
+pub fn totp_code(secret: &str, offset_steps: i64) -> String {
+    let timestamp = (Utc::now().timestamp() + offset_steps * 30) as u64;
+
+    code_at(secret, timestamp).expect("Failed to generate TOTP code")
+}
+
+/// Activa la verificación en dos pasos para la sesión actual y retorna
+/// el secreto junto a la respuesta de activación (nueva sesión y códigos).
+pub async fn enable_two_factor(server: &TestServer) -> (String, Value) {
+    let response = server.post("/auth/two-factor/setup").await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to setup two factor: {}", body.data);
+
+    let secret = token(&body.data, "secret");
+
+    let response = server
+        .post("/auth/two-factor/confirm")
+        .json(&json!({ "code": totp_code(&secret, 0) }))
+        .await;
+
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to confirm two factor: {}", body.data);
+
+    (secret, body.data)
+}
+
+pub async fn login_two_factor(
+    server: &TestServer,
+    challenge_token: &str,
+    input: Value,
+) -> TestResponse {
+    let mut payload = input;
+    payload["challengeToken"] = json!(challenge_token);
+
+    server.post("/auth/login/two-factor").json(&payload).await
+}
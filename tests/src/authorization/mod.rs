@@ -0,0 +1,294 @@
+pub mod utils;
+
+use serde_json::{Value, json};
+use sword::web::ResponseBody;
+
+use crate::{
+    TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, act_as,
+    courses::utils::{CourseBuilder, create_course, update_course},
+    enrollments::utils::{EnrollmentBuilder, create_enrollment},
+    extract_resource_id, init_test_app,
+    practices::utils::TestPractice,
+    users::utils::UserBuilder,
+};
+
+use utils::*;
+
+fn scores_for(course: &Value, score: f64) -> Value {
+    let scores = course["evaluations"]
+        .as_array()
+        .expect("Course should contain evaluations")
+        .iter()
+        .map(|evaluation| json!({ "evaluationId": evaluation["id"], "score": score }))
+        .collect::<Vec<_>>();
+
+    json!({ "studentScores": scores })
+}
+
+// ==================== UNAUTHENTICATED ====================
+
+#[tokio::test]
+async fn test_unauthenticated_requests_are_rejected() {
+    let mut app = init_test_app().await;
+    app.clear_headers();
+
+    assert_eq!(app.get("/courses").await.status_code(), 401);
+    assert_eq!(app.get("/users").await.status_code(), 401);
+    assert_eq!(app.post("/courses/enroll").json(&json!({})).await.status_code(), 401);
+    assert_eq!(app.post("/imports/course").json(&json!({})).await.status_code(), 401);
+}
+
+// ==================== STUDENT ====================
+
+#[tokio::test]
+async fn test_student_permissions() {
+    let mut app = init_test_app().await;
+    let student = TestAccount::create(&app, "student").await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+
+    student.act(&mut app).await;
+
+    assert_eq!(app.get("/courses").await.status_code(), 200);
+    assert_eq!(app.get("/users").await.status_code(), 403);
+
+    let course = CourseBuilder::new(&teacher.id).build();
+    assert_eq!(app.post("/courses").json(&course).await.status_code(), 403);
+
+    let enrollment = EnrollmentBuilder::new().with_student_id(&student.id).build();
+
+    assert_eq!(app.post("/courses/enroll").json(&enrollment).await.status_code(), 403);
+    assert_eq!(app.delete(&format!("/users/{}", teacher.id)).await.status_code(), 403);
+}
+
+// ==================== TEACHER ====================
+
+#[tokio::test]
+async fn test_teacher_permissions() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+
+    let course_data = CourseBuilder::new(&teacher.id).build();
+    let course_id = extract_resource_id(&create_course(&app, &course_data).await);
+
+    teacher.act(&mut app).await;
+
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&student.id)
+        .with_course_id(&course_id)
+        .build();
+
+    assert_eq!(app.get("/users").await.status_code(), 403);
+    assert_eq!(app.post("/courses/enroll").json(&enrollment).await.status_code(), 403);
+
+    let import = json!({ "id": course_id, "students": [] });
+    assert_eq!(app.post("/imports/course").json(&import).await.status_code(), 403);
+
+    let response = app.get(&format!("/courses/{course_id}/students")).await;
+    assert_eq!(response.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_only_course_teacher_can_update_scores() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let other_teacher = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student.id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment_data).await);
+    let route = format!("/courses/enrollments/{enrollment_id}");
+    let scores = scores_for(&course, 6.5);
+
+    // El administrador no es profesor del curso
+    assert_eq!(app.patch(&route).json(&scores).await.status_code(), 403);
+
+    other_teacher.act(&mut app).await;
+    assert_eq!(app.patch(&route).json(&scores).await.status_code(), 403);
+
+    teacher.act(&mut app).await;
+    let response = app.patch(&route).json(&scores).await;
+
+    assert_eq!(response.status_code(), 200);
+
+    let body = response.json::<ResponseBody>();
+    let saved_scores = body.data["studentScores"].as_array().expect("Scores should be present");
+
+    assert_eq!(saved_scores.len(), course["evaluations"].as_array().unwrap().len());
+}
+
+// ==================== SECRETARY ====================
+
+#[tokio::test]
+async fn test_secretary_permissions() {
+    let mut app = init_test_app().await;
+    let secretary = TestAccount::create(&app, "secretary").await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+
+    secretary.act(&mut app).await;
+
+    assert_eq!(app.get("/users").await.status_code(), 200);
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student.id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment_data).await);
+
+    let route = format!("/courses/enrollments/{enrollment_id}");
+    let response = app.patch(&route).json(&scores_for(&course, 5.0)).await;
+    assert_eq!(response.status_code(), 403);
+
+    let response = app.delete(&format!("/users/{}", student.id)).await;
+    assert_eq!(response.status_code(), 403);
+}
+
+// ==================== ADMINISTRATOR ====================
+
+#[tokio::test]
+async fn test_administrator_permissions() {
+    let mut app = init_test_app().await;
+    let secretary = TestAccount::create(&app, "secretary").await;
+
+    // Volver a la sesión del administrador tras actuar como otro rol
+    secretary.act(&mut app).await;
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let update = json!({ "roles": ["secretary", "teacher"] });
+    let response = app.put(&format!("/users/{}", secretary.id)).json(&update).await;
+    assert_eq!(response.status_code(), 200);
+
+    let user = UserBuilder::new().with_roles(vec!["student"]).build();
+    let response = app.post("/users").json(&user).await;
+    assert_eq!(response.status_code(), 201);
+
+    let response = app.delete(&format!("/users/{}", secretary.id)).await;
+    assert_eq!(response.status_code(), 200);
+}
+
+// ==================== OWNERSHIP ====================
+
+#[tokio::test]
+async fn test_teacher_only_sees_students_of_own_courses() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let other_teacher = TestAccount::create(&app, "teacher").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let route = format!("/courses/{}/students", extract_resource_id(&course));
+
+    other_teacher.act(&mut app).await;
+    assert_eq!(app.get(&route).await.status_code(), 403);
+
+    teacher.act(&mut app).await;
+    assert_eq!(app.get(&route).await.status_code(), 200);
+}
+
+#[tokio::test]
+async fn test_student_only_accesses_own_enrollment_and_practice() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let owner = TestAccount::create(&app, "student").await;
+    let intruder = TestAccount::create(&app, "student").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    let mut enrollment_ids = vec![];
+
+    for student in [&owner, &intruder] {
+        let data = EnrollmentBuilder::new()
+            .with_student_id(&student.id)
+            .with_course_id(&course_id)
+            .build();
+
+        enrollment_ids.push(extract_resource_id(&create_enrollment(&app, &data).await));
+    }
+
+    let owner_enrollment = &enrollment_ids[0];
+
+    intruder.act(&mut app).await;
+
+    let route = format!("/enrollments/{owner_enrollment}/practice");
+    assert_eq!(app.post(&route).json(&practice_payload()).await.status_code(), 403);
+
+    owner.act(&mut app).await;
+
+    let practice_id = TestPractice::create(&app, owner_enrollment, practice_payload()).await;
+    TestPractice::authorize(&app, owner_enrollment, &practice_id).await;
+
+    let docs_route = format!("/enrollments/practice/{practice_id}/docs");
+    assert_ne!(app.get(&docs_route).await.status_code(), 403);
+
+    intruder.act(&mut app).await;
+
+    assert_eq!(app.get(&docs_route).await.status_code(), 403);
+
+    let update = json!({ "enterpriseName": "Empresa Ajena" });
+    assert_eq!(app.patch(&route).json(&update).await.status_code(), 403);
+
+    owner.act(&mut app).await;
+
+    let response = app.patch(&route).json(&update).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["enterpriseName"], "Empresa Ajena");
+}
+
+#[tokio::test]
+async fn test_only_course_coordinator_reviews_practices() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+    let coordinator = TestAccount::create(&app, "coordinator").await;
+    let other_coordinator = TestAccount::create(&app, "coordinator").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    update_course(&app, &course_id, &json!({ "coordinatorId": coordinator.id })).await;
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student.id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment_data).await);
+    TestPractice::create(&app, &enrollment_id, practice_payload()).await;
+
+    let route = format!("/enrollments/{enrollment_id}/practice/status");
+    let review = json!({ "status": "approved" });
+
+    teacher.act(&mut app).await;
+    assert_eq!(app.patch(&route).json(&review).await.status_code(), 403);
+
+    other_coordinator.act(&mut app).await;
+    assert_eq!(app.patch(&route).json(&review).await.status_code(), 403);
+
+    coordinator.act(&mut app).await;
+
+    let students_route = format!("/courses/{course_id}/students");
+    assert_eq!(app.get(&students_route).await.status_code(), 200);
+
+    let response = app.patch(&route).json(&json!({ "status": "pending" })).await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = app.patch(&route).json(&review).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["practiceStatus"], "approved");
+
+    // Una práctica ya revisada no puede volver a cambiar de estado
+    let response = app.patch(&route).json(&json!({ "status": "declined" })).await;
+    assert_eq!(response.status_code(), 400);
+}
@@ -0,0 +1,50 @@
+use axum_test::TestServer;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+use crate::{
+    act_as, extract_resource_id,
+    practices::utils::TestPractice,
+    users::utils::{UserBuilder, create_user, generate_unique_email},
+};
+
+/// Contraseña por defecto de los usuarios creados con `UserBuilder`
+pub const ACCOUNT_PASSWORD: &str = "TestPassword123!";
+
+pub struct TestAccount {
+    pub id: String,
+    pub email: String,
+}
+
+impl TestAccount {
+    pub async fn create(server: &TestServer, role: &str) -> Self {
+        let email = generate_unique_email();
+        let user = UserBuilder::new().with_roles(vec![role]).with_email(&email).build();
+
+        let data = create_user(server, user).await;
+
+        TestAccount {
+            id: extract_resource_id(&data),
+            email,
+        }
+    }
+
+    pub async fn act(&self, server: &mut TestServer) {
+        act_as(server, &self.email, ACCOUNT_PASSWORD).await;
+    }
+}
+
+pub fn practice_payload() -> Value {
+    let start_date = Utc::now();
+    let end_date = start_date + Duration::days(90);
+
+    TestPractice::builder()
+        .with_enterprise_name("Empresa Test")
+        .with_description("Descripción de prueba")
+        .with_location("Ubicación de prueba")
+        .with_supervisor_name("Supervisor Test")
+        .with_supervisor_phone("+56912345678")
+        .with_start_date(&start_date.to_rfc3339())
+        .with_end_date(&end_date.to_rfc3339())
+        .build()
+}
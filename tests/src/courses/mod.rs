@@ -1,9 +1,11 @@
 pub mod utils;
 
 use crate::{
+    TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, act_as,
+    authorization::utils::TestAccount,
     enrollments::utils::{EnrollmentBuilder, create_enrollment, delete_enrollment},
     extract_resource_id, init_test_app,
-    users::utils::{create_student, create_teacher, delete_user},
+    users::utils::{create_coordinator, create_student, create_teacher, delete_user},
 };
 
 use serde_json::json;
@@ -37,11 +39,52 @@ async fn test_create_course_should_work() {
 async fn test_get_courses() {
     let app = init_test_app().await;
 
-    let response = app.get("/courses").await;
-    assert_eq!(response.status_code(), 200);
+    let page = list_courses(&app, "").await;
 
-    let body = response.json::<ResponseBody>();
-    assert!(body.data.is_array());
+    assert!(page["courses"].is_array());
+    assert_eq!(page["currentPage"], 1);
+}
+
+#[tokio::test]
+async fn test_get_courses_filters_and_paginates() {
+    let app = init_test_app().await;
+    let teacher_id = create_teacher(&app).await;
+    let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+
+    for (year, suffix) in [(2023, "A"), (2024, "B"), (2024, "C")] {
+        let course = CourseBuilder::new(&teacher_id)
+            .with_year(year)
+            .with_name(&format!("Práctica {tag} {suffix}"))
+            .build();
+        create_course(&app, &course).await;
+    }
+
+    let page = list_courses(&app, &format!("teacherId={teacher_id}&year=2024")).await;
+    assert_eq!(page["total"], 2);
+
+    let page = list_courses(&app, &format!("teacherId={teacher_id}&perPage=2&page=1")).await;
+    assert_eq!(page["courses"].as_array().map(Vec::len), Some(2));
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["totalPages"], 2);
+    assert_eq!(page["hasNext"], true);
+    assert_eq!(page["courses"][0]["year"], 2024);
+
+    let page = list_courses(&app, &format!("search={}", tag.to_uppercase())).await;
+    assert_eq!(page["total"], 3);
+
+    let page = list_courses(&app, &format!("teacherId={teacher_id}&status=completed")).await;
+    assert_eq!(page["total"], 0);
+
+    for query in [
+        "perPage=0",
+        "page=0",
+        "status=archived",
+        "code=ABC",
+        "year=99",
+    ] {
+        let response = app.get(&format!("/courses?{query}")).await;
+        assert_eq!(response.status_code(), 400, "Query should be rejected: {query}");
+    }
 }
 
 #[tokio::test]
@@ -76,6 +119,49 @@ async fn test_update_course() {
     delete_user(&app, &new_teacher_id).await;
 }
 
+#[tokio::test]
+async fn test_assign_course_coordinator() {
+    let app = init_test_app().await;
+    let teacher_id = create_teacher(&app).await;
+    let coordinator_id = create_coordinator(&app).await;
+
+    let created_course = create_course(&app, &CourseBuilder::new(&teacher_id).build()).await;
+    let created_course_id = extract_resource_id(&created_course);
+
+    assert!(created_course["coordinatorId"].is_null());
+
+    let updated_course =
+        update_course(&app, &created_course_id, &json!({ "coordinatorId": coordinator_id })).await;
+
+    assert_eq!(updated_course["coordinatorId"].as_str(), Some(coordinator_id.as_str()));
+
+    let page = list_courses(&app, &format!("teacherId={teacher_id}")).await;
+
+    let listed_course = page["courses"]
+        .as_array()
+        .and_then(|courses| courses.iter().find(|c| c["id"] == created_course_id.as_str()))
+        .expect("Course should be listed");
+
+    assert_eq!(listed_course["coordinator"]["id"].as_str(), Some(coordinator_id.as_str()));
+    assert_eq!(listed_course["teacher"]["id"].as_str(), Some(teacher_id.as_str()));
+}
+
+#[tokio::test]
+async fn test_assign_non_coordinator_should_fail() {
+    let app = init_test_app().await;
+    let teacher_id = create_teacher(&app).await;
+
+    let created_course = create_course(&app, &CourseBuilder::new(&teacher_id).build()).await;
+    let created_course_id = extract_resource_id(&created_course);
+
+    let response = app
+        .patch(&format!("/courses/{created_course_id}"))
+        .json(&json!({ "coordinatorId": teacher_id }))
+        .await;
+
+    assert_eq!(response.status_code(), 400);
+}
+
 #[tokio::test]
 async fn test_delete_course() {
     let app = init_test_app().await;
@@ -530,6 +616,32 @@ async fn test_update_course_invalid_teacher_id() {
     delete_user(&app, &teacher_id).await;
 }
 
+#[tokio::test]
+async fn test_update_course_teacher_must_be_a_teacher() {
+    let app = init_test_app().await;
+    let teacher_id = create_teacher(&app).await;
+    let student_id = create_student(&app).await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher_id).build()).await;
+    let route = format!("/courses/{}", extract_resource_id(&course));
+
+    let response = app.patch(&route).json(&json!({ "teacherId": student_id })).await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = app.patch(&route).json(&json!({ "teacherId": Uuid::new_v4() })).await;
+    assert_eq!(response.status_code(), 404);
+
+    let new_teacher_id = create_teacher(&app).await;
+    let response = app.patch(&route).json(&json!({ "teacherId": new_teacher_id })).await;
+    assert_eq!(response.status_code(), 200);
+
+    let page = list_courses(&app, &format!("staffId={student_id}")).await;
+    assert_eq!(page["total"], 0);
+
+    let page = list_courses(&app, &format!("staffId={new_teacher_id}")).await;
+    assert_eq!(page["courses"][0]["staff"][0]["role"], "lead");
+}
+
 #[tokio::test]
 async fn test_update_nonexistent_asignature() {
     let app = init_test_app().await;
@@ -807,6 +919,63 @@ async fn test_update_course_evaluations_success() {
     delete_user(&app, &teacher_id).await;
 }
 
+#[tokio::test]
+async fn test_update_course_evaluations_preserves_scores() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+
+    let course = CourseBuilder::new(&teacher.id)
+        .with_evaluations(vec![("Bitácoras Semanales", 60), ("Informe Final", 40)])
+        .build();
+    let course = create_course(&app, &course).await;
+    let course_id = extract_resource_id(&course);
+    let (first, second) = (&course["evaluations"][0]["id"], &course["evaluations"][1]["id"]);
+
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&create_student(&app).await)
+        .with_course_id(&course_id)
+        .build();
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment).await);
+
+    teacher.act(&mut app).await;
+
+    let response = set_scores(&app, &course, &enrollment_id, &[6.0, 5.0]).await;
+    assert_eq!(response.status_code(), 200);
+
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let route = format!("/courses/{course_id}");
+
+    let reweighted = json!({ "evaluations": [
+        { "id": first, "name": "Bitácoras", "weight": 70 },
+        { "id": second, "name": "Informe Final", "weight": 30 },
+    ]});
+
+    let updated = update_course(&app, &course_id, &reweighted).await;
+    assert_eq!(updated["evaluations"][0]["id"], *first);
+    assert_eq!(updated["evaluations"][0]["name"], "Bitácoras");
+    assert_eq!(updated["evaluations"][1]["id"], *second);
+    assert_eq!(updated["affectedEnrollments"], json!([]));
+
+    let unknown =
+        json!({ "evaluations": [{ "id": Uuid::new_v4(), "name": "Otra", "weight": 100 }] });
+    assert_eq!(app.patch(&route).json(&unknown).await.status_code(), 400);
+
+    // Quitar una evaluación con notas requiere confirmación
+    let mut removal =
+        json!({ "evaluations": [{ "id": first, "name": "Bitácoras", "weight": 100 }] });
+    assert_eq!(app.patch(&route).json(&removal).await.status_code(), 409);
+
+    removal["dropRemovedScores"] = json!(true);
+    let updated = update_course(&app, &course_id, &removal).await;
+    assert_eq!(updated["affectedEnrollments"], json!([enrollment_id]));
+
+    let response = app.get(&format!("/courses/{course_id}/students")).await;
+    let students = response.json::<ResponseBody>().data;
+
+    assert_eq!(students[0]["studentScores"], json!([{ "evaluationId": first, "score": 6.0 }]));
+}
+
 #[tokio::test]
 async fn test_update_course_evaluations_weights_not_sum_100() {
     let app = init_test_app().await;
@@ -892,3 +1061,274 @@ async fn test_update_course_evaluations_invalid_weight_values() {
     delete_course(&app, &created_course_id).await;
     delete_user(&app, &teacher_id).await;
 }
+
+#[tokio::test]
+async fn test_close_course_computes_final_grades() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let other_teacher = TestAccount::create(&app, "teacher").await;
+
+    let course = CourseBuilder::new(&teacher.id)
+        .with_evaluations(vec![("Informe Final", 60), ("Evaluación Empresa", 40)])
+        .build();
+    let course = create_course(&app, &course).await;
+    let course_id = extract_resource_id(&course);
+
+    let mut enrollment_ids = vec![];
+
+    for _ in 0..2 {
+        let enrollment = EnrollmentBuilder::new()
+            .with_student_id(&create_student(&app).await)
+            .with_course_id(&course_id)
+            .build();
+        enrollment_ids.push(extract_resource_id(&create_enrollment(&app, &enrollment).await));
+    }
+
+    let close_route = format!("/courses/{course_id}/close");
+
+    teacher.act(&mut app).await;
+
+    set_scores(&app, &course, &enrollment_ids[0], &[6.0, 5.0])
+        .await
+        .assert_status_ok();
+    assert_eq!(set_scores(&app, &course, &enrollment_ids[1], &[4.0]).await.status_code(), 200);
+
+    // Falta la nota de una evaluación
+    assert_eq!(app.post(&close_route).await.status_code(), 400);
+
+    set_scores(&app, &course, &enrollment_ids[1], &[4.0, 7.0])
+        .await
+        .assert_status_ok();
+
+    other_teacher.act(&mut app).await;
+    assert_eq!(app.post(&close_route).await.status_code(), 403);
+
+    teacher.act(&mut app).await;
+
+    let response = app.post(&close_route).await;
+    assert_eq!(response.status_code(), 200);
+
+    let body = response.json::<ResponseBody>().data;
+    assert_eq!(body["course"]["courseStatus"], "completed");
+
+    let grade_of = |enrollment_id: &str| {
+        body["finalGrades"]
+            .as_array()
+            .and_then(|grades| grades.iter().find(|g| g["enrollmentId"] == enrollment_id))
+            .and_then(|grade| grade["finalGrade"].as_f64())
+    };
+
+    assert_eq!(grade_of(&enrollment_ids[0]), Some(5.6));
+    assert_eq!(grade_of(&enrollment_ids[1]), Some(5.2));
+
+    let response = set_scores(&app, &course, &enrollment_ids[0], &[7.0, 7.0]).await;
+    assert_eq!(response.status_code(), 400);
+    assert_eq!(app.post(&close_route).await.status_code(), 400);
+
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let reopen = json!({ "status": "active" });
+    let response = app.patch(&format!("/courses/{course_id}")).json(&reopen).await;
+    assert_eq!(response.status_code(), 400);
+
+    // Un curso cerrado no admite nuevas inscripciones
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&create_student(&app).await)
+        .with_course_id(&course_id)
+        .build();
+    let response = app.post("/courses/enroll").json(&enrollment).await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_clone_course_into_new_year() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let new_teacher_id = create_teacher(&app).await;
+
+    let course = CourseBuilder::new(&teacher.id)
+        .with_evaluations(vec![("Informe Final", 60), ("Evaluación Empresa", 40)])
+        .build();
+    let course = create_course(&app, &course).await;
+    let course_id = extract_resource_id(&course);
+
+    let mut students = vec![];
+    let mut enrollment_ids = vec![];
+
+    for _ in 0..2 {
+        let student_id = create_student(&app).await;
+        let enrollment = EnrollmentBuilder::new()
+            .with_student_id(&student_id)
+            .with_course_id(&course_id)
+            .build();
+        enrollment_ids.push(extract_resource_id(&create_enrollment(&app, &enrollment).await));
+        students.push(student_id);
+    }
+
+    teacher.act(&mut app).await;
+
+    // El primer estudiante aprueba y el segundo reprueba
+    set_scores(&app, &course, &enrollment_ids[0], &[6.0, 5.0])
+        .await
+        .assert_status_ok();
+    set_scores(&app, &course, &enrollment_ids[1], &[2.0, 3.0])
+        .await
+        .assert_status_ok();
+
+    let response = app.post(&format!("/courses/{course_id}/close")).await;
+    assert_eq!(response.status_code(), 200);
+
+    let clone_route = format!("/courses/{course_id}/clone");
+    let payload = json!({
+        "year": 2025,
+        "teacherId": new_teacher_id,
+        "carryOverStudents": true,
+    });
+
+    assert_eq!(app.post(&clone_route).json(&payload).await.status_code(), 403);
+
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let response = app.post(&clone_route).json(&payload).await;
+    assert_eq!(response.status_code(), 201);
+
+    let clone = response.json::<ResponseBody>().data;
+    assert_eq!(clone["year"], 2025);
+    assert_eq!(clone["code"], course["code"]);
+    assert_eq!(clone["name"], course["name"]);
+    assert_eq!(clone["courseStatus"], "active");
+    assert_eq!(clone["teacherId"], new_teacher_id);
+    assert_ne!(clone["id"], course["id"]);
+
+    let original_evaluations = course["evaluations"].as_array().unwrap();
+    let cloned_evaluations = clone["evaluations"].as_array().unwrap();
+    assert_eq!(cloned_evaluations.len(), original_evaluations.len());
+
+    for (original, cloned) in original_evaluations.iter().zip(cloned_evaluations) {
+        assert_eq!(cloned["name"], original["name"]);
+        assert_eq!(cloned["weight"], original["weight"]);
+        assert_ne!(cloned["id"], original["id"]);
+    }
+
+    assert_eq!(clone["carriedOverStudents"], json!([students[1]]));
+
+    // Ya existe el curso con el mismo código y nombre para ese año
+    assert_eq!(app.post(&clone_route).json(&payload).await.status_code(), 409);
+
+    let missing_teacher = json!({ "year": 2026, "teacherId": Uuid::new_v4() });
+    assert_eq!(app.post(&clone_route).json(&missing_teacher).await.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_course_staff_roles() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let co_teacher = TestAccount::create(&app, "teacher").await;
+    let assistant = TestAccount::create(&app, "teacher").await;
+
+    let course = CourseBuilder::new(&teacher.id)
+        .with_evaluations(vec![("Informe Final", 60), ("Evaluación Empresa", 40)])
+        .build();
+    let course = create_course(&app, &course).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&create_student(&app).await)
+        .with_course_id(&course_id)
+        .build();
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment).await);
+
+    let staff_route = format!("/courses/{course_id}/staff");
+    let close_route = format!("/courses/{course_id}/close");
+
+    // Solo el profesor a cargo gestiona el equipo
+    co_teacher.act(&mut app).await;
+    let payload = json!({ "userId": assistant.id, "role": "assistant" });
+    assert_eq!(app.post(&staff_route).json(&payload).await.status_code(), 403);
+
+    teacher.act(&mut app).await;
+
+    let payload = json!({ "userId": co_teacher.id, "role": "lead" });
+    assert_eq!(app.post(&staff_route).json(&payload).await.status_code(), 400);
+
+    let payload = json!({ "userId": teacher.id, "role": "assistant" });
+    assert_eq!(app.post(&staff_route).json(&payload).await.status_code(), 409);
+
+    for (account, role) in [(&co_teacher, "co_teacher"), (&assistant, "assistant")] {
+        let payload = json!({ "userId": account.id, "role": role });
+        let response = app.post(&staff_route).json(&payload).await;
+        assert_eq!(response.status_code(), 201);
+        assert_eq!(response.json::<ResponseBody>().data["role"], role);
+    }
+
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let page = list_courses(&app, &format!("staffId={}", assistant.id)).await;
+    assert_eq!(page["total"], 1);
+
+    let listed_course = &page["courses"][0];
+    assert_eq!(listed_course["teacher"]["id"], teacher.id);
+
+    let roles = listed_course["staff"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|member| (member["userId"].clone(), member["role"].clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        vec![
+            (json!(teacher.id), json!("lead")),
+            (json!(co_teacher.id), json!("co_teacher")),
+            (json!(assistant.id), json!("assistant")),
+        ]
+    );
+
+    // El ayudante registra notas, pero no cierra el curso
+    assistant.act(&mut app).await;
+
+    set_scores(&app, &course, &enrollment_id, &[6.0, 5.0]).await.assert_status_ok();
+    assert_eq!(app.post(&close_route).await.status_code(), 403);
+
+    co_teacher.act(&mut app).await;
+    assert_eq!(app.post(&close_route).await.status_code(), 200);
+
+    teacher.act(&mut app).await;
+
+    let member_route = |user_id: &str| format!("{staff_route}/{user_id}");
+
+    assert_eq!(app.delete(&member_route(&teacher.id)).await.status_code(), 400);
+    assert_eq!(app.delete(&member_route(&assistant.id)).await.status_code(), 200);
+    assert_eq!(app.delete(&member_route(&assistant.id)).await.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_course_staff_is_listed_without_credentials() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let co_teacher = TestAccount::create(&app, "teacher").await;
+    let coordinator_id = create_coordinator(&app).await;
+    let student = TestAccount::create(&app, "student").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    update_course(&app, &course_id, &json!({ "coordinatorId": coordinator_id })).await;
+
+    let payload = json!({ "userId": co_teacher.id, "role": "co_teacher" });
+    let response = app.post(&format!("/courses/{course_id}/staff")).json(&payload).await;
+
+    assert_eq!(response.status_code(), 201);
+    assert!(!response.text().contains("\"password\""));
+
+    // Cualquier usuario con sesión lista los cursos
+    student.act(&mut app).await;
+
+    let page = list_courses(&app, &format!("teacherId={}", teacher.id)).await;
+    let listed_course = &page["courses"][0];
+
+    assert_eq!(listed_course["teacher"]["id"], teacher.id);
+    assert_eq!(listed_course["coordinator"]["id"], coordinator_id);
+    assert_eq!(listed_course["staff"].as_array().map(Vec::len), Some(3));
+    assert!(!page.to_string().contains("\"password\""));
+}
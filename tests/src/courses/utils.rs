@@ -1,4 +1,4 @@
-use axum_test::TestServer;
+use axum_test::{TestResponse, TestServer};
 use serde_json::{Value, json};
 use sword::web::ResponseBody;
 use uuid::Uuid;
@@ -148,3 +148,34 @@ pub async fn delete_course(server: &TestServer, course_id: &str) {
         format!("Failed to delete course: {:?}", body.data)
     );
 }
+
+/// Página de `GET /courses` para la consulta indicada, con el envoltorio de paginación.
+pub async fn list_courses(server: &TestServer, query: &str) -> Value {
+    let response = server.get(&format!("/courses?{query}")).await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to list courses: {}", body.data);
+
+    body.data
+}
+
+/// Registra las notas de una inscripción, una por evaluación del curso en orden.
+pub async fn set_scores(
+    server: &TestServer,
+    course: &Value,
+    enrollment_id: &str,
+    scores: &[f64],
+) -> TestResponse {
+    let student_scores = course["evaluations"]
+        .as_array()
+        .expect("Course should contain evaluations")
+        .iter()
+        .zip(scores)
+        .map(|(evaluation, score)| json!({ "evaluationId": evaluation["id"], "score": score }))
+        .collect::<Vec<_>>();
+
+    server
+        .patch(&format!("/courses/enrollments/{enrollment_id}"))
+        .json(&json!({ "studentScores": student_scores }))
+        .await
+}
@@ -1,4 +1,6 @@
 use axum::http::StatusCode;
+use chrono::Duration;
+use serde_json::json;
 use sword::web::ResponseBody;
 use uuid::Uuid;
 
@@ -6,8 +8,8 @@ use crate::{
     courses::utils::{CourseBuilder, create_course, delete_course},
     enrollments::utils::{EnrollmentBuilder, create_enrollment, delete_enrollment},
     extract_resource_id, init_test_app,
-    practices::utils::TestPractice,
-    users::utils::{create_student, create_teacher, delete_user},
+    practices::utils::{TestPractice, insert_supervisor_token, supervisor_token},
+    users::utils::{create_student, create_teacher, delete_user, generate_unique_email},
 };
 
 pub mod utils;
@@ -154,8 +156,11 @@ pub async fn create_enrollment_and_decline_practice() {
     let practice_id = extract_resource_id(&json);
 
     // Decline practice
+    let token = supervisor_token(&practice_id, "decline").await;
     let decline_practice_res = app
-        .post(&format!("/enrollments/{enrollment_id}/practice/{practice_id}/decline"))
+        .post(&format!(
+            "/enrollments/{enrollment_id}/practice/{practice_id}/decline?token={token}"
+        ))
         .await;
 
     decline_practice_res.assert_status(StatusCode::OK);
@@ -189,11 +194,14 @@ pub async fn decline_nonexistent_practice_should_fail() {
 
     // Try to decline a non-existent practice
     let fake_practice_id = Uuid::new_v4();
+    let fake_token = format!("{}.{}", Uuid::new_v4(), Uuid::new_v4().simple());
     let decline_practice_res = app
-        .post(&format!("/enrollments/{enrollment_id}/practice/{fake_practice_id}/decline"))
+        .post(&format!(
+            "/enrollments/{enrollment_id}/practice/{fake_practice_id}/decline?token={fake_token}"
+        ))
         .await;
 
-    decline_practice_res.assert_status(StatusCode::NOT_FOUND);
+    decline_practice_res.assert_status(StatusCode::UNAUTHORIZED);
 
     // Clean up
     delete_enrollment(&app, &enrollment_id).await;
@@ -242,15 +250,21 @@ pub async fn decline_already_approved_practice_should_fail() {
     let practice_id = extract_resource_id(&json);
 
     // First approve the practice
+    let token = supervisor_token(&practice_id, "approve").await;
     let approve_practice_res = app
-        .post(&format!("/enrollments/{enrollment_id}/practice/{practice_id}/approve"))
+        .post(&format!(
+            "/enrollments/{enrollment_id}/practice/{practice_id}/approve?token={token}"
+        ))
         .await;
 
     approve_practice_res.assert_status(StatusCode::OK);
 
     // Then try to decline the already approved practice (should fail)
+    let token = supervisor_token(&practice_id, "decline").await;
     let decline_practice_res = app
-        .post(&format!("/enrollments/{enrollment_id}/practice/{practice_id}/decline"))
+        .post(&format!(
+            "/enrollments/{enrollment_id}/practice/{practice_id}/decline?token={token}"
+        ))
         .await;
 
     decline_practice_res.assert_status(StatusCode::BAD_REQUEST);
@@ -262,6 +276,241 @@ pub async fn decline_already_approved_practice_should_fail() {
     delete_user(&app, &teacher_id).await;
 }
 
+#[tokio::test]
+pub async fn supervisor_links_require_a_valid_token() {
+    let app = init_test_app().await;
+
+    let student_id = create_student(&app).await;
+    let teacher_id = create_teacher(&app).await;
+
+    let course_data = CourseBuilder::new(&teacher_id).build();
+
+    let course = create_course(&app, &course_data).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student_id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment = create_enrollment(&app, &enrollment_data).await;
+    let enrollment_id = extract_resource_id(&enrollment);
+
+    let practice_data = TestPractice::builder()
+        .with_enterprise_name("Test Enterprise Supervisor Link")
+        .with_description("Test Description Supervisor Link")
+        .with_location("Test Location Supervisor Link")
+        .with_supervisor_name("Test Supervisor Link")
+        .with_supervisor_phone("+56912345678")
+        .with_start_date("2024-09-01T00:00:00Z")
+        .with_end_date("2024-12-15T00:00:00Z")
+        .build();
+
+    let practice_id = TestPractice::create(&app, &enrollment_id, practice_data).await;
+
+    let route = format!("/enrollments/{enrollment_id}/practice/{practice_id}/approve");
+
+    // Sin token la consulta no es válida
+    app.post(&route).await.assert_status(StatusCode::BAD_REQUEST);
+
+    // Un token con el secreto incorrecto no es aceptado
+    let token = supervisor_token(&practice_id, "approve").await;
+    let (id, _) = token.split_once('.').unwrap();
+    let forged = format!("{id}.{}", Uuid::new_v4().simple());
+
+    app.post(&format!("{route}?token={forged}"))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // Un token expirado tampoco
+    let expired = insert_supervisor_token(&practice_id, "approve", Duration::seconds(-1)).await;
+
+    app.post(&format!("{route}?token={expired}"))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    app.post(&format!("{route}?token={token}")).await.assert_status(StatusCode::OK);
+
+    // Clean up
+    delete_enrollment(&app, &enrollment_id).await;
+    delete_course(&app, &course_id).await;
+    delete_user(&app, &student_id).await;
+    delete_user(&app, &teacher_id).await;
+}
+
+#[tokio::test]
+pub async fn supervisor_token_is_single_use() {
+    let app = init_test_app().await;
+
+    let student_id = create_student(&app).await;
+    let teacher_id = create_teacher(&app).await;
+
+    let course_data = CourseBuilder::new(&teacher_id).build();
+
+    let course = create_course(&app, &course_data).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student_id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment = create_enrollment(&app, &enrollment_data).await;
+    let enrollment_id = extract_resource_id(&enrollment);
+
+    let practice_data = TestPractice::builder()
+        .with_enterprise_name("Test Enterprise Supervisor Link")
+        .with_description("Test Description Supervisor Link")
+        .with_location("Test Location Supervisor Link")
+        .with_supervisor_name("Test Supervisor Link")
+        .with_supervisor_phone("+56912345678")
+        .with_start_date("2024-09-01T00:00:00Z")
+        .with_end_date("2024-12-15T00:00:00Z")
+        .build();
+
+    let practice_id = TestPractice::create(&app, &enrollment_id, practice_data).await;
+
+    let token = supervisor_token(&practice_id, "approve").await;
+    let route =
+        format!("/enrollments/{enrollment_id}/practice/{practice_id}/approve?token={token}");
+
+    app.post(&route).await.assert_status(StatusCode::OK);
+    app.post(&route).await.assert_status(StatusCode::UNAUTHORIZED);
+
+    // Clean up
+    delete_enrollment(&app, &enrollment_id).await;
+    delete_course(&app, &course_id).await;
+    delete_user(&app, &student_id).await;
+    delete_user(&app, &teacher_id).await;
+}
+
+#[tokio::test]
+pub async fn supervisor_token_is_bound_to_its_action_and_practice() {
+    let app = init_test_app().await;
+
+    let student_id = create_student(&app).await;
+    let teacher_id = create_teacher(&app).await;
+
+    let course_data = CourseBuilder::new(&teacher_id).build();
+
+    let course = create_course(&app, &course_data).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student_id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment = create_enrollment(&app, &enrollment_data).await;
+    let enrollment_id = extract_resource_id(&enrollment);
+
+    let practice_data = TestPractice::builder()
+        .with_enterprise_name("Test Enterprise Supervisor Link")
+        .with_description("Test Description Supervisor Link")
+        .with_location("Test Location Supervisor Link")
+        .with_supervisor_name("Test Supervisor Link")
+        .with_supervisor_phone("+56912345678")
+        .with_start_date("2024-09-01T00:00:00Z")
+        .with_end_date("2024-12-15T00:00:00Z")
+        .build();
+
+    let practice_id = TestPractice::create(&app, &enrollment_id, practice_data).await;
+
+    // Un token de rechazo no sirve para aprobar
+    let decline_token = supervisor_token(&practice_id, "decline").await;
+
+    app.post(&format!(
+        "/enrollments/{enrollment_id}/practice/{practice_id}/approve?token={decline_token}"
+    ))
+    .await
+    .assert_status(StatusCode::UNAUTHORIZED);
+
+    // Ni un token de otra práctica
+    let other_practice_id = Uuid::new_v4();
+
+    app.post(&format!(
+        "/enrollments/{enrollment_id}/practice/{other_practice_id}/decline?token={decline_token}"
+    ))
+    .await
+    .assert_status(StatusCode::UNAUTHORIZED);
+
+    // Tampoco habilita la autorización ni la evaluación
+    app.post(&format!(
+        "/enrollments/{enrollment_id}/practice/{practice_id}/evaluate?token={decline_token}"
+    ))
+    .await
+    .assert_status(StatusCode::UNAUTHORIZED);
+
+    app.post(&format!(
+        "/enrollments/{enrollment_id}/practice/{practice_id}/decline?token={decline_token}"
+    ))
+    .await
+    .assert_status(StatusCode::OK);
+
+    // Clean up
+    delete_enrollment(&app, &enrollment_id).await;
+    delete_course(&app, &course_id).await;
+    delete_user(&app, &student_id).await;
+    delete_user(&app, &teacher_id).await;
+}
+
+#[tokio::test]
+pub async fn supervisor_token_is_revoked_when_supervisor_changes() {
+    let app = init_test_app().await;
+
+    let student_id = create_student(&app).await;
+    let teacher_id = create_teacher(&app).await;
+
+    let course_data = CourseBuilder::new(&teacher_id).build();
+
+    let course = create_course(&app, &course_data).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment_data = EnrollmentBuilder::new()
+        .with_student_id(&student_id)
+        .with_course_id(&course_id)
+        .build();
+
+    let enrollment = create_enrollment(&app, &enrollment_data).await;
+    let enrollment_id = extract_resource_id(&enrollment);
+
+    let practice_data = TestPractice::builder()
+        .with_enterprise_name("Test Enterprise Supervisor Link")
+        .with_description("Test Description Supervisor Link")
+        .with_location("Test Location Supervisor Link")
+        .with_supervisor_name("Test Supervisor Link")
+        .with_supervisor_phone("+56912345678")
+        .with_start_date("2024-09-01T00:00:00Z")
+        .with_end_date("2024-12-15T00:00:00Z")
+        .build();
+
+    let practice_id = TestPractice::create(&app, &enrollment_id, practice_data).await;
+
+    let token = supervisor_token(&practice_id, "approve").await;
+
+    app.patch(&format!("/enrollments/{enrollment_id}/practice"))
+        .json(&json!({ "supervisorEmail": generate_unique_email() }))
+        .await
+        .assert_status(StatusCode::OK);
+
+    // El enlace enviado al supervisor anterior ya no sirve
+    let route = format!("/enrollments/{enrollment_id}/practice/{practice_id}/approve");
+
+    app.post(&format!("{route}?token={token}"))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let token = supervisor_token(&practice_id, "approve").await;
+
+    app.post(&format!("{route}?token={token}")).await.assert_status(StatusCode::OK);
+
+    // Clean up
+    delete_enrollment(&app, &enrollment_id).await;
+    delete_course(&app, &course_id).await;
+    delete_user(&app, &student_id).await;
+    delete_user(&app, &teacher_id).await;
+}
+
 // #[tokio::test]
 // async fn create_and_delete_inscription_should_work() {
 //     let app = init_test_app().await;
@@ -100,5 +100,20 @@ async fn test_import_users() {
 
     assert_eq!(response.status_code(), 201);
 
+    let emails = students
+        .iter()
+        .filter_map(|s| s["email"].as_str().map(String::from))
+        .collect::<Vec<_>>();
+
+    let flagged: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM users WHERE email = ANY($1) AND must_change_password = TRUE",
+    )
+    .bind(&emails)
+    .fetch_one(&get_db_pool().await)
+    .await
+    .expect("Failed to count imported users");
+
+    assert_eq!(flagged, emails.len() as i64);
+
     cleanup_import_test_data(&course_id, &teacher_id).await;
 }
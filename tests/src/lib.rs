@@ -1,23 +1,39 @@
+use axum::http::{HeaderValue, header::AUTHORIZATION};
 use axum_test::TestServer;
-use serde_json::Value;
+use serde_json::{Value, json};
+use uuid::Uuid;
 
 use server::{
+    audit::AuditController,
+    auth::{
+        AuthController, InMemoryLoginAttemptRepository, InMemorySessionStore,
+        LoginAttemptRepository, SessionStore,
+    },
     imports::ImportsController,
     shared::services::{
         event_queue::{EventSubscriber, SubscriberOptions, TokioEventSender},
+        hasher::{BcryptPasswordHasher, PasswordHasher},
         mailer::{Mailer, MailerConfig},
         printer::Printer,
         templates::TemplateConfig,
     },
 };
 
-use sword::prelude::Application;
+use sword::{prelude::Application, web::ResponseBody};
 
+#[cfg(test)]
+pub mod audit;
+#[cfg(test)]
+pub mod auth;
+#[cfg(test)]
+pub mod authorization;
 #[cfg(test)]
 pub mod courses;
 #[cfg(test)]
 pub mod enrollments;
 #[cfg(test)]
+pub mod periods;
+#[cfg(test)]
 pub mod practices;
 #[cfg(test)]
 pub mod users;
@@ -46,13 +62,31 @@ pub static TEST_EMAILS: std::sync::LazyLock<std::collections::HashMap<String, St
     });
 
 use server::{
-    config::PostgresDbConfig, container::DependencyContainer, courses::CoursesController,
-    enrollments::EnrollmentsController, shared::database::PostgresDatabase, users::UsersController,
+    config::{AuthConfig, PostgresDbConfig},
+    container::DependencyContainer,
+    courses::CoursesController,
+    enrollments::EnrollmentsController,
+    periods::PeriodsController,
+    shared::database::PostgresDatabase,
+    users::{MeController, UsersController},
 };
 
+pub const TEST_ADMIN_EMAIL: &str = "administrator@test.cl";
+pub const TEST_ADMIN_PASSWORD: &str = "TestPassword123!";
+
+/// Parámetros de bloqueo de login reducidos para que las pruebas no esperen segundos
+pub const TEST_LOGIN_LOCKOUT_THRESHOLD: i32 = 5;
+pub const TEST_LOGIN_BACKOFF_BASE_MS: u64 = 200;
+
 use tokio::sync::mpsc;
 
 pub async fn init_test_app() -> TestServer {
+    init_test_app_with(|_| {}).await
+}
+
+/// Igual que `init_test_app`, permitiendo ajustar la configuración de
+/// autenticación antes de construir el contenedor.
+pub async fn init_test_app_with(configure: impl FnOnce(&mut AuthConfig)) -> TestServer {
     let mut app = Application::builder().expect("Failed to create application builder");
 
     let pg_db_config =
@@ -62,6 +96,21 @@ pub async fn init_test_app() -> TestServer {
 
     let tamplate_config = app.config.get::<TemplateConfig>().expect("Failed to get TemplateConfig");
 
+    let mut auth_config = app.config.get::<AuthConfig>().expect("Failed to get AuthConfig");
+
+    auth_config.login_lockout_threshold = TEST_LOGIN_LOCKOUT_THRESHOLD;
+    auth_config.login_backoff_base_ms = TEST_LOGIN_BACKOFF_BASE_MS;
+
+    // La verificación en dos pasos obligatoria se prueba explícitamente,
+    // de lo contrario el administrador de pruebas no podría operar.
+    auth_config.two_factor_required_roles = Vec::new();
+
+    // Las peticiones de prueba simulan pasar por el proxy reverso, que informa
+    // la IP del cliente en sus headers.
+    auth_config.trusted_proxy = true;
+
+    configure(&mut auth_config);
+
     let (db, mailer, printer) = {
         let db = PostgresDatabase::new(&pg_db_config)
             .await
@@ -69,10 +118,14 @@ pub async fn init_test_app() -> TestServer {
 
         db.migrate().await.expect("Failed to create database connection");
 
-        sqlx::query("TRUNCATE TABLE practices, enrollments, courses, users CASCADE")
-            .execute(&db.pool)
-            .await
-            .expect("Failed to truncate tables");
+        sqlx::query(
+            "TRUNCATE TABLE practices, enrollments, courses, academic_periods, users CASCADE",
+        )
+        .execute(&db.pool)
+        .await
+        .expect("Failed to truncate tables");
+
+        seed_test_administrator(&db).await;
 
         let mailer =
             Mailer::new(&mailer_config, &tamplate_config).expect("Failed to create mailer");
@@ -85,7 +138,14 @@ pub async fn init_test_app() -> TestServer {
     let (tx, rx) = mpsc::channel(100);
 
     let publisher = TokioEventSender::new(tx);
-    let dependency_container = DependencyContainer::new(db, publisher);
+
+    // Sesiones y contadores de intentos de login en memoria, aislados por servidor de pruebas
+    let module = DependencyContainer::builder(db, publisher, auth_config)
+        .with_component_override::<dyn SessionStore>(Box::new(InMemorySessionStore::default()))
+        .with_component_override::<dyn LoginAttemptRepository>(Box::new(
+            InMemoryLoginAttemptRepository::default(),
+        ))
+        .build();
 
     EventSubscriber::new(SubscriberOptions {
         rx,
@@ -96,14 +156,90 @@ pub async fn init_test_app() -> TestServer {
     .await;
 
     app = app
-        .di_module(dependency_container.module)
+        .di_module(module)
         .expect("Failed to load dependency module")
+        .controller::<AuthController>()
         .controller::<UsersController>()
+        .controller::<MeController>()
         .controller::<CoursesController>()
         .controller::<EnrollmentsController>()
-        .controller::<ImportsController>();
+        .controller::<PeriodsController>()
+        .controller::<ImportsController>()
+        .controller::<AuditController>();
+
+    let mut server = TestServer::new(app.router()).expect("Failed to start test server");
+
+    act_as(&mut server, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    server
+}
+
+async fn seed_test_administrator(db: &PostgresDatabase) {
+    let password = BcryptPasswordHasher::default()
+        .hash(TEST_ADMIN_PASSWORD)
+        .expect("Failed to hash test administrator password");
+
+    sqlx::query(
+        "INSERT INTO users (id, rut, name, email, password, roles, created_at)
+         VALUES ($1, $2, $3, $4, $5, ARRAY['administrator']::user_role[], NOW())",
+    )
+    .bind(Uuid::new_v4())
+    .bind("11111111-1")
+    .bind("Test Administrator")
+    .bind(TEST_ADMIN_EMAIL)
+    .bind(password)
+    .execute(&db.pool)
+    .await
+    .expect("Failed to seed test administrator");
+}
+
+/// Inicia sesión y retorna el cuerpo de la respuesta (tokens + usuario)
+pub async fn login(server: &TestServer, email: &str, password: &str) -> Value {
+    let response = server
+        .post("/auth/login")
+        .json(&json!({ "email": email, "password": password }))
+        .await;
+
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 200, "Failed to login as {email}: {}", body.data);
+
+    body.data
+}
+
+/// Reemplaza la sesión usada por defecto en todas las peticiones del servidor de pruebas
+pub fn set_session(server: &mut TestServer, session_token: &str) {
+    let value = HeaderValue::from_str(&format!("Bearer {session_token}"))
+        .expect("Invalid session token header");
+
+    server.clear_headers();
+    server.add_header(AUTHORIZATION, value);
+}
+
+pub async fn act_as(server: &mut TestServer, email: &str, password: &str) -> Value {
+    let session = login(server, email, password).await;
+
+    let token = session
+        .get("sessionToken")
+        .and_then(|token| token.as_str())
+        .expect("Login response does not contain 'sessionToken'");
+
+    set_session(server, token);
+
+    session
+}
+
+/// Conexión directa a la base de datos de pruebas, para preparar datos que
+/// no pueden obtenerse a través de la API (ej: tokens enviados por correo)
+pub async fn test_database() -> PostgresDatabase {
+    let app = Application::builder().expect("Failed to create application builder");
+
+    let pg_db_config =
+        app.config.get::<PostgresDbConfig>().expect("Failed to get PostgresDbConfig");
 
-    TestServer::new(app.router()).expect("Failed to start test server")
+    PostgresDatabase::new(&pg_db_config)
+        .await
+        .expect("Failed to create database connection")
 }
 
 pub fn extract_resource_id(data: &Value) -> String {
@@ -0,0 +1,185 @@
+pub mod utils;
+
+use crate::{
+    authorization::utils::TestAccount,
+    courses::utils::{CourseBuilder, create_course, list_courses, set_scores},
+    enrollments::utils::{EnrollmentBuilder, create_enrollment},
+    extract_resource_id, init_test_app,
+    practices::utils::TestPractice,
+    users::utils::{create_student, create_teacher},
+};
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sword::web::ResponseBody;
+use utils::*;
+
+#[tokio::test]
+async fn test_create_period_validates_dates() {
+    let app = init_test_app().await;
+    let now = Utc::now();
+
+    let period = PeriodBuilder::new().build();
+    let created = create_period(&app, &period).await;
+    let period_id = extract_resource_id(&created);
+
+    assert_eq!(created["year"], period["year"]);
+    assert_eq!(created["term"], period["term"]);
+
+    // Solo un periodo por año y semestre
+    assert_eq!(app.post("/periods").json(&period).await.status_code(), 409);
+
+    let mut invalid_term = PeriodBuilder::new().build();
+    invalid_term["term"] = json!(3);
+    assert_eq!(app.post("/periods").json(&invalid_term).await.status_code(), 400);
+
+    let late_enrollment =
+        PeriodBuilder::new().with_enrollment_deadline(now + Duration::days(120)).build();
+    assert_eq!(app.post("/periods").json(&late_enrollment).await.status_code(), 400);
+
+    let early_grades = PeriodBuilder::new().with_grades_deadline(now + Duration::days(60)).build();
+    assert_eq!(app.post("/periods").json(&early_grades).await.status_code(), 400);
+
+    let response = app.get(&format!("/periods?year={}", period["year"])).await;
+    assert_eq!(response.status_code(), 200);
+
+    let periods = response.json::<ResponseBody>().data;
+    assert!(
+        periods
+            .as_array()
+            .is_some_and(|periods| periods.iter().any(|p| p["id"] == period_id))
+    );
+
+    let route = format!("/periods/{period_id}");
+
+    let response = app.patch(&route).json(&finished_period_dates()).await;
+    assert_eq!(response.status_code(), 200);
+
+    // El plazo de notas quedaría antes del término del periodo
+    let response = app.patch(&route).json(&json!({ "endDate": now.to_rfc3339() })).await;
+    assert_eq!(response.status_code(), 400);
+
+    assert_eq!(app.delete(&route).await.status_code(), 200);
+    assert_eq!(app.get(&route).await.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_filter_courses_and_enrollments_by_period() {
+    let app = init_test_app().await;
+    let teacher_id = create_teacher(&app).await;
+
+    let period = create_period(&app, &PeriodBuilder::new().build()).await;
+    let period_id = extract_resource_id(&period);
+    let year = period["year"].as_i64().unwrap() as i32;
+
+    // El curso debe ser del mismo año que el periodo
+    let mut mismatched = CourseBuilder::new(&teacher_id).with_year(year % 2100 + 1).build();
+    mismatched["periodId"] = json!(period_id);
+    assert_eq!(app.post("/courses").json(&mismatched).await.status_code(), 400);
+
+    let mut in_period = CourseBuilder::new(&teacher_id).with_year(year).build();
+    in_period["periodId"] = json!(period_id);
+
+    let in_period = create_course(&app, &in_period).await;
+    let in_period_id = extract_resource_id(&in_period);
+    assert_eq!(in_period["periodId"], period_id);
+
+    let other = create_course(&app, &CourseBuilder::new(&teacher_id).with_year(year).build()).await;
+    let other_id = extract_resource_id(&other);
+
+    let listing = list_courses(&app, &format!("periodId={period_id}")).await;
+    let courses = listing["courses"].as_array().unwrap();
+
+    assert_eq!(courses.len(), 1);
+    assert_eq!(courses[0]["id"], in_period_id);
+
+    let student_id = create_student(&app).await;
+
+    for course_id in [&in_period_id, &other_id] {
+        let enrollment = EnrollmentBuilder::new()
+            .with_student_id(&student_id)
+            .with_course_id(course_id)
+            .build();
+        create_enrollment(&app, &enrollment).await;
+    }
+
+    let response = app.get(&format!("/enrollments?studentId={student_id}")).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data.as_array().map(Vec::len), Some(2));
+
+    let response = app.get(&format!("/enrollments?periodId={period_id}")).await;
+    assert_eq!(response.status_code(), 200);
+
+    let enrollments = response.json::<ResponseBody>().data;
+    let enrollments = enrollments.as_array().unwrap();
+
+    assert_eq!(enrollments.len(), 1);
+    assert_eq!(enrollments[0]["courseId"], in_period_id);
+
+    // Un periodo con cursos no se puede eliminar
+    let response = app.delete(&format!("/periods/{period_id}")).await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_period_deadlines_and_practice_dates() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let now = Utc::now();
+
+    let period = create_period(&app, &PeriodBuilder::new().build()).await;
+    let period_id = extract_resource_id(&period);
+
+    let mut course = CourseBuilder::new(&teacher.id)
+        .with_year(period["year"].as_i64().unwrap() as i32)
+        .build();
+    course["periodId"] = json!(period_id);
+
+    let course = create_course(&app, &course).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&create_student(&app).await)
+        .with_course_id(&course_id)
+        .build();
+    let enrollment_id = extract_resource_id(&create_enrollment(&app, &enrollment).await);
+
+    // La práctica debe comenzar dentro del periodo
+    let practice = |start: chrono::DateTime<Utc>, end: chrono::DateTime<Utc>| {
+        TestPractice::builder()
+            .with_enterprise_name("Empresa Test")
+            .with_description("Descripción de prueba")
+            .with_location("Ubicación de prueba")
+            .with_supervisor_name("Supervisor Test")
+            .with_supervisor_phone("+56912345678")
+            .with_start_date(&start.to_rfc3339())
+            .with_end_date(&end.to_rfc3339())
+            .build()
+    };
+
+    let route = format!("/enrollments/{enrollment_id}/practice");
+
+    let early = practice(now - Duration::days(60), now + Duration::days(30));
+    assert_eq!(app.post(&route).json(&early).await.status_code(), 400);
+
+    let late = practice(now, now + Duration::days(200));
+    assert_eq!(app.post(&route).json(&late).await.status_code(), 400);
+
+    TestPractice::create(&app, &enrollment_id, practice(now, now + Duration::days(60))).await;
+
+    // Con los plazos vencidos no se inscribe ni se califica
+    let response = app.patch(&format!("/periods/{period_id}")).json(&finished_period_dates()).await;
+    assert_eq!(response.status_code(), 200);
+
+    let late_enrollment = EnrollmentBuilder::new()
+        .with_student_id(&create_student(&app).await)
+        .with_course_id(&course_id)
+        .build();
+    let response = app.post("/courses/enroll").json(&late_enrollment).await;
+    assert_eq!(response.status_code(), 400);
+
+    teacher.act(&mut app).await;
+
+    let response = set_scores(&app, &course, &enrollment_id, &[6.0, 5.0]).await;
+    assert_eq!(response.status_code(), 400);
+}
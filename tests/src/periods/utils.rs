@@ -0,0 +1,71 @@
+use axum_test::TestServer;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+use sword::web::ResponseBody;
+
+pub struct PeriodBuilder {
+    year: i32,
+    term: i32,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    enrollment_deadline: DateTime<Utc>,
+    grades_deadline: DateTime<Utc>,
+}
+
+impl PeriodBuilder {
+    /// Periodo en curso, con un año al azar para no chocar con otras pruebas.
+    pub fn new() -> Self {
+        let now = Utc::now();
+
+        Self {
+            year: rand::random_range(2000..=2100),
+            term: rand::random_range(1..=2),
+            start_date: now - Duration::days(30),
+            end_date: now + Duration::days(90),
+            enrollment_deadline: now + Duration::days(10),
+            grades_deadline: now + Duration::days(100),
+        }
+    }
+
+    pub fn with_enrollment_deadline(mut self, deadline: DateTime<Utc>) -> Self {
+        self.enrollment_deadline = deadline;
+        self
+    }
+
+    pub fn with_grades_deadline(mut self, deadline: DateTime<Utc>) -> Self {
+        self.grades_deadline = deadline;
+        self
+    }
+
+    pub fn build(self) -> Value {
+        json!({
+            "year": self.year,
+            "term": self.term,
+            "startDate": self.start_date.to_rfc3339(),
+            "endDate": self.end_date.to_rfc3339(),
+            "enrollmentDeadline": self.enrollment_deadline.to_rfc3339(),
+            "gradesDeadline": self.grades_deadline.to_rfc3339(),
+        })
+    }
+}
+
+pub async fn create_period(server: &TestServer, period: &Value) -> Value {
+    let response = server.post("/periods").json(period).await;
+    let body = response.json::<ResponseBody>();
+
+    assert_eq!(response.status_code(), 201, "Failed to create period: {}", body.data);
+
+    body.data
+}
+
+/// Fechas de un periodo ya terminado, con ambos plazos vencidos.
+pub fn finished_period_dates() -> Value {
+    let now = Utc::now();
+
+    json!({
+        "startDate": (now - Duration::days(200)).to_rfc3339(),
+        "endDate": (now - Duration::days(30)).to_rfc3339(),
+        "enrollmentDeadline": (now - Duration::days(100)).to_rfc3339(),
+        "gradesDeadline": (now - Duration::days(10)).to_rfc3339(),
+    })
+}
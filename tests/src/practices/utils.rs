@@ -3,10 +3,13 @@ use axum_test::{
     TestServer,
     multipart::{MultipartForm, Part},
 };
+use chrono::{Duration, Utc};
 use serde_json::{Value, json};
+use server::shared::services::hasher::{BcryptPasswordHasher, PasswordHasher};
 use sword::web::ResponseBody;
+use uuid::Uuid;
 
-use crate::{TEST_EMAILS, extract_resource_id, users::utils::generate_unique_email};
+use crate::{TEST_EMAILS, extract_resource_id, test_database, users::utils::generate_unique_email};
 
 pub struct PracticeBuilder {
     pub enterprise_name: Option<String>,
@@ -105,14 +108,22 @@ impl TestPractice {
     }
 
     pub async fn approve(app: &TestServer, enrollment_id: &String, practice_id: &String) {
-        let route = format!("/enrollments/{}/practice/{}/approve", enrollment_id, practice_id);
+        let token = supervisor_token(practice_id, "approve").await;
+        let route = format!(
+            "/enrollments/{}/practice/{}/approve?token={}",
+            enrollment_id, practice_id, token
+        );
         let response = app.post(&route).await;
 
         assert_eq!(response.status_code(), 200, "Failed to approve practice");
     }
 
     pub async fn authorize(app: &TestServer, enrollment_id: &String, practice_id: &String) {
-        let route = format!("/enrollments/{}/practice/{}/authorize", enrollment_id, practice_id);
+        let token = supervisor_token(practice_id, "authorize").await;
+        let route = format!(
+            "/enrollments/{}/practice/{}/authorize?token={}",
+            enrollment_id, practice_id, token
+        );
         let pdf_part =
             Part::bytes(include_bytes!("../../files/Autorización de práctica.pdf").as_slice())
                 .file_name(&"auth_doc.pdf")
@@ -130,3 +141,48 @@ impl TestPractice {
             .assert_status(StatusCode::NO_CONTENT);
     }
 }
+
+/// Emite directamente en la base de datos un token de supervisor para la acción
+/// indicada, ya que el secreto real sólo viaja en los correos enviados.
+pub async fn insert_supervisor_token(
+    practice_id: &str,
+    action: &str,
+    expires_in: Duration,
+) -> String {
+    let db = test_database().await;
+
+    let id = Uuid::new_v4();
+    let secret = Uuid::new_v4().simple().to_string();
+    let hash = BcryptPasswordHasher::default()
+        .hash(&secret)
+        .expect("Failed to hash supervisor token");
+
+    sqlx::query(
+        "INSERT INTO supervisor_tokens (id, practice_id, action, token_hash, expires_at, created_at)
+         VALUES ($1, $2, $3::supervisor_action, $4, $5, NOW())",
+    )
+    .bind(id)
+    .bind(Uuid::parse_str(practice_id).expect("Invalid practice id"))
+    .bind(action)
+    .bind(hash)
+    .bind(Utc::now() + expires_in)
+    .execute(&db.pool)
+    .await
+    .expect("Failed to insert supervisor token");
+
+    format!("{id}.{secret}")
+}
+
+pub async fn supervisor_token(practice_id: &str, action: &str) -> String {
+    insert_supervisor_token(practice_id, action, Duration::days(1)).await
+}
+
+pub async fn practice_exists(practice_id: &str) -> bool {
+    let db = test_database().await;
+
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM practices WHERE id = $1)")
+        .bind(Uuid::parse_str(practice_id).expect("Invalid practice id"))
+        .fetch_one(&db.pool)
+        .await
+        .expect("Failed to check practice")
+}
@@ -1,9 +1,20 @@
 pub mod utils;
 
+use chrono::Duration;
 use serde_json::json;
 use sword::web::ResponseBody;
-
-use crate::init_test_app;
+use uuid::Uuid;
+
+use crate::{
+    TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD, act_as,
+    audit::utils::audit_entries,
+    auth::utils::{refresh, token},
+    authorization::utils::{ACCOUNT_PASSWORD, TestAccount},
+    courses::utils::{CourseBuilder, create_course, list_courses},
+    enrollments::utils::{EnrollmentBuilder, create_enrollment},
+    extract_resource_id, init_test_app, login,
+    practices::utils::{TestPractice, practice_exists},
+};
 
 use utils::*;
 
@@ -42,14 +53,10 @@ async fn test_update_user() {
 
     assert_eq!(update_response.status_code(), 200);
 
+    // El nuevo email queda pendiente hasta que se confirme
     let updated_body = update_response.json::<ResponseBody>();
-    let updated_user_email = updated_body
-        .data
-        .get("email")
-        .and_then(|name| name.as_str())
-        .expect("Updated user name should be present");
-
-    assert_eq!(updated_user_email, new_email);
+    assert_eq!(updated_body.data["email"], body["email"]);
+    assert_eq!(pending_email_changes(user_id).await, vec![new_email]);
     delete_user(&app, user_id).await;
 }
 
@@ -58,7 +65,7 @@ async fn test_update_user() {
 #[tokio::test]
 async fn test_create_user_invalid_rut_format() {
     let app = init_test_app().await;
-    let new_user = UserBuilder::new().with_rut("34108499").build();
+    let new_user = UserBuilder::new().with_rut("34.108.49A-9").build();
 
     let response = app.post("/users").json(&new_user).await;
 
@@ -74,6 +81,28 @@ async fn test_create_user_invalid_rut_verification_digit() {
     assert_eq!(response.status_code(), 400);
 }
 
+#[tokio::test]
+async fn test_create_user_normalizes_rut() {
+    let app = init_test_app().await;
+    let rut = generate_unique_rut();
+    let (number, dv) = rut.split_once('-').unwrap();
+
+    // 12345678-5 -> 12.345.678-5
+    let (millions, rest) = number.split_at(number.len() - 6);
+    let formatted = format!("{millions}.{}.{} - {}", &rest[..3], &rest[3..], dv.to_lowercase());
+
+    let body = create_user(&app, UserBuilder::new().with_rut(&formatted).build()).await;
+    assert_eq!(body["rut"], rut);
+
+    let users = search_users(&app, &formatted.replace(' ', ""), false).await;
+    assert_eq!(users.len(), 1);
+
+    let response = app.post("/users").json(&UserBuilder::new().with_rut(&rut).build()).await;
+    assert_eq!(response.status_code(), 409);
+
+    delete_user(&app, &extract_resource_id(&body)).await;
+}
+
 #[tokio::test]
 async fn test_create_user_name_too_short() {
     let app = init_test_app().await;
@@ -372,3 +401,423 @@ async fn test_create_user_extract_json() {
 
     delete_user(&app, user_id).await;
 }
+
+// ==================== SOFT DELETE ====================
+
+#[tokio::test]
+async fn test_deleted_users_are_hidden_unless_requested() {
+    let mut app = init_test_app().await;
+    let email = generate_unique_email();
+    let body = create_user(&app, UserBuilder::new().with_email(&email).build()).await;
+    let user_id = extract_resource_id(&body);
+
+    delete_user(&app, &user_id).await;
+
+    assert!(search_users(&app, &email, false).await.is_empty());
+    assert_eq!(app.get(&format!("/users/{user_id}")).await.status_code(), 404);
+
+    let users = search_users(&app, &email, true).await;
+    assert_eq!(users.len(), 1);
+    assert!(users[0]["deleted_at"].is_string());
+
+    let secretary = TestAccount::create(&app, "secretary").await;
+    secretary.act(&mut app).await;
+
+    let response = app.get(&format!("/users?search={email}&includeDeleted=true")).await;
+    assert_eq!(response.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_deleted_user_credentials_can_be_reused() {
+    let app = init_test_app().await;
+    let user = UserBuilder::new().build();
+    let user_id = extract_resource_id(&create_user(&app, user.clone()).await);
+
+    delete_user(&app, &user_id).await;
+
+    let replacement = extract_resource_id(&create_user(&app, user).await);
+
+    // El email ya pertenece a otro usuario activo
+    let response = app.post(&format!("/users/{user_id}/restore")).await;
+    assert_eq!(response.status_code(), 409);
+
+    delete_user(&app, &replacement).await;
+
+    let response = app.post(&format!("/users/{user_id}/restore")).await;
+    assert_eq!(response.status_code(), 200);
+    assert!(response.json::<ResponseBody>().data["deleted_at"].is_null());
+
+    let response = app.post(&format!("/users/{user_id}/restore")).await;
+    assert_eq!(response.status_code(), 400);
+
+    delete_user(&app, &user_id).await;
+}
+
+#[tokio::test]
+async fn test_purge_anonymizes_deleted_user() {
+    let app = init_test_app().await;
+    let email = generate_unique_email();
+    let body = create_user(&app, UserBuilder::new().with_email(&email).build()).await;
+    let user_id = extract_resource_id(&body);
+
+    let response = app.delete(&format!("/users/{user_id}/purge")).await;
+    assert_eq!(response.status_code(), 400);
+
+    delete_user(&app, &user_id).await;
+
+    let response = app.delete(&format!("/users/{user_id}/purge")).await;
+    assert_eq!(response.status_code(), 200);
+
+    assert!(search_users(&app, &email, true).await.is_empty());
+
+    let response = app.post(&format!("/users/{user_id}/restore")).await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_purge_scrubs_personal_data_from_audit_log() {
+    let app = init_test_app().await;
+    let email = generate_unique_email();
+    let name = format!("Purgado {}", Uuid::new_v4().simple());
+    let user = UserBuilder::new().with_email(&email).with_name(&name).build();
+    let user_id = extract_resource_id(&create_user(&app, user).await);
+
+    let response = app
+        .put(&format!("/users/{user_id}"))
+        .json(&json!({ "roles": ["teacher"] }))
+        .await;
+    assert_eq!(response.status_code(), 200);
+
+    delete_user(&app, &user_id).await;
+
+    let response = app.delete(&format!("/users/{user_id}/purge")).await;
+    assert_eq!(response.status_code(), 200);
+
+    // Los registros se conservan, pero sin los datos personales
+    let entries = audit_entries(&app, &format!("entityId={user_id}")).await;
+    let actions = entries.iter().map(|e| e["action"].as_str().unwrap()).collect::<Vec<_>>();
+
+    assert_eq!(actions, vec!["user.purge", "user.delete", "user.update", "user.create"]);
+
+    let serialized = json!(entries).to_string();
+    assert!(!serialized.contains(&email));
+    assert!(!serialized.contains(&name));
+}
+
+// ==================== LISTING ====================
+
+#[tokio::test]
+async fn test_get_users_filters_sorts_and_paginates() {
+    let app = init_test_app().await;
+    let tag = Uuid::new_v4().simple().to_string();
+
+    let mut ids = vec![];
+    for (name, role) in [
+        ("Carla", "teacher"),
+        ("Ana", "student"),
+        ("Beatriz", "teacher"),
+    ] {
+        let user = UserBuilder::new().with_name(&format!("{name} {tag}")).with_roles(vec![role]);
+        ids.push(extract_resource_id(&create_user(&app, user.build()).await));
+    }
+
+    let response = app
+        .get(&format!("/users?search={tag}&roles=teacher&sortBy=name&order=asc&perPage=1"))
+        .await;
+    assert_eq!(response.status_code(), 200);
+
+    let data = response.json::<ResponseBody>().data;
+    assert_eq!(data["total"], 2);
+    assert_eq!(data["totalPages"], 2);
+    assert_eq!(data["hasNext"], true);
+    assert_eq!(data["users"][0]["name"], format!("Beatriz {tag}"));
+
+    let response = app.get(&format!("/users?search={tag}&sortBy=name&order=desc")).await;
+    let data = response.json::<ResponseBody>().data;
+    assert_eq!(data["total"], 3);
+    assert_eq!(data["users"][0]["name"], format!("Carla {tag}"));
+
+    for id in ids {
+        delete_user(&app, &id).await;
+    }
+}
+
+#[tokio::test]
+async fn test_get_users_rejects_invalid_query() {
+    let app = init_test_app().await;
+
+    for query in [
+        "perPage=101",
+        "perPage=0",
+        "roles=student,wizard",
+        "sortBy=password",
+        "createdFrom=2025-02-01T00:00:00Z&createdTo=2025-01-01T00:00:00Z",
+    ] {
+        let response = app.get(&format!("/users?{query}")).await;
+        assert_eq!(response.status_code(), 400, "{query}");
+    }
+}
+
+// ==================== PROFILE ====================
+
+#[tokio::test]
+async fn test_update_own_profile() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let other_session = login(&app, &account.email, ACCOUNT_PASSWORD).await;
+
+    account.act(&mut app).await;
+
+    let response = app.get("/me").await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["id"], account.id);
+
+    let response = app.patch("/me").json(&json!({ "name": "Nombre Actualizado" })).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["name"], "Nombre Actualizado");
+
+    // El email y la contraseña exigen confirmar la contraseña actual
+    let email = generate_unique_email();
+    let response = app.patch("/me").json(&json!({ "email": email })).await;
+    assert_eq!(response.status_code(), 400);
+
+    let input = json!({ "email": email, "currentPassword": "WrongPassword123!" });
+    assert_eq!(app.patch("/me").json(&input).await.status_code(), 400);
+
+    let input = json!({
+        "password": "NewPassword456!",
+        "confirmPassword": "NewPassword456!",
+        "currentPassword": ACCOUNT_PASSWORD,
+    });
+    assert_eq!(app.patch("/me").json(&input).await.status_code(), 200);
+
+    // La sesión actual continúa abierta, las demás se cierran
+    assert_eq!(app.get("/me").await.status_code(), 200);
+
+    let response = refresh(&app, &token(&other_session, "refreshToken")).await;
+    assert_eq!(response.status_code(), 401);
+
+    login(&app, &account.email, "NewPassword456!").await;
+}
+
+#[tokio::test]
+async fn test_email_change_requires_confirmation() {
+    let mut app = init_test_app().await;
+    let account = TestAccount::create(&app, "student").await;
+    let new_email = generate_unique_email();
+
+    account.act(&mut app).await;
+
+    let input = json!({ "email": new_email, "currentPassword": ACCOUNT_PASSWORD });
+    let response = app.patch("/me").json(&input).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["email"], account.email);
+    assert_eq!(pending_email_changes(&account.id).await, vec![new_email.clone()]);
+
+    let expired = insert_email_change(&account.id, &new_email, Duration::hours(-1)).await;
+    assert_eq!(confirm_email_change(&app, &expired).await.status_code(), 400);
+
+    let token = insert_email_change(&account.id, &new_email, Duration::hours(1)).await;
+    let (id, _) = token.split_once('.').unwrap();
+
+    let response = confirm_email_change(&app, &format!("{id}.wrong-secret")).await;
+    assert_eq!(response.status_code(), 400);
+
+    let response = confirm_email_change(&app, &token).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.json::<ResponseBody>().data["email"], new_email);
+
+    assert_eq!(confirm_email_change(&app, &token).await.status_code(), 400);
+
+    login(&app, &new_email, ACCOUNT_PASSWORD).await;
+}
+
+#[tokio::test]
+async fn test_own_enrollments_are_listed() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&teacher.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    let enrollment = EnrollmentBuilder::new()
+        .with_student_id(&student.id)
+        .with_course_id(&course_id)
+        .build();
+    create_enrollment(&app, &enrollment).await;
+
+    student.act(&mut app).await;
+
+    let response = app.get("/me/enrollments").await;
+    assert_eq!(response.status_code(), 200);
+
+    let enrollments = response.json::<ResponseBody>().data;
+    assert_eq!(enrollments.as_array().map(Vec::len), Some(1));
+    assert_eq!(enrollments[0]["course"]["id"], course_id);
+
+    teacher.act(&mut app).await;
+
+    let response = app.get("/me/enrollments").await;
+    assert_eq!(response.json::<ResponseBody>().data.as_array().map(Vec::len), Some(0));
+}
+
+#[tokio::test]
+async fn test_merge_users_combines_enrollments() {
+    let mut app = init_test_app().await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+    let source = TestAccount::create(&app, "student").await;
+    let target = TestAccount::create(&app, "student").await;
+
+    let shared_course = CourseBuilder::new(&teacher.id)
+        .with_evaluations(vec![("Bitácoras Semanales", 50), ("Informe Final", 50)])
+        .build();
+    let shared_course = create_course(&app, &shared_course).await;
+    let shared_course_id = extract_resource_id(&shared_course);
+    let other_course_id =
+        extract_resource_id(&create_course(&app, &CourseBuilder::new(&teacher.id).build()).await);
+
+    let enroll = |student_id: &String, course_id: &String| {
+        EnrollmentBuilder::new()
+            .with_student_id(student_id)
+            .with_course_id(course_id)
+            .build()
+    };
+
+    let source_shared =
+        extract_resource_id(&create_enrollment(&app, &enroll(&source.id, &shared_course_id)).await);
+    let target_shared =
+        extract_resource_id(&create_enrollment(&app, &enroll(&target.id, &shared_course_id)).await);
+    let source_other =
+        extract_resource_id(&create_enrollment(&app, &enroll(&source.id, &other_course_id)).await);
+
+    let evaluations = shared_course["evaluations"].as_array().expect("Evaluations expected");
+    let (first, second) = (&evaluations[0]["id"], &evaluations[1]["id"]);
+
+    let mut practice_ids = vec![];
+
+    for (enrollment_id, enterprise) in [
+        (&source_shared, "Empresa Origen"),
+        (&target_shared, "Empresa Destino"),
+    ] {
+        let practice = TestPractice::builder()
+            .with_enterprise_name(enterprise)
+            .with_description("Práctica registrada en ambas cuentas")
+            .with_location("Temuco")
+            .with_supervisor_name("Supervisor de Prueba")
+            .with_supervisor_phone("+56912345678")
+            .with_start_date("2024-09-01T00:00:00Z")
+            .with_end_date("2024-12-15T00:00:00Z")
+            .build();
+
+        practice_ids.push(TestPractice::create(&app, enrollment_id, practice).await);
+    }
+
+    let (source_practice, target_practice) = (&practice_ids[0], &practice_ids[1]);
+
+    teacher.act(&mut app).await;
+
+    let source_scores = json!({ "studentScores": [
+        { "evaluationId": first, "score": 5.0 },
+        { "evaluationId": second, "score": 5.0 },
+    ]});
+    let target_scores = json!({ "studentScores": [{ "evaluationId": first, "score": 6.0 }] });
+
+    for (enrollment_id, scores) in [
+        (&source_shared, source_scores),
+        (&target_shared, target_scores),
+    ] {
+        let route = format!("/courses/enrollments/{enrollment_id}");
+        assert_eq!(app.patch(&route).json(&scores).await.status_code(), 200);
+    }
+
+    act_as(&mut app, TEST_ADMIN_EMAIL, TEST_ADMIN_PASSWORD).await;
+
+    let response = app
+        .post(&format!("/users/{}/merge", target.id))
+        .json(&json!({ "sourceId": source.id }))
+        .await;
+    assert_eq!(response.status_code(), 200);
+
+    let report = response.json::<ResponseBody>().data;
+    assert_eq!(report["movedEnrollments"], json!([source_other]));
+
+    let combined = &report["combinedEnrollments"][0];
+    assert_eq!(combined["enrollmentId"], target_shared);
+    assert_eq!(combined["discardedEnrollmentId"], source_shared);
+    assert_eq!(combined["practiceId"], *target_practice);
+    assert_eq!(combined["discardedPracticeId"], *source_practice);
+
+    // La práctica reemplazada se elimina junto a su inscripción
+    assert!(!practice_exists(source_practice).await);
+    assert!(practice_exists(target_practice).await);
+
+    let chosen = combined["scores"]
+        .as_array()
+        .expect("Scores expected")
+        .iter()
+        .map(|choice| (choice["evaluationId"].clone(), choice["chosen"].as_f64()))
+        .collect::<Vec<_>>();
+    assert_eq!(chosen, vec![(first.clone(), Some(6.0)), (second.clone(), Some(5.0))]);
+
+    assert!(search_users(&app, &source.email, false).await.is_empty());
+
+    // Los registros de la cuenta absorbida se encuentran a través del destino
+    let created = audit_entries(&app, &format!("action=user.create&entityId={}", target.id)).await;
+    assert_eq!(created.len(), 2);
+
+    target.act(&mut app).await;
+
+    let enrollments = app.get("/me/enrollments").await.json::<ResponseBody>().data;
+    assert_eq!(enrollments.as_array().map(Vec::len), Some(2));
+}
+
+#[tokio::test]
+async fn test_merge_users_rejects_invalid_requests() {
+    let mut app = init_test_app().await;
+    let source = TestAccount::create(&app, "student").await;
+    let target = TestAccount::create(&app, "student").await;
+
+    let merge = |target_id: &str, source_id: &str| {
+        (format!("/users/{target_id}/merge"), json!({ "sourceId": source_id }))
+    };
+
+    let (route, body) = merge(&target.id, &target.id);
+    assert_eq!(app.post(&route).json(&body).await.status_code(), 400);
+
+    let (route, body) = merge(&target.id, &Uuid::new_v4().to_string());
+    assert_eq!(app.post(&route).json(&body).await.status_code(), 404);
+
+    target.act(&mut app).await;
+
+    let (route, body) = merge(&target.id, &source.id);
+    assert_eq!(app.post(&route).json(&body).await.status_code(), 403);
+}
+
+#[tokio::test]
+async fn test_merge_users_requires_roles_for_taken_over_courses() {
+    let app = init_test_app().await;
+    let source = TestAccount::create(&app, "teacher").await;
+    let student = TestAccount::create(&app, "student").await;
+    let teacher = TestAccount::create(&app, "teacher").await;
+
+    let course = create_course(&app, &CourseBuilder::new(&source.id).build()).await;
+    let course_id = extract_resource_id(&course);
+
+    // Un estudiante no puede quedar a cargo del curso de la cuenta absorbida
+    let response = app
+        .post(&format!("/users/{}/merge", student.id))
+        .json(&json!({ "sourceId": source.id }))
+        .await;
+    assert_eq!(response.status_code(), 400);
+    assert!(!search_users(&app, &source.email, false).await.is_empty());
+
+    let response = app
+        .post(&format!("/users/{}/merge", teacher.id))
+        .json(&json!({ "sourceId": source.id }))
+        .await;
+    assert_eq!(response.status_code(), 200);
+
+    let page = list_courses(&app, &format!("teacherId={}", teacher.id)).await;
+    assert_eq!(page["courses"][0]["id"], course_id);
+}
@@ -1,9 +1,11 @@
-use axum_test::TestServer;
+use axum_test::{TestResponse, TestServer};
+use chrono::{Duration, Utc};
 use serde_json::{Value, json};
+use server::shared::services::hasher::{BcryptPasswordHasher, PasswordHasher};
 use sword::web::ResponseBody;
 use uuid::Uuid;
 
-use crate::{TEST_EMAILS, extract_resource_id};
+use crate::{TEST_EMAILS, extract_resource_id, test_database};
 
 pub struct UserBuilder {
     rut: String,
@@ -97,6 +99,13 @@ pub async fn create_administrator(server: &TestServer) -> String {
     extract_resource_id(&data)
 }
 
+pub async fn create_coordinator(server: &TestServer) -> String {
+    let user = UserBuilder::new().with_roles(vec!["coordinator"]).build();
+    let data = create_user(server, user).await;
+
+    extract_resource_id(&data)
+}
+
 pub async fn create_student(server: &TestServer) -> String {
     let email = TEST_EMAILS.get("student").cloned();
 
@@ -154,3 +163,59 @@ fn calculate_rut_verification_digit(rut: u32) -> char {
         n => char::from_digit(n as u32, 10).unwrap(),
     }
 }
+
+/// Usuarios listados por `GET /users` para la búsqueda indicada.
+pub async fn search_users(server: &TestServer, search: &str, include_deleted: bool) -> Vec<Value> {
+    let response = server
+        .get(&format!("/users?search={search}&includeDeleted={include_deleted}"))
+        .await;
+
+    assert_eq!(response.status_code(), 200);
+
+    let body = response.json::<ResponseBody>();
+    body.data["users"].as_array().cloned().unwrap_or_default()
+}
+
+/// Emails pendientes de confirmación del usuario.
+pub async fn pending_email_changes(user_id: &str) -> Vec<String> {
+    let db = test_database().await;
+
+    sqlx::query_scalar(
+        "SELECT new_email FROM email_change_requests WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+    .fetch_all(&db.pool)
+    .await
+    .expect("Failed to fetch email changes")
+}
+
+/// Inserta un cambio de email con un token conocido, equivalente al enviado
+/// por correo a la nueva dirección, y retorna su forma `<id>.<secreto>`.
+pub async fn insert_email_change(user_id: &str, email: &str, expires_in: Duration) -> String {
+    let db = test_database().await;
+
+    let id = Uuid::new_v4();
+    let secret = Uuid::new_v4().simple().to_string();
+    let hash = BcryptPasswordHasher::default()
+        .hash(&secret)
+        .expect("Failed to hash email change token");
+
+    sqlx::query(
+        "INSERT INTO email_change_requests (id, user_id, new_email, token_hash, expires_at, created_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())",
+    )
+    .bind(id)
+    .bind(Uuid::parse_str(user_id).expect("Invalid user id"))
+    .bind(email)
+    .bind(hash)
+    .bind(Utc::now() + expires_in)
+    .execute(&db.pool)
+    .await
+    .expect("Failed to insert email change");
+
+    format!("{id}.{secret}")
+}
+
+pub async fn confirm_email_change(server: &TestServer, token: &str) -> TestResponse {
+    server.post("/users/email/confirm").json(&json!({ "token": token })).await
+}